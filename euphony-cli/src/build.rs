@@ -1,10 +1,39 @@
-use crate::{compiler::Compiler, manifest::Manifest, Result};
+use crate::{compiler::Compiler, manifest::Manifest, progress, Result};
+use euphony_command::asm;
+use euphony_compiler::{sample::SampleRate, Processors};
+use euphony_store::Format;
 use std::{fs, io, path::PathBuf};
 use structopt::StructOpt;
 
-#[derive(Debug, Default, StructOpt)]
+#[derive(Clone, Debug, Default, StructOpt)]
 pub struct Build {
     input: Option<PathBuf>,
+
+    /// The output sample rate, in Hz
+    #[structopt(long, parse(try_from_str = parse_sample_rate))]
+    sample_rate: Option<SampleRate>,
+
+    /// Compresses newly rendered sinks
+    ///
+    /// Sinks that were already rendered are kept in the format they were written in.
+    #[structopt(long)]
+    compress: bool,
+
+    /// The URL of a shared store to cache the contents of
+    ///
+    /// Sinks that were already rendered on another machine are downloaded instead of rendered
+    /// again, and new ones are uploaded.
+    #[structopt(long, env = "EUPHONY_SHARED_STORE")]
+    shared_store: Option<String>,
+
+    /// Overrides the directory the timelines and contents are written to
+    #[structopt(skip)]
+    out_dir: Option<PathBuf>,
+}
+
+pub(crate) fn parse_sample_rate(value: &str) -> Result<SampleRate> {
+    let hz = value.parse()?;
+    SampleRate::new(hz).ok_or_else(|| anyhow::anyhow!("invalid sample rate: {:?}", value))
 }
 
 impl Build {
@@ -13,52 +42,116 @@ impl Build {
         Ok(())
     }
 
+    /// Returns `true` if the command stream is read from stdin
+    pub fn is_stdin(&self) -> bool {
+        self.input
+            .as_ref()
+            .is_some_and(|input| input.to_str() == Some("-"))
+    }
+
+    pub fn set_out_dir(&mut self, out_dir: PathBuf) {
+        self.out_dir = Some(out_dir);
+    }
+
     pub fn build(&self) -> Result<Vec<Compiler>> {
-        let out_dir = PathBuf::from("target/euphony");
-        let contents = PathBuf::from("target/euphony/contents");
+        let out_dir = self
+            .out_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("target/euphony"));
+        let contents = out_dir.join("contents");
 
         if let Some(input) = self.input.as_ref() {
             if input.is_dir() {
                 let manifest_path = input.join("Cargo.toml");
-                let mut manifest = Manifest::new(Some(&manifest_path), None)?;
-                manifest.compile()?;
+                let mut manifest = self.manifest(Some(&manifest_path))?;
+                compile(&mut manifest)?;
                 return Ok(manifest.finish());
             }
 
             match input.file_name().and_then(|v| v.to_str()) {
                 Some("-") => {
                     let timeline = out_dir.join("main.json");
-                    let mut comp = Compiler::new(contents, timeline);
+                    let mut comp = self.compiler(contents, timeline);
                     let mut input = io::stdin();
-                    comp.render(&mut input)?;
+                    render(&mut comp, &mut input)?;
                     Ok(vec![comp])
                 }
                 Some("Cargo.toml") => {
-                    let mut manifest = Manifest::new(Some(input), None)?;
-                    manifest.compile()?;
+                    let mut manifest = self.manifest(Some(input))?;
+                    compile(&mut manifest)?;
                     Ok(manifest.finish())
                 }
+                Some(name) if input.extension().is_some_and(|ext| ext == "euphasm") => {
+                    let timeline = out_dir.join(name).with_extension("json");
+                    let mut comp = self.compiler(contents, timeline);
+                    let input = fs::read_to_string(input)?;
+                    let input = asm::assemble(&input, &Processors)?;
+                    render(&mut comp, &mut io::Cursor::new(input))?;
+                    Ok(vec![comp])
+                }
                 Some(name) => {
                     let timeline = out_dir.join(name).with_extension("json");
-                    let mut comp = Compiler::new(contents, timeline);
+                    let mut comp = self.compiler(contents, timeline);
                     let input = fs::File::open(input)?;
                     let mut input = io::BufReader::new(input);
-                    comp.render(&mut input)?;
+                    render(&mut comp, &mut input)?;
                     Ok(vec![comp])
                 }
                 None => {
                     let timeline = out_dir.join("main.json");
-                    let mut comp = Compiler::new(contents, timeline);
+                    let mut comp = self.compiler(contents, timeline);
                     let input = fs::File::open(input)?;
                     let mut input = io::BufReader::new(input);
-                    comp.render(&mut input)?;
+                    render(&mut comp, &mut input)?;
                     Ok(vec![comp])
                 }
             }
         } else {
-            let mut manifest = Manifest::new(None, None)?;
-            manifest.compile()?;
+            let mut manifest = self.manifest(None)?;
+            compile(&mut manifest)?;
             Ok(manifest.finish())
         }
     }
+
+    fn compiler(&self, contents: PathBuf, timeline: PathBuf) -> Compiler {
+        let mut comp = Compiler::new(contents, timeline);
+        if let Some(rate) = self.sample_rate {
+            comp.set_sample_rate(rate);
+        }
+        comp.set_format(self.format());
+        if let Some(url) = self.shared_store.as_deref() {
+            comp.set_shared_store(url);
+        }
+        comp
+    }
+
+    fn manifest(&self, path: Option<&std::path::Path>) -> Result<Manifest> {
+        let mut manifest = Manifest::new(path, self.out_dir.as_deref())?;
+        if let Some(rate) = self.sample_rate {
+            manifest.set_sample_rate(rate);
+        }
+        manifest.set_format(self.format());
+        if let Some(url) = self.shared_store.as_deref() {
+            manifest.set_shared_store(url);
+        }
+        Ok(manifest)
+    }
+
+    fn format(&self) -> Format {
+        if self.compress {
+            Format::Compressed
+        } else {
+            Format::Raw
+        }
+    }
+}
+
+fn compile(manifest: &mut Manifest) -> Result<()> {
+    let _progress = progress::report(manifest.progress.clone());
+    manifest.compile()
+}
+
+fn render<I: io::Read>(comp: &mut Compiler, input: &mut I) -> Result<()> {
+    let _progress = progress::report(comp.progress());
+    comp.render(input)
 }
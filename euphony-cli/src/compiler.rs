@@ -1,10 +1,16 @@
 use crate::Result;
-use euphony_store::Store;
+use euphony_compiler::{sample::SampleRate, Progress};
+use euphony_store::{
+    storage::{fs::Directory, Backend},
+    Format,
+};
 use std::{
     fs, io,
     path::{Path, PathBuf},
 };
 
+pub type Store = euphony_store::Store<Backend>;
+
 #[derive(Debug)]
 pub struct Compiler {
     timeline_path: PathBuf,
@@ -17,7 +23,10 @@ impl Compiler {
         Self {
             timeline_path: timeline,
             compiler: Default::default(),
-            store: Store::new(contents_dir),
+            store: Store {
+                storage: Backend::Local(Directory::new(contents_dir)),
+                timeline: Default::default(),
+            },
         }
     }
 
@@ -25,9 +34,33 @@ impl Compiler {
         let _ = fs::create_dir_all(self.store.storage.path());
         let _ = fs::create_dir_all(self.timeline_path.parent().unwrap());
 
+        // unchanged groups can reuse their previous measurements
+        let previous = core::mem::take(&mut self.store.timeline.groups);
+
         self.store.timeline.reset();
+        self.store.timeline.sample_rate = self.compiler.sample_rate().into();
         self.compiler.compile(input, &mut self.store)?;
 
+        let report = self.compiler.report();
+        if !report.is_empty() {
+            log::info!("{}: {}", self.timeline_path.display(), report);
+        }
+
+        self.store.timeline.tempo = self.compiler.tempo();
+        self.store
+            .timeline
+            .set_annotations(self.compiler.annotations());
+        self.store.measure_groups(&previous)?;
+
+        let mut buffers: Vec<_> = self.compiler.buffers().map(str::to_string).collect();
+        buffers.sort_unstable();
+        buffers.dedup();
+        self.store.timeline.buffers = buffers;
+
+        self.write_timeline()
+    }
+
+    pub fn write_timeline(&self) -> Result<()> {
         let timeline = fs::File::create(&self.timeline_path)?;
         let mut timeline = io::BufWriter::new(timeline);
         self.store.timeline.to_json(&mut timeline)?;
@@ -36,6 +69,31 @@ impl Compiler {
         Ok(())
     }
 
+    pub fn sample_rate(&self) -> SampleRate {
+        self.compiler.sample_rate()
+    }
+
+    pub fn set_sample_rate(&mut self, rate: SampleRate) {
+        self.compiler.set_sample_rate(rate);
+    }
+
+    pub fn set_format(&mut self, format: Format) {
+        self.store.storage.set_format(format);
+    }
+
+    /// Caches the contents of the shared store at `url` in the contents directory
+    pub fn set_shared_store(&mut self, url: &str) {
+        self.store.storage.share(url);
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.compiler.progress()
+    }
+
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.compiler.set_progress(progress);
+    }
+
     pub fn timeline_path(&self) -> &Path {
         &self.timeline_path
     }
@@ -43,4 +101,8 @@ impl Compiler {
     pub fn store(&self) -> &Store {
         &self.store
     }
+
+    pub fn store_mut(&mut self) -> &mut Store {
+        &mut self.store
+    }
 }
@@ -1,5 +1,6 @@
 use crate::Result;
-use euphony_compiler::Compiler;
+use euphony_command::{asm::Printer, Handler};
+use euphony_compiler::{Compiler, Processors};
 use std::{
     fs, io,
     path::{Path, PathBuf},
@@ -11,6 +12,10 @@ pub struct Disasm {
     #[structopt(short, long)]
     instructions: bool,
 
+    /// Prints the node graph in the Graphviz DOT format
+    #[structopt(short, long, conflicts_with = "instructions")]
+    graph: bool,
+
     inputs: Vec<PathBuf>,
 }
 
@@ -47,10 +52,14 @@ impl Disasm {
         if self.instructions {
             let mut compiler = Compiler::default();
             compiler.display(&mut input, &mut io::stdout())?;
+        } else if self.graph {
+            let mut compiler = Compiler::default();
+            compiler.graph(&mut input, &mut io::stdout())?;
         } else {
-            let mut output = String::new();
+            let output = io::BufWriter::new(io::stdout());
+            let mut output = Printer::new(output, Processors);
             euphony_command::decode(&mut input, &mut output)?;
-            print!("{}", output);
+            output.finish()?;
         }
 
         Ok(())
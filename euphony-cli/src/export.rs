@@ -1,17 +1,270 @@
-use crate::Result;
-use std::path::PathBuf;
+use crate::{
+    build::Build,
+    compiler::Compiler,
+    render::{render_file, write_midi, Format},
+    Result,
+};
+use euphony_mix::layout::Layout;
+use euphony_store::timeline::Timeline;
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
 use structopt::StructOpt;
 
-// TODO export to FCMXL, Logic Pro X, etc
-
+/// Exports each group to its own stem along with a Reaper session that arranges them
 #[derive(Debug, StructOpt)]
 pub struct Export {
-    #[allow(dead_code)]
-    input: PathBuf,
+    #[structopt(flatten)]
+    build: Build,
+
+    /// The speaker layout or ambisonic order of the stems
+    #[structopt(long, alias = "channels", default_value = "stereo")]
+    layout: Layout,
+
+    /// The directory to write the exports to, with a subdirectory for each timeline
+    #[structopt(short, long, default_value = "target/euphony/export")]
+    out_dir: PathBuf,
 }
 
 impl Export {
     pub fn run(&self) -> Result<()> {
+        let comps = self.build.build()?;
+
+        for comp in comps {
+            self.export(&comp)?;
+        }
+
+        Ok(())
+    }
+
+    fn export(&self, comp: &Compiler) -> Result<()> {
+        let store = comp.store();
+        let timeline = &store.timeline;
+
+        let name = comp
+            .timeline_path()
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or("main");
+        let dir = self.out_dir.join(name);
+        fs::create_dir_all(&dir)?;
+
+        let mut names = BTreeSet::new();
+        let mut tracks = vec![];
+
+        for group in &timeline.groups {
+            // group names can contain anything so make sure they're unique, valid file names
+            let base = file_name(&group.name);
+            let mut file = base.clone();
+            let mut suffix = 1;
+            while !names.insert(file.clone()) {
+                suffix += 1;
+                file = format!("{base}-{suffix}");
+            }
+
+            let wav = format!("{file}.wav");
+            let frames = render_file(
+                &dir.join(&wav),
+                store,
+                Some(group),
+                self.layout,
+                Format::Wav32f,
+                None,
+            )?
+            .frames;
+
+            // groups that only contain midi don't get a stem
+            let wav = if frames == 0 {
+                fs::remove_file(dir.join(&wav))?;
+                None
+            } else {
+                Some(wav)
+            };
+
+            let midi = if let Some(midi) = group.midi.as_ref() {
+                let path = format!("{file}.mid");
+                write_midi(&dir.join(&path), store, midi)?;
+                Some(path)
+            } else {
+                None
+            };
+
+            tracks.push(Track {
+                name: group.name.clone(),
+                wav,
+                midi,
+                frames,
+            });
+        }
+
+        let session = dir.join(name).with_extension("rpp");
+        let out = fs::File::create(&session)?;
+        let mut out = io::BufWriter::new(out);
+        write_rpp(&mut out, timeline, &tracks)?;
+        out.flush()?;
+
+        log::info!("exported {} stems to {:?}", tracks.len(), session);
+
         Ok(())
     }
 }
+
+#[derive(Debug)]
+struct Track {
+    name: String,
+    /// The stem, relative to the session
+    wav: Option<String>,
+    /// The SMF, relative to the session
+    midi: Option<String>,
+    frames: u64,
+}
+
+/// Writes a Reaper project with the tempo map and a track for each group, starting at the
+/// beginning of the project
+fn write_rpp<W: Write>(out: &mut W, timeline: &Timeline, tracks: &[Track]) -> io::Result<()> {
+    let rate = timeline.sample_rate as f64;
+    let frames = tracks.iter().map(|track| track.frames).max().unwrap_or(0);
+    let length = frames as f64 / rate;
+
+    writeln!(out, "<REAPER_PROJECT 0.1 \"6.0\" 0")?;
+    writeln!(out, "  TEMPO {} 4 4", timeline.tempo)?;
+    writeln!(out, "  SAMPLERATE {} 0 0", timeline.sample_rate)?;
+
+    // each point holds its tempo (shape 1) or ramps to the next point (shape 0)
+    writeln!(out, "  <TEMPOENVEX")?;
+    writeln!(out, "    ACT 1 -1")?;
+    writeln!(out, "    VIS 1 0 1")?;
+    writeln!(out, "    DEFSHAPE 1 -1 -1")?;
+    for change in timeline.tempo_changes() {
+        let position = change.sample as f64 / rate;
+        let shape = if change.ramp { 0 } else { 1 };
+        writeln!(out, "    PT {position} {} {shape}", change.bpm)?;
+    }
+    writeln!(out, "  >")?;
+
+    for track in tracks {
+        writeln!(out, "  <TRACK")?;
+        writeln!(out, "    NAME {}", quote(&track.name))?;
+
+        if let Some(wav) = track.wav.as_ref() {
+            let length = track.frames as f64 / rate;
+            write_item(out, wav, "WAVE", length)?;
+        }
+
+        // the SMF is written in beats so it doesn't have its own length
+        if let Some(midi) = track.midi.as_ref() {
+            write_item(out, midi, "MIDI", length)?;
+        }
+
+        writeln!(out, "  >")?;
+    }
+
+    writeln!(out, ">")?;
+
+    Ok(())
+}
+
+fn write_item<W: Write>(out: &mut W, file: &str, source: &str, length: f64) -> io::Result<()> {
+    writeln!(out, "    <ITEM")?;
+    writeln!(out, "      POSITION 0")?;
+    writeln!(out, "      LENGTH {length}")?;
+    writeln!(out, "      NAME {}", quote(file))?;
+    writeln!(out, "      <SOURCE {source}")?;
+    writeln!(out, "        FILE {}", quote(file))?;
+    writeln!(out, "      >")?;
+    writeln!(out, "    >")?;
+    Ok(())
+}
+
+/// Reaper strings can't escape quotes so replace them instead
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+fn file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    let name = name.trim_matches(|c| c == '.' || c == ' ');
+    if name.is_empty() {
+        "group".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euphony_store::timeline::TempoChange;
+
+    #[test]
+    fn rpp_test() {
+        let change = |sample, beat, bpm, ramp| TempoChange {
+            sample,
+            beat,
+            bpm,
+            ramp,
+        };
+        let timeline = Timeline {
+            tempo_map: vec![
+                change(0, 0.0, 120.0, false),
+                change(24000, 1.0, 120.0, true),
+                change(60000, 3.0, 240.0, false),
+            ],
+            ..Default::default()
+        };
+
+        let tracks = [
+            Track {
+                name: "lead \"synth\"".to_string(),
+                wav: Some("lead _synth_.wav".to_string()),
+                midi: Some("lead _synth_.mid".to_string()),
+                frames: 96000,
+            },
+            Track {
+                name: "drums".to_string(),
+                wav: None,
+                midi: Some("drums.mid".to_string()),
+                frames: 0,
+            },
+        ];
+
+        let mut out = vec![];
+        write_rpp(&mut out, &timeline, &tracks).unwrap();
+        insta::assert_snapshot!(String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn rpp_legacy_tempo_test() {
+        // timelines without a tempo map still get their starting tempo
+        let timeline = Timeline {
+            tempo: 90.0,
+            ..Default::default()
+        };
+
+        let mut out = vec![];
+        write_rpp(&mut out, &timeline, &[]).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("TEMPO 90 4 4"), "{out}");
+        assert!(out.contains("PT 0 90 1"), "{out}");
+    }
+
+    #[test]
+    fn file_name_test() {
+        assert_eq!(file_name("lead \"synth\""), "lead _synth_");
+        assert_eq!(file_name("../drums"), "_drums");
+        assert_eq!(file_name(" . "), "group");
+    }
+}
@@ -1,17 +1,340 @@
 use crate::Result;
-use std::path::PathBuf;
+use anyhow::{anyhow, bail, Context};
+use euphony_store::{gc::Reachable, storage::fs::Directory, timeline::Timeline};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 use structopt::StructOpt;
 
-// TODO garbage collect the contents
-
+/// Removes rendered contents that are no longer referenced by any timeline
 #[derive(Debug, StructOpt)]
 pub struct Gc {
-    #[allow(dead_code)]
+    /// The output directory containing `contents` and the timelines, which can be nested
+    #[structopt(default_value = "target/euphony")]
     input: PathBuf,
+
+    /// Reports what would be removed without deleting anything
+    #[structopt(long)]
+    dry_run: bool,
+
+    /// Keeps unreferenced contents modified within this age, e.g. `30m`, `12h` or `7d`
+    #[structopt(long, parse(try_from_str = parse_age))]
+    keep_recent: Option<Duration>,
+
+    /// Removes contents even if some timelines can't be read
+    ///
+    /// Anything only referenced by the unreadable timelines is removed as well.
+    #[structopt(long)]
+    force: bool,
 }
 
 impl Gc {
     pub fn run(&self) -> Result<()> {
+        let storage = Directory::new(self.input.join("contents"));
+
+        let mut reachable = Reachable::default();
+        let mut timelines = 0;
+        let mut skipped = 0;
+
+        for path in find_timelines(&self.input)? {
+            let timeline = match read_timeline(&path) {
+                Ok(timeline) => timeline,
+                // the entries it references can't be known so nothing is safe to remove
+                Err(err) if !(self.dry_run || self.force) => {
+                    return Err(err.context("pass --force to remove contents anyway"));
+                }
+                Err(err) => {
+                    log::warn!(
+                        "skipping {:?}; entries only it references aren't kept: {err:#}",
+                        path
+                    );
+                    skipped += 1;
+                    continue;
+                }
+            };
+            reachable.mark(&storage, &timeline)?;
+            timelines += 1;
+        }
+
+        // without any roots everything would be removed
+        if timelines == 0 {
+            bail!("no timelines found in {:?}", self.input);
+        }
+
+        let now = SystemTime::now();
+        let mut kept = Usage::default();
+        let mut retained = Usage::default();
+        let mut removed = Usage::default();
+
+        for (hash, entry) in storage.list()? {
+            let metadata = entry.metadata()?;
+            let size = metadata.len();
+
+            if reachable.contains(&hash) {
+                kept.add(size);
+                continue;
+            }
+
+            if let Some(keep) = self.keep_recent {
+                let age = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .unwrap_or_default();
+
+                if age < keep {
+                    retained.add(size);
+                    continue;
+                }
+            }
+
+            removed.add(size);
+
+            if self.dry_run {
+                log::info!("would remove {:?} ({})", entry.path(), Size(size));
+            } else {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        log::info!("scanned {} timelines", timelines);
+        if skipped > 0 {
+            log::warn!("skipped {} unreadable timelines", skipped);
+        }
+        log::info!("kept {} referenced entries", kept);
+        if self.keep_recent.is_some() {
+            log::info!("kept {} recent entries", retained);
+        }
+        if self.dry_run {
+            log::info!("would remove {} entries", removed);
+        } else {
+            log::info!("removed {} entries", removed);
+        }
+
         Ok(())
     }
 }
+
+/// Returns the timelines in `dir` and any of its subdirectories, other than `contents`
+fn find_timelines(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let contents = dir.join("contents");
+    let mut timelines = vec![];
+    let mut dirs = vec![dir.to_owned()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            // symlinked directories aren't followed so they can't loop
+            if entry.file_type()?.is_dir() {
+                if path != contents {
+                    dirs.push(path);
+                }
+                continue;
+            }
+
+            let is_timeline = path
+                .extension()
+                .is_some_and(|ext| ext == "euph" || ext == "json");
+            if is_timeline && path.is_file() {
+                timelines.push(path);
+            }
+        }
+    }
+
+    timelines.sort();
+    Ok(timelines)
+}
+
+fn read_timeline(path: &Path) -> Result<Timeline> {
+    // the hashes are borrowed while parsing so the whole file needs to be read first
+    let timeline = fs::read(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| Ok(serde_json::from_slice(&contents)?))
+        .with_context(|| format!("could not read timeline {:?}", path))?;
+    Ok(timeline)
+}
+
+fn parse_age(value: &str) -> Result<Duration> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (count, unit) = value.split_at(split);
+    let count: u64 = count
+        .parse()
+        .map_err(|_| anyhow!("invalid age: {:?}", value))?;
+
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        "w" => 60 * 60 * 24 * 7,
+        _ => bail!(
+            "invalid age unit {:?}; expected one of s, m, h, d or w",
+            unit
+        ),
+    };
+
+    Ok(Duration::from_secs(count * secs))
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Usage {
+    count: u64,
+    bytes: u64,
+}
+
+impl Usage {
+    fn add(&mut self, bytes: u64) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+}
+
+impl core::fmt::Display for Usage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} ({})", self.count, Size(self.bytes))
+    }
+}
+
+struct Size(u64);
+
+impl core::fmt::Display for Size {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = UNITS[0];
+        for next in &UNITS[1..] {
+            if value < 1024.0 {
+                break;
+            }
+            value /= 1024.0;
+            unit = next;
+        }
+
+        write!(f, "{value:.1} {unit}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euphony_compiler::Hash;
+    use euphony_store::{
+        storage::{Output as _, Storage as _},
+        timeline::Group,
+    };
+
+    #[test]
+    fn find_timelines_test() {
+        let dir = std::env::temp_dir().join(format!("euphony-gc-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for path in ["contents", "project/nested"] {
+            fs::create_dir_all(dir.join(path)).unwrap();
+        }
+        for path in [
+            "main.json",
+            "main.wav",
+            "contents/entry.json",
+            "project/other.euph",
+            "project/nested/broken.json",
+        ] {
+            fs::write(dir.join(path), "{").unwrap();
+        }
+
+        let timelines = find_timelines(&dir).unwrap();
+        let timelines: Vec<_> = timelines
+            .iter()
+            .map(|path| path.strip_prefix(&dir).unwrap().to_owned())
+            .collect();
+        assert_eq!(
+            timelines,
+            [
+                PathBuf::from("main.json"),
+                PathBuf::from("project/nested/broken.json"),
+                PathBuf::from("project/other.euph"),
+            ]
+        );
+
+        // unparsable timelines are reported rather than panicking
+        assert!(read_timeline(&dir.join("main.json")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn collect() {
+        let dir = std::env::temp_dir().join(format!("euphony-gc-collect-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("contents")).unwrap();
+        fs::create_dir_all(dir.join("project")).unwrap();
+
+        let storage = Directory::new(dir.join("contents"));
+        let write = |contents: &[u8]| {
+            let mut output = storage.create();
+            output.write(contents);
+            output.finish()
+        };
+
+        // a group with a single sink, which lists its samples and coordinates
+        let samples = write(b"samples");
+        let coordinates = write(b"coordinates");
+        let sink = write(&[samples, coordinates].concat());
+        let group = write(&[&0u64.to_le_bytes()[..], &sink].concat());
+        let orphan = write(b"orphan");
+
+        let timeline = Timeline {
+            groups: vec![Group {
+                name: "lead".to_string(),
+                entries: group.into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut out = vec![];
+        timeline.to_json(&mut out).unwrap();
+        fs::write(dir.join("main.json"), out).unwrap();
+
+        // real timelines are read and marked
+        let mut reachable = Reachable::default();
+        let timeline = read_timeline(&dir.join("main.json")).unwrap();
+        reachable.mark(&storage, &timeline).unwrap();
+        for hash in [group, sink, samples, coordinates] {
+            assert!(reachable.contains(&hash));
+        }
+        assert!(!reachable.contains(&orphan));
+
+        let gc = |dry_run, force| Gc {
+            input: dir.clone(),
+            dry_run,
+            keep_recent: None,
+            force,
+        };
+        let exists = |hash: &Hash| storage.hash_path(hash).exists();
+
+        // an unreadable timeline may reference the orphan so nothing is removed
+        fs::write(dir.join("project/broken.json"), "{").unwrap();
+        assert!(gc(false, false).run().is_err());
+        assert!(exists(&orphan));
+        gc(true, false).run().unwrap();
+        assert!(exists(&orphan));
+
+        // unless the removal is forced
+        gc(false, true).run().unwrap();
+        for hash in [group, sink, samples, coordinates] {
+            assert!(exists(&hash));
+        }
+        assert!(!exists(&orphan));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -10,7 +10,9 @@ pub mod logger;
 pub mod manifest;
 #[cfg(feature = "play")]
 pub mod play;
+pub mod progress;
 pub mod render;
+pub mod verify;
 pub mod watcher;
 pub mod workspace;
 
@@ -28,6 +30,7 @@ pub enum Arguments {
     Disasm(disasm::Disasm),
     Export(export::Export),
     Render(render::Render),
+    Verify(verify::Verify),
     Gc(gc::Gc),
     #[structopt(alias = "ws")]
     Workspace(workspace::Workspace),
@@ -70,6 +73,7 @@ impl Arguments {
             Arguments::Serve(args) => args.run(),
             Arguments::Export(args) => args.run(),
             Arguments::Render(args) => args.run(),
+            Arguments::Verify(args) => args.run(),
             Arguments::Gc(args) => args.run(),
             Arguments::Workspace(args) => args.run(),
         }
@@ -2,6 +2,8 @@
 
 use crate::{compiler::Compiler, Result};
 use anyhow::anyhow;
+use euphony_compiler::{sample::SampleRate, Progress};
+use euphony_store::Format;
 use rayon::prelude::*;
 use std::{
     collections::BTreeMap,
@@ -16,13 +18,28 @@ pub struct Manifest {
     pub out_dir: PathBuf,
     pub projects: BTreeMap<String, Compiler>,
     pub project: Option<String>,
+    pub sample_rate: SampleRate,
+    pub format: Format,
+    pub shared_store: Option<String>,
+    pub progress: Progress,
 }
 
 impl Manifest {
     pub fn new(manifest_path: Option<&Path>, out_dir: Option<&Path>) -> Result<Self> {
         let mut projects = Default::default();
 
-        let root = Self::build_manifest(manifest_path, out_dir, &mut projects)?;
+        let sample_rate = SampleRate::default();
+        let format = Format::default();
+        let progress = Progress::default();
+        let root = Self::build_manifest(
+            manifest_path,
+            out_dir,
+            sample_rate,
+            format,
+            None,
+            &progress,
+            &mut projects,
+        )?;
         let out_dir = out_dir.unwrap_or(&root).to_owned();
 
         let comp = Self {
@@ -30,6 +47,10 @@ impl Manifest {
             out_dir,
             projects,
             project: None,
+            sample_rate,
+            format,
+            shared_store: None,
+            progress,
         };
         Ok(comp)
     }
@@ -39,6 +60,10 @@ impl Manifest {
         Self::build_manifest(
             Some(&manifest_path),
             Some(&self.out_dir),
+            self.sample_rate,
+            self.format,
+            self.shared_store.as_deref(),
+            &self.progress,
             &mut self.projects,
         )?;
         Ok(())
@@ -62,6 +87,28 @@ impl Manifest {
         Ok(())
     }
 
+    pub fn set_sample_rate(&mut self, rate: SampleRate) {
+        self.sample_rate = rate;
+        for project in self.projects.values_mut() {
+            project.set_sample_rate(rate);
+        }
+    }
+
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+        for project in self.projects.values_mut() {
+            project.set_format(format);
+        }
+    }
+
+    /// Caches the contents of the shared store at `url` instead of only storing them locally
+    pub fn set_shared_store(&mut self, url: &str) {
+        self.shared_store = Some(url.to_string());
+        for project in self.projects.values_mut() {
+            project.set_shared_store(url);
+        }
+    }
+
     pub fn project(&self) -> Result<&Compiler> {
         let project = self
             .project
@@ -74,6 +121,10 @@ impl Manifest {
     fn build_manifest(
         manifest_path: Option<&Path>,
         out_dir: Option<&Path>,
+        sample_rate: SampleRate,
+        format: Format,
+        shared_store: Option<&str>,
+        progress: &Progress,
         projects: &mut BTreeMap<String, Compiler>,
     ) -> Result<PathBuf> {
         let mut cmd = cargo_metadata::MetadataCommand::new();
@@ -107,7 +158,13 @@ impl Manifest {
                             root.join(format!("target/euphony/{}.euph", package.name)),
                         )
                     };
-                    let project = Compiler::new(contents, timeline);
+                    let mut project = Compiler::new(contents, timeline);
+                    project.set_sample_rate(sample_rate);
+                    project.set_format(format);
+                    if let Some(url) = shared_store {
+                        project.set_shared_store(url);
+                    }
+                    project.set_progress(progress.clone());
                     projects.insert(package.name.clone(), project);
                 }
             }
@@ -118,6 +175,9 @@ impl Manifest {
 
     pub fn compile(&mut self) -> Result<()> {
         let root = &self.root;
+        let progress = &self.progress;
+
+        progress.reset();
 
         let mut build = std::process::Command::new("cargo");
 
@@ -140,6 +200,11 @@ impl Manifest {
             return Err(anyhow::anyhow!("cargo build command failed"));
         }
 
+        // a newer build was requested while cargo was running
+        if progress.is_cancelled() {
+            return Err(anyhow::anyhow!("build was cancelled"));
+        }
+
         fn render(root: &Path, name: &str, compiler: &mut Compiler) -> Result<()> {
             let mut proc = process::Command::new(format!("target/euphony/build/debug/{}", name));
 
@@ -151,9 +216,13 @@ impl Manifest {
 
             let mut proc = proc.spawn()?;
             crate::logger::cmd_stderr(proc.stderr.take());
-            let mut stdout = io::BufReader::new(proc.stdout.unwrap());
+            let mut stdout = io::BufReader::new(proc.stdout.take().unwrap());
 
-            compiler.render(&mut stdout)?;
+            if let Err(err) = compiler.render(&mut stdout) {
+                // don't leave the composition running if we bailed early
+                let _ = proc.kill();
+                return Err(err);
+            }
 
             Ok(())
         }
@@ -1,6 +1,8 @@
-use crate::{manifest::Manifest, Result};
+use crate::{build::parse_sample_rate, manifest::Manifest, Result};
 use anyhow::anyhow;
 use cpal::{traits::*, Device, Host, SampleFormat, SampleRate, SupportedStreamConfig};
+use euphony_compiler::sample;
+use euphony_mix::layout::Layout;
 use std::path::Path;
 use structopt::StructOpt;
 
@@ -20,8 +22,19 @@ pub struct Play {
     #[structopt(long, short)]
     project: Option<String>,
 
+    /// The speaker layout or ambisonic order, e.g. `2`, `5.1`, `7.1.4` or `ambix1`
+    ///
+    /// Defaults to the layout matching the device's channel count.
     #[structopt(long, short)]
-    channels: Option<u16>,
+    channels: Option<Layout>,
+
+    /// The preferred output sample rate, in Hz
+    #[structopt(long, parse(try_from_str = parse_sample_rate))]
+    sample_rate: Option<sample::SampleRate>,
+
+    /// The URL of a shared store to cache the contents of
+    #[structopt(long, env = "EUPHONY_SHARED_STORE")]
+    shared_store: Option<String>,
 
     #[structopt(long)]
     paused: bool,
@@ -99,7 +112,16 @@ impl Play {
         let mut manifest = Manifest::new(path, None)?;
         self.manifest_project(&mut manifest)?;
 
-        let stream = stream::Stream::with_manifest(device, config, manifest)?;
+        // render at whatever rate the device ended up with so playback isn't resampled
+        let rate = sample::SampleRate::new(config.sample_rate().0)
+            .ok_or_else(|| anyhow!("unsupported sample rate: {}", config.sample_rate().0))?;
+        manifest.set_sample_rate(rate);
+
+        if let Some(url) = self.shared_store.as_deref() {
+            manifest.set_shared_store(url);
+        }
+
+        let stream = stream::Stream::with_manifest(device, config, self.channels, manifest)?;
 
         Ok(stream)
     }
@@ -121,34 +143,33 @@ impl Play {
     fn device_config(&self, device: &Device) -> Result<SupportedStreamConfig> {
         let mut config = device.default_output_config().ok();
 
-        const SAMPLE_RATE: SampleRate = SampleRate(48_000);
+        let sample_rate = self.sample_rate.unwrap_or_default();
+        let sample_rate = SampleRate(sample_rate.into());
+
+        let channels = self.channels.map(|layout| layout.channels() as u16);
 
-        fn is_preferred(config: &SupportedStreamConfig, channels: Option<u16>) -> bool {
+        let is_preferred = |config: &SupportedStreamConfig, channels: Option<u16>| {
             if let Some(channels) = channels {
                 if config.channels() != channels {
                     return false;
                 }
             }
 
-            config.sample_rate() == SAMPLE_RATE && config.sample_format() == SampleFormat::F32
-        }
+            config.sample_rate() == sample_rate && config.sample_format() == SampleFormat::F32
+        };
 
         // if it's not ideal, then search for a better one
-        if config
-            .as_ref()
-            .map_or(true, |c| !is_preferred(c, self.channels))
-        {
-            let channels = self
-                .channels
-                .unwrap_or_else(|| config.as_ref().map(|c| c.channels()).unwrap_or(2u16));
+        if config.as_ref().map_or(true, |c| !is_preferred(c, channels)) {
+            let channels =
+                channels.unwrap_or_else(|| config.as_ref().map(|c| c.channels()).unwrap_or(2u16));
 
             for conf in device.supported_output_configs()? {
                 let range = conf.min_sample_rate()..=conf.max_sample_rate();
-                if !range.contains(&SAMPLE_RATE) {
+                if !range.contains(&sample_rate) {
                     continue;
                 }
 
-                let conf = conf.with_sample_rate(SAMPLE_RATE);
+                let conf = conf.with_sample_rate(sample_rate);
                 if is_preferred(&conf, Some(channels)) {
                     config = Some(conf);
                     break;
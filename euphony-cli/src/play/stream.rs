@@ -1,14 +1,13 @@
-use crate::{manifest::Manifest, watcher, Result};
+use crate::{compiler::Store, manifest::Manifest, watcher, Result};
 use anyhow::anyhow;
-use arc_swap::ArcSwap;
+use arc_swap::{ArcSwap, ArcSwapOption};
 use cpal::{traits::*, Device, SupportedStreamConfig};
-use euphony_compiler::{sample, Hash};
+use euphony_compiler::{sample, Hash, Progress, Snapshot};
 use euphony_mix::{
     frame::{self, Frame as _},
-    mono::Mono,
-    stereo::Stereo,
+    layout::{Layout, Spatial},
 };
-use euphony_store::Store;
+use euphony_store::overview::Overview;
 use rayon::prelude::*;
 use std::{
     collections::HashSet,
@@ -71,6 +70,7 @@ pub struct TrackControl {
     is_muted: AtomicBool,
     is_soloed: AtomicBool,
     end: AtomicUsize,
+    waveform: ArcSwapOption<Waveform>,
 }
 
 impl TrackControl {
@@ -90,6 +90,10 @@ impl TrackControl {
         self.end.load(Ordering::Relaxed)
     }
 
+    pub fn overview(&self) -> Option<Arc<Waveform>> {
+        self.waveform.load_full()
+    }
+
     fn can_play(&self, any_soloed: bool) -> bool {
         let mut can_play = !self.is_muted();
 
@@ -101,6 +105,13 @@ impl TrackControl {
     }
 }
 
+/// The overview of a track, keyed by its hash so it's only read when the group changes
+#[derive(Debug)]
+pub struct Waveform {
+    hash: Hash,
+    pub overview: Overview,
+}
+
 pub struct TracksIter {
     guard: arc_swap::Guard<Arc<Vec<Arc<TrackControl>>>>,
 }
@@ -116,9 +127,14 @@ impl core::ops::Deref for TracksIter {
 pub struct Stream {
     inner: cpal::Stream,
     controls: Arc<Controls>,
+    progress: Progress,
 }
 
 impl Stream {
+    pub fn build_progress(&self) -> Snapshot {
+        self.progress.snapshot()
+    }
+
     pub fn play(&self) -> Result<()> {
         self.controls.is_playing.store(true, Ordering::Relaxed);
         self.inner.play()?;
@@ -284,53 +300,74 @@ impl Stream {
     pub fn with_manifest(
         device: &Device,
         config: &SupportedStreamConfig,
+        layout: Option<Layout>,
         manifest: Manifest,
     ) -> Result<Self> {
+        let channels = config.channels() as usize;
+        let layout = layout
+            .or_else(|| Layout::from_channels(channels))
+            .ok_or_else(|| anyhow!("unsupported channel count: {}", channels))?;
+        if layout.channels() != channels {
+            return Err(anyhow!(
+                "{} needs {} channels but the device was opened with {}",
+                layout,
+                layout.channels(),
+                channels
+            ));
+        }
+
         let controls;
         let target = manifest.project()?.timeline_path().to_owned();
+        let progress = manifest.progress.clone();
 
         macro_rules! build {
             ($(($format:ident, $sample:ty)),* $(,)?) => {
                 match config.sample_format() {
                     $(
                         cpal::SampleFormat::$format => {
-                            let sample_rate = config.sample_rate().0;
-
-                            if sample_rate != 48_000 {
-                                return Err(anyhow!("unsupported sample rate: {}", sample_rate));
-                            }
-
                             controls = Arc::new(Controls {
-                                sample_rate: 48_000,
+                                sample_rate: config.sample_rate().0,
                                 channels: config.channels() as _,
                                 ..Default::default()
                             });
 
                             let tracks = Tracks::new(controls.clone());
 
-                            match config.channels() {
+                            let midi_output = midi::Output::new(controls.clone());
+                            match channels {
                                 1 => {
-                                    let subscriber: Subscriber<1, $sample> = Subscriber {
-                                        tracks: tracks.clone(),
-                                        midi_output: midi::Output::new(controls.clone()),
-                                        midi_groups: vec![],
-                                        target,
-                                    };
-
+                                    let subscriber = Subscriber::<1, $sample>::new(tracks.clone(), midi_output, target, layout);
                                     manifest.watch(subscriber);
                                 }
                                 2 => {
-                                    let subscriber: Subscriber<2, $sample> = Subscriber {
-                                        tracks: tracks.clone(),
-                                        midi_output: midi::Output::new(controls.clone()),
-                                        midi_groups: vec![],
-                                        target,
-                                    };
-
+                                    let subscriber = Subscriber::<2, $sample>::new(tracks.clone(), midi_output, target, layout);
+                                    manifest.watch(subscriber);
+                                }
+                                4 => {
+                                    let subscriber = Subscriber::<4, $sample>::new(tracks.clone(), midi_output, target, layout);
+                                    manifest.watch(subscriber);
+                                }
+                                6 => {
+                                    let subscriber = Subscriber::<6, $sample>::new(tracks.clone(), midi_output, target, layout);
+                                    manifest.watch(subscriber);
+                                }
+                                8 => {
+                                    let subscriber = Subscriber::<8, $sample>::new(tracks.clone(), midi_output, target, layout);
+                                    manifest.watch(subscriber);
+                                }
+                                9 => {
+                                    let subscriber = Subscriber::<9, $sample>::new(tracks.clone(), midi_output, target, layout);
+                                    manifest.watch(subscriber);
+                                }
+                                12 => {
+                                    let subscriber = Subscriber::<12, $sample>::new(tracks.clone(), midi_output, target, layout);
+                                    manifest.watch(subscriber);
+                                }
+                                16 => {
+                                    let subscriber = Subscriber::<16, $sample>::new(tracks.clone(), midi_output, target, layout);
                                     manifest.watch(subscriber);
                                 }
                                 channels => {
-                                    // TODO support this
                                     return Err(anyhow!("unsupported channel count: {}", channels));
                                 }
                             }
@@ -354,7 +391,11 @@ impl Stream {
 
         let inner = build!((F32, f32), (I16, i16), (U16, u16));
 
-        Ok(Self { inner, controls })
+        Ok(Self {
+            inner,
+            controls,
+            progress,
+        })
     }
 }
 
@@ -363,17 +404,21 @@ pub struct Subscriber<const CHANNELS: usize, Sample: Copy> {
     midi_output: midi::Output,
     midi_groups: Vec<Option<midi::Group>>,
     target: PathBuf,
+    layout: Layout,
 }
 
-impl<Sample> watcher::Subscriptions<Manifest> for Subscriber<1, Sample>
+impl<const CHANNELS: usize, Sample> watcher::Subscriptions<Manifest>
+    for Subscriber<CHANNELS, Sample>
 where
     Sample: sample::Sample + sample::FromSample<f64> + Send + Sync,
+    [Sample; CHANNELS]: frame::Frame<Sample = Sample>,
 {
     fn on_update(&mut self, updates: &mut HashSet<PathBuf>, manifest: &mut Manifest) {
         let store = manifest.project().unwrap().store();
+        let layout = self.layout;
 
         self.update(updates, store, |hash, writer, store| {
-            let mut mixer = Mono::new(writer);
+            let mut mixer = Spatial::<CHANNELS, _>::new(writer, layout);
 
             if let Err(err) = store.mix_group(hash, &mut mixer) {
                 // TODO log
@@ -385,30 +430,25 @@ where
     }
 }
 
-impl<Sample> watcher::Subscriptions<Manifest> for Subscriber<2, Sample>
+impl<const CHANNELS: usize, Sample> Subscriber<CHANNELS, Sample>
 where
     Sample: sample::Sample + sample::FromSample<f64> + Send + Sync,
 {
-    fn on_update(&mut self, updates: &mut HashSet<PathBuf>, manifest: &mut Manifest) {
-        let store = manifest.project().unwrap().store();
-
-        self.update(updates, store, |hash, writer, store| {
-            let mut mixer = Stereo::new(writer);
-
-            if let Err(err) = store.mix_group(hash, &mut mixer) {
-                // TODO log
-                dbg!(err);
-            }
-
-            mixer.finish()
-        });
+    fn new(
+        tracks: Tracks<Sample>,
+        midi_output: midi::Output,
+        target: PathBuf,
+        layout: Layout,
+    ) -> Self {
+        Self {
+            tracks,
+            midi_output,
+            midi_groups: vec![],
+            target,
+            layout,
+        }
     }
-}
 
-impl<const CHANNELS: usize, Sample> Subscriber<CHANNELS, Sample>
-where
-    Sample: sample::Sample + sample::FromSample<f64> + Send + Sync,
-{
     fn update<F>(&mut self, updates: &mut HashSet<PathBuf>, store: &Store, mix: F)
     where
         F: Fn(&Hash, TrackWriter<CHANNELS, Sample>, &Store) -> TrackWriter<CHANNELS, Sample> + Sync,
@@ -440,10 +480,41 @@ where
                         is_muted: AtomicBool::new(false),
                         is_soloed: AtomicBool::new(false),
                         end: AtomicUsize::new(0),
+                        waveform: ArcSwapOption::empty(),
                     });
                     (control, None)
                 };
 
+                if let Some(hash) = group.overview.as_deref() {
+                    let is_current = controls
+                        .waveform
+                        .load()
+                        .as_ref()
+                        .is_some_and(|waveform| waveform.hash == *hash);
+
+                    if !is_current {
+                        match store.read_overview(hash) {
+                            Ok(overview) => {
+                                let waveform = Waveform {
+                                    hash: *hash,
+                                    overview,
+                                };
+                                controls.waveform.store(Some(Arc::new(waveform)));
+                            }
+                            Err(err) => {
+                                log::warn!(
+                                    "could not read the overview for {:?}: {}",
+                                    group.name,
+                                    err
+                                );
+                                controls.waveform.store(None);
+                            }
+                        }
+                    }
+                } else {
+                    controls.waveform.store(None);
+                }
+
                 let track = if let Some(track) =
                     idx.and_then(|idx| tracks.get(idx).filter(|track| track.hash == hash))
                 {
@@ -456,7 +527,12 @@ where
                     Arc::new(track)
                 };
 
-                let midi = midi::Group::read(group.midi.as_deref(), &store.storage, midi_groups);
+                let midi = midi::Group::read(
+                    group.midi.as_deref(),
+                    &store.storage,
+                    store.timeline.sample_rate,
+                    midi_groups,
+                );
 
                 let track_end = track.start + track.buffer.len();
                 let midi_end = midi.as_ref().map(|m| m.end()).unwrap_or_default() as usize;
@@ -25,6 +25,7 @@ impl Group {
     pub fn read<S: Storage>(
         hash: Option<&Hash>,
         storage: &S,
+        sample_rate: u32,
         prev: &[Option<Self>],
     ) -> Option<Self> {
         let hash = *hash?;
@@ -44,7 +45,7 @@ impl Group {
         for event in reader {
             let (sample, _beat, data) = event.ok()?;
             let sample: u64 = sample.into();
-            let sample = sample / (48_000 / FPS);
+            let sample = sample / (sample_rate as u64 / FPS);
             events.push((sample, data));
         }
 
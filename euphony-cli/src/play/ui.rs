@@ -66,6 +66,7 @@ impl App {
         let is_playing = stream.is_playing();
         let is_looping = stream.is_looping();
         let is_clipped = stream.is_clipped();
+        let build = stream.build_progress();
 
         {
             let status = Block::default().title(vec![
@@ -95,6 +96,20 @@ impl App {
                 Span::from(" | Volume [-] "),
                 Span::from(format!("{:>3}", (stream.volume() * 100.0).round())),
                 Span::from("% [+]"),
+                Span::from(" | "),
+                if build.is_finished() {
+                    Span::styled("Built", Style::default().fg(Color::DarkGray))
+                } else {
+                    Span::styled(
+                        format!(
+                            "Building {:>3}% ({}/{} sinks)",
+                            (build.ratio() * 100.0).round(),
+                            build.sinks_rendered,
+                            build.sinks_total
+                        ),
+                        Style::default().fg(Color::Yellow),
+                    )
+                },
             ]);
             f.render_widget(status, chunks[0]);
         }
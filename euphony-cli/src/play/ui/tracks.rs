@@ -1,12 +1,18 @@
-use crate::play::stream::Stream;
+use crate::play::stream::{Stream, Waveform};
 use tui::{
     backend::Backend,
     layout::{Constraint, Rect},
     style::{Color, Style},
+    text::{Span, Spans},
     widgets::{Cell, Row, Table, TableState},
     Frame,
 };
 
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The widest the name column will grow before the waveforms take the rest of the row
+const MAX_NAME_WIDTH: u16 = 24;
+
 pub struct TracksTable {
     state: TableState,
 }
@@ -33,6 +39,19 @@ impl TracksTable {
     pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect, stream: &Stream) {
         let mut rows = vec![];
         let tracks = stream.tracks();
+
+        let name_width = tracks
+            .iter()
+            .map(|track| track.name().chars().count() as u16 + 1)
+            .max()
+            .unwrap_or(0)
+            .min(MAX_NAME_WIDTH);
+        // the highlight symbol and the M/S cells take up the first 7 columns
+        let waveform_width = rect.width.saturating_sub(7 + name_width);
+
+        let duration = stream.duration().as_secs_f64();
+        let playhead = stream.playhead().as_secs_f64();
+
         for track in tracks.iter() {
             let muted = Cell::from("M").style(if track.is_muted() {
                 Style::default().fg(Color::Green)
@@ -52,21 +71,29 @@ impl TracksTable {
                 solo,
                 Cell::from("] "),
                 Cell::from(track.name()),
+                Cell::from(waveform(
+                    track.overview().as_deref(),
+                    waveform_width,
+                    duration,
+                    playhead,
+                )),
             ];
             let row = Row::new(cells).height(1);
             rows.push(row);
         }
 
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(2),
+            Constraint::Length(name_width),
+            Constraint::Percentage(100),
+        ];
         let t = Table::new(rows)
             .column_spacing(0)
             .highlight_symbol("> ")
-            .widths(&[
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(2),
-                Constraint::Percentage(100),
-            ]);
+            .widths(&widths);
         f.render_stateful_widget(t, rect, &mut self.state);
     }
 
@@ -98,3 +125,44 @@ impl TracksTable {
         self.state.select(Some(i));
     }
 }
+
+/// Draws the peaks of the track across the duration of the timeline, highlighting what's been
+/// played
+fn waveform(
+    waveform: Option<&Waveform>,
+    width: u16,
+    duration: f64,
+    playhead: f64,
+) -> Spans<'static> {
+    let overview = match waveform {
+        Some(waveform) if width > 0 && duration > 0.0 => &waveform.overview,
+        _ => return Spans::default(),
+    };
+
+    let samples = duration * overview.sample_rate as f64;
+    let step = samples / width as f64;
+    let played = ((playhead / duration) * width as f64) as u16;
+
+    let bars = |range: core::ops::Range<u16>| -> String {
+        range
+            .map(|column| {
+                let start = (column as f64 * step) as u64;
+                let end = ((column + 1) as f64 * step) as u64;
+                let peak = overview.range(start, end).peak.clamp(0.0, 1.0);
+
+                if peak == 0.0 {
+                    ' '
+                } else {
+                    let idx = (peak * (BARS.len() - 1) as f32).round() as usize;
+                    BARS[idx]
+                }
+            })
+            .collect()
+    };
+
+    let played = played.min(width);
+    Spans::from(vec![
+        Span::styled(bars(0..played), Style::default().fg(Color::Green)),
+        Span::styled(bars(played..width), Style::default().fg(Color::DarkGray)),
+    ])
+}
@@ -0,0 +1,76 @@
+use euphony_compiler::Progress;
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+const WIDTH: usize = 30;
+
+/// Draws a progress bar on stderr until dropped
+pub struct Reporter {
+    is_done: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+pub fn report(progress: Progress) -> Reporter {
+    let is_done = Arc::new(AtomicBool::new(false));
+
+    // don't clutter up logs with control characters
+    let handle = if io::stderr().is_terminal() && !crate::logger::is_alt_screen() {
+        let is_done = is_done.clone();
+        Some(thread::spawn(move || {
+            let mut stderr = io::stderr();
+            while !is_done.load(Ordering::Relaxed) {
+                draw(&mut stderr, &progress);
+                thread::sleep(Duration::from_millis(100));
+            }
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }))
+    } else {
+        None
+    };
+
+    Reporter { is_done, handle }
+}
+
+fn draw<W: Write>(out: &mut W, progress: &Progress) {
+    let snapshot = progress.snapshot();
+
+    // nothing has been compiled yet
+    if snapshot.nodes_total == 0 {
+        return;
+    }
+
+    let ratio = snapshot.ratio();
+    let filled = (ratio * WIDTH as f64).round() as usize;
+
+    let _ = write!(
+        out,
+        "\r\x1b[2K[{:#<filled$}{:-<empty$}] {:>3}% | {}/{} sinks | {}/{} nodes hashed",
+        "",
+        "",
+        (ratio * 100.0).round(),
+        snapshot.sinks_rendered,
+        snapshot.sinks_total,
+        snapshot.nodes_hashed,
+        snapshot.nodes_total,
+        filled = filled,
+        empty = WIDTH - filled,
+    );
+    let _ = out.flush();
+}
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        self.is_done.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -1,32 +1,122 @@
-use crate::{build::Build, compiler::Compiler, Result};
-use euphony_compiler::{midi, sample, Hash};
+use crate::{
+    build::Build,
+    compiler::{Compiler, Store},
+    Result,
+};
+use euphony_compiler::{midi, Hash};
 use euphony_mix::{
-    frame::{self, Frame as _},
-    mono::Mono,
-    stereo::Stereo,
+    frame,
+    layout::{Layout, Spatial},
+    limiter::Limiter,
+    loudness::{Measurement, Meter},
+};
+use euphony_store::{
+    storage::Storage,
+    timeline::{self, Group},
 };
-use euphony_store::{storage::Storage, Store};
-use std::marker::PhantomData;
+use euphony_units::dynamics::loudness::Loudness;
+use std::{fs, io, path::Path, str::FromStr};
 use structopt::StructOpt;
 
+mod dither;
+#[cfg(feature = "flac")]
+mod flac;
+#[cfg(feature = "opus")]
+mod opus;
+
 #[derive(Debug, StructOpt)]
 pub struct Render {
     #[structopt(flatten)]
     build: Build,
 
-    #[structopt(long, default_value = "2")]
-    channels: u16,
+    /// The speaker layout or ambisonic order, e.g. `5.1`, `7.1.4`, `octagon` or `ambix3`
+    ///
+    /// A channel count selects the default layout with that many channels.
+    #[structopt(long, alias = "channels", default_value = "stereo")]
+    layout: Layout,
+
+    /// The output format: `wav32f`, `wav24`, `wav16`, `flac` or `opus`
+    ///
+    /// The integer formats are dithered and `flac` is written with 24 bits. `opus` previews are
+    /// only available when built with the `opus` feature, which links libopus.
+    #[structopt(long, default_value = "wav32f")]
+    format: Format,
+
+    /// Normalizes the integrated loudness of the render, e.g. `-14LUFS`
+    #[structopt(long, allow_hyphen_values = true)]
+    normalize: Option<Loudness>,
+
+    /// The true peak ceiling in dBTP when normalizing
+    #[structopt(long, default_value = "-1", allow_hyphen_values = true)]
+    true_peak: f64,
 }
 
 impl Default for Render {
     fn default() -> Self {
         Self {
             build: Default::default(),
-            channels: 2,
+            layout: Default::default(),
+            format: Default::default(),
+            normalize: None,
+            true_peak: -1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    Wav16,
+    Wav24,
+    #[default]
+    Wav32f,
+    Flac,
+    Opus,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Wav16 | Self::Wav24 | Self::Wav32f => "wav",
+            Self::Flac => "flac",
+            Self::Opus => "opus",
+        }
+    }
+
+    /// The most channels the format can hold
+    pub fn max_channels(self) -> u16 {
+        match self {
+            Self::Wav16 | Self::Wav24 | Self::Wav32f => u16::MAX,
+            Self::Flac => 8,
+            Self::Opus => 2,
         }
     }
 }
 
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let format = match s {
+            "wav16" => Self::Wav16,
+            "wav24" => Self::Wav24,
+            "wav32f" | "wav" => Self::Wav32f,
+            "flac" if cfg!(feature = "flac") => Self::Flac,
+            "opus" if cfg!(feature = "opus") => Self::Opus,
+            "flac" | "opus" => {
+                return Err(anyhow::anyhow!(
+                    "euphony was built without {s} support; enable the `{s}` feature"
+                ))
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "invalid format {s:?}; expected one of wav32f, wav24, wav16, flac or opus"
+                ))
+            }
+        };
+        Ok(format)
+    }
+}
+
 impl Render {
     pub fn run(&self) -> Result<()> {
         let comps = self.build.build()?;
@@ -35,46 +125,51 @@ impl Render {
     }
 
     pub fn run_compilers(&self, comps: Vec<Compiler>) -> Result<()> {
-        for comp in comps {
+        let normalize = self.normalize.map(|target| Normalize {
+            target,
+            ceiling: 10f64.powf(self.true_peak / 20.0),
+        });
+
+        for mut comp in comps {
             let store = comp.store();
 
-            let buffer = match self.channels {
-                1 => {
-                    let mut mixer = Mixer::<1, f32>::default();
-                    mixer.render(store)
-                }
-                2 => {
-                    let mut mixer = Mixer::<2, f32>::default();
-                    mixer.render(store)
-                }
-                channels => {
-                    return Err(anyhow::anyhow!("Invalid number of channels: {}", channels));
-                }
-            };
+            let timeline = comp.timeline_path().to_owned();
+            let mut out = timeline.to_owned();
+            out.set_extension(self.format.extension());
 
-            let timeline = comp.timeline_path();
-            let mut wav = timeline.to_owned();
-            wav.set_extension("wav");
+            let groups = store.timeline.groups.iter();
+            let rendered = render_file(&out, store, groups, self.layout, self.format, normalize)?;
 
-            let spec = hound::WavSpec {
-                channels: self.channels,
-                sample_rate: 48_000,
-                bits_per_sample: 32,
-                sample_format: hound::SampleFormat::Float,
-            };
-            let mut writer = hound::WavWriter::create(&wav, spec)?;
-            for sample in buffer {
-                writer.write_sample(sample)?;
+            if let Some(gain) = rendered.gain {
+                log::info!("applied {:.1} dB of gain", 20.0 * gain.log10());
+            }
+            if let Some(measurement) = rendered.measurement.as_ref() {
+                log::info!(
+                    "{} integrated, {} short-term, {:.1} dBTP",
+                    measurement.integrated,
+                    measurement.short_term,
+                    measurement.true_peak_db()
+                );
             }
-            writer.finalize()?;
+
+            let loudness = rendered
+                .measurement
+                .filter(|measurement| !measurement.integrated.is_silent())
+                .map(|measurement| timeline::Loudness {
+                    integrated: measurement.integrated.0,
+                    short_term: measurement.short_term.0,
+                    true_peak: measurement.true_peak_db(),
+                    gain: rendered.gain.map_or(0.0, |gain| 20.0 * gain.log10()),
+                });
+            comp.store_mut().timeline.loudness = loudness;
+            comp.write_timeline()?;
+
+            let store = comp.store();
 
             let mut timeline_created = false;
 
             for group in store.timeline.groups.iter() {
                 if let Some(midi) = group.midi.as_ref() {
-                    let midi = store.storage.open_raw(midi)?;
-                    let mut midi = midi::Reader::new(midi);
-
                     let mut path = timeline.to_owned();
                     path.set_extension("");
 
@@ -86,123 +181,305 @@ impl Render {
                     path.push(&group.name);
                     path.set_extension("mid");
 
-                    let out = std::fs::File::create(&path)?;
-                    let out = std::io::BufWriter::new(out);
-                    midi.write_smf(out)?;
+                    write_midi(&path, store, midi)?;
                 }
             }
 
-            log::info!("rendered {:?}", wav);
+            log::info!("rendered {:?}", out);
         }
         Ok(())
     }
 }
 
-#[derive(Default)]
-pub struct Mixer<const CHANNELS: usize, Sample: Copy> {
-    samples: PhantomData<Sample>,
+/// The loudness to normalize a render to
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Normalize {
+    pub target: Loudness,
+    /// The linear true peak ceiling
+    pub ceiling: f64,
 }
 
-impl<Sample> Mixer<1, Sample>
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Rendered {
+    /// The number of frames that were written
+    pub frames: u64,
+    /// The readings of the written file
+    ///
+    /// Ambisonic renders aren't measured since their channels don't feed speakers.
+    pub measurement: Option<Measurement>,
+    /// The linear gain applied when normalizing
+    pub gain: Option<f64>,
+}
+
+/// Mixes the groups straight into a file, starting at sample zero
+///
+/// The groups are read in lockstep so memory use doesn't grow with the length of the piece. When
+/// normalizing, the groups are mixed twice: once to measure them and again to write them.
+pub(crate) fn render_file<'a, G>(
+    path: &Path,
+    store: &Store,
+    groups: G,
+    layout: Layout,
+    format: Format,
+    normalize: Option<Normalize>,
+) -> Result<Rendered>
 where
-    Sample: sample::Sample + sample::FromSample<f64> + core::ops::AddAssign + Send + Sync,
+    G: IntoIterator<Item = &'a Group>,
 {
-    fn render(&mut self, store: &Store) -> Vec<Sample> {
-        self.render_inner(store, |hash, writer, store| {
-            let mut mixer = Mono::new(writer);
+    let channels = layout.channels() as u16;
+    if channels > format.max_channels() {
+        return Err(anyhow::anyhow!(
+            "{format:?} supports up to {} channels but {layout} needs {channels}",
+            format.max_channels()
+        ));
+    }
+
+    let weights = layout.loudness_weights();
+    if normalize.is_some() && weights.is_none() {
+        return Err(anyhow::anyhow!(
+            "{layout} can't be normalized since its channels don't feed speakers; \
+            render a speaker layout instead"
+        ));
+    }
+    let is_measured = weights.is_some();
+    let weights = weights.unwrap_or_else(|| vec![1.0; channels as usize]);
+
+    let groups: Vec<Hash> = groups.into_iter().map(|group| *group.entries).collect();
+    let sample_rate = store.timeline.sample_rate;
+
+    macro_rules! render {
+        ($channels:literal) => {{
+            let gain = if let Some(normalize) = normalize {
+                let mut mixer = Spatial::<$channels, _>::new(
+                    Meter::with_weights(Discard::<$channels>, sample_rate, weights.clone()),
+                    layout,
+                );
+                store.mix_groups(&groups, &mut mixer)?;
+                let (_, measurement) = mixer.finish().finish();
+                Some(measurement.integrated.gain_to(normalize.target))
+            } else {
+                None
+            };
+
+            let encoder = Encoder::create(path, format, channels, sample_rate)?;
+            let writer = FileWriter::<$channels>::new(encoder);
+            let writer = Meter::with_weights(writer, sample_rate, weights);
+
+            let (writer, measurement) = match (gain, normalize) {
+                (Some(gain), Some(normalize)) => {
+                    let limiter = Limiter::new(writer, sample_rate, gain, normalize.ceiling);
+                    let mut mixer = Spatial::<$channels, _>::new(limiter, layout);
+                    store.mix_groups(&groups, &mut mixer)?;
+                    let (writer, _) = mixer.finish().finish()?;
+                    writer.finish()
+                }
+                _ => {
+                    let mut mixer = Spatial::<$channels, _>::new(writer, layout);
+                    store.mix_groups(&groups, &mut mixer)?;
+                    mixer.finish().finish()
+                }
+            };
 
-            if let Err(err) = store.mix_group(hash, &mut mixer) {
-                log::error!("could not mix group: {}", err);
+            Rendered {
+                frames: writer.finish()?,
+                measurement: is_measured.then_some(measurement),
+                gain,
             }
+        }};
+    }
 
-            mixer.finish()
+    let rendered = match channels {
+        1 => render!(1),
+        2 => render!(2),
+        4 => render!(4),
+        6 => render!(6),
+        8 => render!(8),
+        9 => render!(9),
+        12 => render!(12),
+        16 => render!(16),
+        _ => return Err(anyhow::anyhow!("{layout} isn't supported")),
+    };
+
+    Ok(rendered)
+}
+
+pub(crate) fn write_midi(path: &Path, store: &Store, midi: &Hash) -> Result<()> {
+    let midi = store.storage.open_raw(midi)?;
+    let mut midi = midi::Reader::new(midi);
+
+    let out = std::fs::File::create(path)?;
+    let out = std::io::BufWriter::new(out);
+    let tempo_map: Vec<_> = store
+        .timeline
+        .tempo_changes()
+        .into_iter()
+        .map(|change| euphony_compiler::TempoChange {
+            sample: change.sample,
+            beat: change.beat,
+            bpm: change.bpm,
+            ramp: change.ramp,
         })
-    }
+        .collect();
+    midi.write_smf(&tempo_map, out)?;
+    Ok(())
 }
 
-impl<Sample> Mixer<2, Sample>
-where
-    Sample: sample::Sample + sample::FromSample<f64> + core::ops::AddAssign + Send + Sync,
-{
-    fn render(&mut self, store: &Store) -> Vec<Sample> {
-        self.render_inner(store, |hash, writer, store| {
-            let mut mixer = Stereo::new(writer);
+type Wav = hound::WavWriter<io::BufWriter<fs::File>>;
 
-            if let Err(err) = store.mix_group(hash, &mut mixer) {
-                log::error!("could not mix group: {}", err);
+enum Encoder {
+    Float(Wav),
+    Pcm(Wav, dither::Dither),
+    #[cfg(feature = "flac")]
+    Flac(flac::Encoder, dither::Dither),
+    #[cfg(feature = "opus")]
+    Opus(opus::Encoder),
+}
+
+impl Encoder {
+    fn create(path: &Path, format: Format, channels: u16, sample_rate: u32) -> Result<Self> {
+        let wav = |bits_per_sample, sample_format| {
+            let spec = hound::WavSpec {
+                channels,
+                sample_rate,
+                bits_per_sample,
+                sample_format,
+            };
+            hound::WavWriter::create(path, spec)
+        };
+
+        let encoder = match format {
+            Format::Wav32f => Self::Float(wav(32, hound::SampleFormat::Float)?),
+            Format::Wav24 | Format::Wav16 => {
+                let bits = if format == Format::Wav24 { 24 } else { 16 };
+                let dither = dither::Dither::new(channels as _, bits);
+                Self::Pcm(wav(bits, hound::SampleFormat::Int)?, dither)
+            }
+            #[cfg(feature = "flac")]
+            Format::Flac => {
+                let encoder = flac::Encoder::create(path, channels, sample_rate, 24)?;
+                Self::Flac(encoder, dither::Dither::new(channels as _, 24))
             }
+            #[cfg(feature = "opus")]
+            Format::Opus => Self::Opus(opus::Encoder::create(path, channels, sample_rate)?),
+            #[allow(unreachable_patterns)]
+            format => unreachable!("{format:?} is rejected when parsing"),
+        };
 
-            mixer.finish()
-        })
+        Ok(encoder)
     }
-}
 
-impl<const CHANNELS: usize, Sample> Mixer<CHANNELS, Sample>
-where
-    Sample: sample::Sample + sample::FromSample<f64> + Send + Sync,
-{
-    fn render_inner<F>(&mut self, store: &Store, mix: F) -> Vec<Sample>
-    where
-        F: Fn(&Hash, TrackWriter<CHANNELS, Sample>, &Store) -> TrackWriter<CHANNELS, Sample> + Sync,
-    {
-        let mut writer = <TrackWriter<CHANNELS, Sample>>::new();
-        for group in store.timeline.groups.iter() {
-            writer = mix(&group.entries, writer, store);
-            writer.cursor = 0;
+    /// Writes a single interleaved frame
+    #[inline]
+    fn write(&mut self, frame: &[f32]) -> io::Result<()> {
+        match self {
+            Self::Float(wav) => {
+                for sample in frame {
+                    wav.write_sample(*sample).map_err(wav_error)?;
+                }
+            }
+            Self::Pcm(wav, dither) => {
+                for (channel, sample) in frame.iter().enumerate() {
+                    let sample = dither.quantize(channel, *sample);
+                    wav.write_sample(sample).map_err(wav_error)?;
+                }
+            }
+            #[cfg(feature = "flac")]
+            Self::Flac(flac, dither) => {
+                let mut samples = [0; 8];
+                for (channel, sample) in frame.iter().enumerate() {
+                    samples[channel] = dither.quantize(channel, *sample);
+                }
+                flac.write(&samples[..frame.len()])?;
+            }
+            #[cfg(feature = "opus")]
+            Self::Opus(opus) => opus.write(frame)?,
         }
-        writer.buffer
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Float(wav) | Self::Pcm(wav, _) => wav.finalize()?,
+            #[cfg(feature = "flac")]
+            Self::Flac(flac, _) => flac.finish()?,
+            #[cfg(feature = "opus")]
+            Self::Opus(opus) => opus.finish()?,
+        }
+
+        Ok(())
     }
 }
 
-struct TrackWriter<const CHANNELS: usize, Sample: Copy> {
-    buffer: Vec<Sample>,
-    cursor: usize,
+/// Writes the mixed frames to an encoder
+struct FileWriter<const CHANNELS: usize> {
+    encoder: Encoder,
+    frames: u64,
 }
 
-impl<const CHANNELS: usize, Sample: euphony_compiler::sample::Sample>
-    TrackWriter<CHANNELS, Sample>
-{
+impl<const CHANNELS: usize> FileWriter<CHANNELS> {
     #[inline]
-    fn new() -> Self {
-        Self {
-            buffer: vec![],
-            cursor: 0,
-        }
+    fn new(encoder: Encoder) -> Self {
+        Self { encoder, frames: 0 }
+    }
+
+    fn finish(self) -> Result<u64> {
+        self.encoder.finish()?;
+        Ok(self.frames)
     }
 }
 
-impl<const CHANNELS: usize, Sample: euphony_compiler::sample::Sample> euphony_mix::Writer
-    for TrackWriter<CHANNELS, Sample>
+impl<const CHANNELS: usize> euphony_mix::Writer for FileWriter<CHANNELS>
 where
-    Sample: sample::Sample + sample::FromSample<f64> + core::ops::AddAssign,
-    [Sample; CHANNELS]: frame::Frame<Sample = Sample>,
+    [f32; CHANNELS]: frame::Frame<Sample = f32>,
 {
-    type Error = std::io::Error;
-    type Sample = Sample;
-    type Frame = [Sample; CHANNELS];
+    type Error = io::Error;
+    type Sample = f32;
+    type Frame = [f32; CHANNELS];
 
     #[inline]
     fn skip(&mut self, frames: usize) -> Result<(), Self::Error> {
-        let samples = CHANNELS * frames;
-        self.cursor += samples;
+        for _ in 0..frames {
+            self.write(<Self::Frame as frame::Frame>::EQUILIBRIUM)?;
+        }
 
         Ok(())
     }
 
     #[inline]
     fn write(&mut self, frame: Self::Frame) -> Result<(), Self::Error> {
-        let cursor = self.cursor;
-        let new_cursor = cursor + CHANNELS;
-        if self.buffer.len() <= new_cursor {
-            self.buffer.resize(new_cursor, Sample::EQUILIBRIUM);
-        }
+        self.encoder.write(&frame)?;
+        self.frames += 1;
 
-        for (idx, sample) in frame.channels().enumerate() {
-            unsafe { *self.buffer.get_unchecked_mut(cursor + idx) += sample };
-        }
+        Ok(())
+    }
+}
 
-        self.cursor = new_cursor;
+/// Drops the mixed frames so they can be measured without writing them anywhere
+struct Discard<const CHANNELS: usize>;
 
+impl<const CHANNELS: usize> euphony_mix::Writer for Discard<CHANNELS>
+where
+    [f32; CHANNELS]: frame::Frame<Sample = f32>,
+{
+    type Error = io::Error;
+    type Sample = f32;
+    type Frame = [f32; CHANNELS];
+
+    #[inline]
+    fn skip(&mut self, _frames: usize) -> Result<(), Self::Error> {
         Ok(())
     }
+
+    #[inline]
+    fn write(&mut self, _frame: Self::Frame) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+fn wav_error(err: hound::Error) -> io::Error {
+    match err {
+        hound::Error::IoError(err) => err,
+        err => io::Error::other(err),
+    }
 }
@@ -0,0 +1,112 @@
+/// Quantizes samples to integers with TPDF dither and noise shaping
+///
+/// The dither is seeded with a constant so rendering the same project twice produces identical
+/// files.
+#[derive(Debug)]
+pub struct Dither {
+    scale: f64,
+    min: f64,
+    max: f64,
+    rng: u64,
+    /// The last two quantization errors for each channel
+    errors: Vec<[f64; 2]>,
+}
+
+impl Dither {
+    pub fn new(channels: usize, bits: u16) -> Self {
+        let scale = (1u64 << (bits - 1)) as f64;
+        Self {
+            scale,
+            min: -scale,
+            max: scale - 1.0,
+            rng: 0x9e37_79b9_7f4a_7c15,
+            errors: vec![[0.0; 2]; channels],
+        }
+    }
+
+    #[inline]
+    pub fn quantize(&mut self, channel: usize, sample: f32) -> i32 {
+        let [e1, e2] = self.errors[channel];
+
+        // shape the error with (1 - z^-1)^2 to push it above the range we're most sensitive to
+        let target = sample as f64 * self.scale - 2.0 * e1 + e2;
+
+        // the difference of two uniform values gives a triangular distribution of +/- 1 LSB
+        let noise = self.uniform() - self.uniform();
+        let value = (target + noise).round().clamp(self.min, self.max);
+
+        // clipped samples would otherwise feed back an error that grows without bound
+        let error = (value - target).clamp(-1.0, 1.0);
+        self.errors[channel] = [error, e1];
+
+        value as i32
+    }
+
+    #[inline]
+    fn uniform(&mut self) -> f64 {
+        // xorshift64*
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let value = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_test() {
+        for bits in [16, 24] {
+            let mut dither = Dither::new(1, bits);
+            let max = (1i32 << (bits - 1)) - 1;
+            let min = -(1i32 << (bits - 1));
+
+            // clipped input stays in range and doesn't destabilize the shaping filter
+            for sample in [1.5, 1.0, -1.0, -1.5, 0.0, 0.5, -0.5] {
+                for _ in 0..1000 {
+                    let value = dither.quantize(0, sample);
+                    assert!((min..=max).contains(&value), "{bits} {sample} {value}");
+                }
+            }
+
+            // silence only contains the shaped dither, which stays within a few LSBs
+            for _ in 0..10_000 {
+                assert!(dither.quantize(0, 0.0).abs() <= 4);
+            }
+        }
+    }
+
+    #[test]
+    fn mean_test() {
+        let mut dither = Dither::new(2, 16);
+        let sample = 0.25f32 + 0.3 / 32768.0;
+        let expected = sample as f64 * 32768.0;
+
+        let len = 100_000;
+        let mut sums = [0.0; 2];
+        for _ in 0..len {
+            for (channel, sum) in sums.iter_mut().enumerate() {
+                *sum += dither.quantize(channel, sample) as f64;
+            }
+        }
+
+        // the dither keeps the fractional part of the signal on average
+        for sum in sums {
+            assert!((sum / len as f64 - expected).abs() < 0.01, "{sum}");
+        }
+    }
+
+    #[test]
+    fn deterministic_test() {
+        let render = || {
+            let mut dither = Dither::new(1, 16);
+            (0..1000)
+                .map(|idx| dither.quantize(0, (idx as f32 * 0.01).sin()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(render(), render());
+    }
+}
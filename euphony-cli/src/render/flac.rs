@@ -0,0 +1,554 @@
+//! A FLAC encoder using fixed predictors and Rice coded residuals
+//!
+//! It doesn't compress as well as the reference encoder's LPC search but it's lossless and fast
+//! enough to run alongside the mixer.
+
+use std::{
+    fs,
+    io::{self, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const BLOCK_SIZE: usize = 4096;
+const MAX_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 8;
+/// Parameters stop at 14 since 15 escapes to unencoded residuals
+const MAX_RICE_PARAM: u32 = 14;
+
+pub struct Encoder {
+    out: io::BufWriter<fs::File>,
+    channels: usize,
+    bits: u16,
+    sample_rate: u32,
+    /// The samples of the current block for each channel
+    block: Vec<Vec<i64>>,
+    frame_number: u64,
+    total_samples: u64,
+    min_frame_size: u32,
+    max_frame_size: u32,
+}
+
+impl Encoder {
+    pub fn create(path: &Path, channels: u16, sample_rate: u32, bits: u16) -> io::Result<Self> {
+        let out = fs::File::create(path)?;
+        let mut out = io::BufWriter::new(out);
+
+        out.write_all(b"fLaC")?;
+
+        let mut encoder = Self {
+            out,
+            channels: channels as usize,
+            bits,
+            sample_rate,
+            block: vec![Vec::with_capacity(BLOCK_SIZE); channels as usize],
+            frame_number: 0,
+            total_samples: 0,
+            min_frame_size: 0,
+            max_frame_size: 0,
+        };
+
+        // the sizes aren't known until the end so this is written again in `finish`
+        encoder.write_stream_info()?;
+
+        Ok(encoder)
+    }
+
+    /// Writes a single interleaved frame
+    #[inline]
+    pub fn write(&mut self, frame: &[i32]) -> io::Result<()> {
+        for (channel, sample) in self.block.iter_mut().zip(frame) {
+            channel.push(*sample as i64);
+        }
+
+        self.total_samples += 1;
+
+        if self.block[0].len() == BLOCK_SIZE {
+            self.write_frame()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if !self.block[0].is_empty() {
+            self.write_frame()?;
+        }
+
+        self.out.seek(SeekFrom::Start(4))?;
+        self.write_stream_info()?;
+        self.out.flush()?;
+
+        Ok(())
+    }
+
+    fn write_stream_info(&mut self) -> io::Result<()> {
+        let mut w = BitWriter::default();
+
+        // the last metadata block is the STREAMINFO block with a length of 34 bytes
+        w.write(1, 1);
+        w.write(7, 0);
+        w.write(24, 34);
+
+        w.write(16, BLOCK_SIZE as u64);
+        w.write(16, BLOCK_SIZE as u64);
+        w.write(24, self.min_frame_size as u64);
+        w.write(24, self.max_frame_size as u64);
+        w.write(20, self.sample_rate as u64);
+        w.write(3, self.channels as u64 - 1);
+        w.write(5, self.bits as u64 - 1);
+        w.write(36, self.total_samples);
+        // an empty MD5 signals that it wasn't computed
+        w.write(64, 0);
+        w.write(64, 0);
+
+        self.out.write_all(&w.finish())
+    }
+
+    fn write_frame(&mut self) -> io::Result<()> {
+        let len = self.block[0].len();
+        let mut w = BitWriter::default();
+
+        // sync code and a fixed block size
+        w.write(16, 0xfff8);
+        // the block size is stored at the end of the header and the sample rate is read from
+        // STREAMINFO
+        w.write(4, 0b0111);
+        w.write(4, 0b0000);
+        w.write(4, self.channels as u64 - 1);
+        // some decoders don't read the sample size from STREAMINFO so it's always written here
+        w.write(3, sample_size_code(self.bits));
+        w.write(1, 0);
+        w.write_utf8(self.frame_number);
+        w.write(16, len as u64 - 1);
+
+        let crc = crc8(w.bytes());
+        w.write(8, crc as u64);
+
+        for channel in &self.block {
+            write_subframe(&mut w, channel, self.bits as u32);
+        }
+
+        w.align();
+        let crc = crc16(w.bytes());
+        w.write(16, crc as u64);
+
+        let frame = w.finish();
+        self.out.write_all(&frame)?;
+
+        let size = frame.len() as u32;
+        if self.frame_number == 0 {
+            self.min_frame_size = size;
+        } else {
+            self.min_frame_size = self.min_frame_size.min(size);
+        }
+        self.max_frame_size = self.max_frame_size.max(size);
+
+        self.frame_number += 1;
+        for channel in &mut self.block {
+            channel.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the frame header code for a sample size
+fn sample_size_code(bits: u16) -> u64 {
+    match bits {
+        8 => 0b001,
+        12 => 0b010,
+        16 => 0b100,
+        20 => 0b101,
+        24 => 0b110,
+        32 => 0b111,
+        // read from STREAMINFO
+        _ => 0b000,
+    }
+}
+
+fn write_subframe(w: &mut BitWriter, samples: &[i64], bits: u32) {
+    let first = samples[0];
+    if samples.iter().all(|sample| *sample == first) {
+        w.write(8, 0b0000_0000);
+        w.write_signed(bits, first);
+        return;
+    }
+
+    let verbatim = samples.len() as u64 * bits as u64;
+    let mut best: Option<(usize, Residual)> = None;
+    let mut residuals = vec![0; samples.len()];
+
+    for order in 0..=MAX_ORDER.min(samples.len() - 1) {
+        predict(samples, order, &mut residuals);
+        let mut residual = Residual::new(&residuals[order..], samples.len(), order);
+        // include the warm up samples
+        residual.cost += order as u64 * bits as u64;
+
+        if best
+            .as_ref()
+            .map_or(true, |(_, best)| residual.cost < best.cost)
+        {
+            best = Some((order, residual));
+        }
+    }
+
+    match best {
+        Some((order, residual)) if residual.cost < verbatim => {
+            w.write(8, (0b0000_1000 | order as u64) << 1);
+            for sample in &samples[..order] {
+                w.write_signed(bits, *sample);
+            }
+
+            predict(samples, order, &mut residuals);
+            residual.write(w, &residuals[order..]);
+        }
+        _ => {
+            w.write(8, 0b0000_0010);
+            for sample in samples {
+                w.write_signed(bits, *sample);
+            }
+        }
+    }
+}
+
+/// Computes the residuals of the fixed predictor of the given order
+fn predict(samples: &[i64], order: usize, residuals: &mut [i64]) {
+    for idx in order..samples.len() {
+        let s = |offset: usize| samples[idx - offset];
+        let prediction = match order {
+            0 => 0,
+            1 => s(1),
+            2 => 2 * s(1) - s(2),
+            3 => 3 * s(1) - 3 * s(2) + s(3),
+            _ => 4 * s(1) - 6 * s(2) + 4 * s(3) - s(4),
+        };
+        residuals[idx] = samples[idx] - prediction;
+    }
+}
+
+/// The partitioning and Rice parameters with the lowest estimated cost for a set of residuals
+struct Residual {
+    partition_order: u32,
+    params: Vec<u32>,
+    /// The number of residuals in each partition
+    lens: Vec<u64>,
+    cost: u64,
+}
+
+impl Residual {
+    fn new(residuals: &[i64], block_size: usize, order: usize) -> Self {
+        // sum the residuals in the finest partitions and merge them for the coarser orders
+        let mut max_order = 0;
+        while max_order < MAX_PARTITION_ORDER
+            && block_size % (1 << (max_order + 1)) == 0
+            && (block_size >> (max_order + 1)) > order
+        {
+            max_order += 1;
+        }
+
+        let partitions = 1usize << max_order;
+        let partition_len = block_size >> max_order;
+        let mut sums = vec![0u64; partitions];
+        let mut lens = vec![partition_len as u64; partitions];
+        lens[0] -= order as u64;
+
+        let mut offset = 0;
+        for (sum, len) in sums.iter_mut().zip(&lens) {
+            let len = *len as usize;
+            *sum = residuals[offset..offset + len]
+                .iter()
+                .map(|r| zigzag(*r))
+                .sum();
+            offset += len;
+        }
+
+        let mut best: Option<Self> = None;
+        let mut partition_order = max_order;
+
+        loop {
+            let mut params = vec![];
+            // the coding method and partition order
+            let mut cost = 2 + 4;
+
+            for (sum, len) in sums.iter().zip(&lens) {
+                let (param, bits) = rice_param(*sum, *len);
+                params.push(param);
+                cost += 4 + bits;
+            }
+
+            if best.as_ref().map_or(true, |best| cost < best.cost) {
+                best = Some(Self {
+                    partition_order,
+                    params,
+                    lens: lens.clone(),
+                    cost,
+                });
+            }
+
+            if partition_order == 0 {
+                break;
+            }
+
+            partition_order -= 1;
+            sums = sums.chunks(2).map(|pair| pair[0] + pair[1]).collect();
+            lens = lens.chunks(2).map(|pair| pair[0] + pair[1]).collect();
+        }
+
+        best.unwrap()
+    }
+
+    fn write(&self, w: &mut BitWriter, residuals: &[i64]) {
+        w.write(2, 0b00);
+        w.write(4, self.partition_order as u64);
+
+        let mut offset = 0;
+        for (param, len) in self.params.iter().zip(&self.lens) {
+            let param = *param;
+            let len = *len as usize;
+
+            w.write(4, param as u64);
+            for residual in &residuals[offset..offset + len] {
+                let value = zigzag(*residual);
+                w.write_unary(value >> param);
+                w.write(param, value & ((1 << param) - 1));
+            }
+            offset += len;
+        }
+    }
+}
+
+/// Estimates the best Rice parameter for a partition, returning it along with its cost in bits
+fn rice_param(sum: u64, len: u64) -> (u32, u64) {
+    let cost = |param: u32| len * (param as u64 + 1) + (sum >> param);
+
+    let mut best = (0, cost(0));
+    for param in 1..=MAX_RICE_PARAM {
+        let bits = cost(param);
+        if bits < best.1 {
+            best = (param, bits);
+        }
+    }
+    best
+}
+
+#[inline]
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    len: u32,
+}
+
+impl BitWriter {
+    #[inline]
+    fn write(&mut self, bits: u32, value: u64) {
+        if bits > 32 {
+            self.write(bits - 32, value >> 32);
+            self.write(32, value & 0xffff_ffff);
+            return;
+        }
+
+        if bits == 0 {
+            return;
+        }
+
+        self.acc = (self.acc << bits) | (value & ((1 << bits) - 1));
+        self.len += bits;
+
+        while self.len >= 8 {
+            self.len -= 8;
+            self.bytes.push((self.acc >> self.len) as u8);
+        }
+    }
+
+    #[inline]
+    fn write_signed(&mut self, bits: u32, value: i64) {
+        self.write(bits, value as u64 & ((1 << bits) - 1));
+    }
+
+    #[inline]
+    fn write_unary(&mut self, mut zeros: u64) {
+        while zeros >= 32 {
+            self.write(32, 0);
+            zeros -= 32;
+        }
+        self.write(zeros as u32 + 1, 1);
+    }
+
+    /// Writes a value with the UTF-8 style coding used for frame numbers
+    fn write_utf8(&mut self, value: u64) {
+        if value < 0x80 {
+            self.write(8, value);
+            return;
+        }
+
+        let mut bytes = 2;
+        while value >= 1 << (5 * bytes + 1) {
+            bytes += 1;
+        }
+
+        let lead = (0xff00u64 >> bytes) & 0xff;
+        self.write(8, lead | (value >> (6 * (bytes - 1))));
+        for idx in (0..bytes - 1).rev() {
+            self.write(8, 0x80 | ((value >> (6 * idx)) & 0x3f));
+        }
+    }
+
+    fn align(&mut self) {
+        if self.len > 0 {
+            self.write(8 - self.len, 0);
+        }
+    }
+
+    /// Returns the bytes that have been completely written
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_utf8(value: u64) -> Vec<u8> {
+        let mut w = BitWriter::default();
+        w.write_utf8(value);
+        w.finish()
+    }
+
+    #[test]
+    fn crc_test() {
+        // the check values for CRC-8 and CRC-16/UMTS, which are the variants FLAC uses
+        assert_eq!(crc8(b"123456789"), 0xf4);
+        assert_eq!(crc16(b"123456789"), 0xfee8);
+        assert_eq!(crc8(&[]), 0);
+        assert_eq!(crc16(&[]), 0);
+    }
+
+    #[test]
+    fn utf8_test() {
+        for value in [0, 0x7f, 0x80, 0x7ff, 0x800, 0xffff, 0x10000, 0x10ffff] {
+            let mut expected = [0; 4];
+            let expected = char::from_u32(value as u32)
+                .unwrap()
+                .encode_utf8(&mut expected)
+                .as_bytes();
+            assert_eq!(encode_utf8(value), expected, "{value:#x}");
+        }
+
+        // frame numbers go past the range of chars with the same pattern
+        assert_eq!(
+            encode_utf8(0x7fff_ffff),
+            [0xfd, 0xbf, 0xbf, 0xbf, 0xbf, 0xbf]
+        );
+    }
+
+    fn round_trip(name: &str, channels: u16, frames: &[Vec<i32>]) {
+        let path =
+            std::env::temp_dir().join(format!("euphony-flac-{}-{name}.flac", std::process::id()));
+
+        let mut encoder = Encoder::create(&path, channels, 48_000, 24).unwrap();
+        for frame in frames {
+            encoder.write(frame).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let mut reader = claxon::FlacReader::open(&path).unwrap();
+        let info = reader.streaminfo();
+        assert_eq!(info.channels, channels as u32);
+        assert_eq!(info.sample_rate, 48_000);
+        assert_eq!(info.bits_per_sample, 24);
+        assert_eq!(info.samples, Some(frames.len() as u64));
+
+        let decoded = reader.samples().collect::<Result<Vec<_>, _>>().unwrap();
+        let expected: Vec<_> = frames.iter().flatten().copied().collect();
+        assert_eq!(decoded.len(), expected.len());
+        // compare in chunks so a failure doesn't print the whole stream
+        for (idx, (decoded, expected)) in decoded
+            .chunks(BLOCK_SIZE)
+            .zip(expected.chunks(BLOCK_SIZE))
+            .enumerate()
+        {
+            assert_eq!(decoded, expected, "{name} chunk {idx}");
+        }
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        const MAX: i32 = (1 << 23) - 1;
+        const MIN: i32 = -(1 << 23);
+
+        let mut rng = 0x2545_f491_4f6c_dd1du64;
+        let mut noise = move || {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            (rng >> 40) as i32 - (1 << 23)
+        };
+
+        // spans several blocks and leaves a partial one at the end
+        let len = BLOCK_SIZE * 3 + 123;
+        let frames: Vec<_> = (0..len)
+            .map(|idx| {
+                let t = idx as f64 / 48_000.0;
+                vec![
+                    // silence and constants are written as constant subframes
+                    if idx < BLOCK_SIZE { 0 } else { 1234 },
+                    ((t * 440.0 * core::f64::consts::TAU).sin() * MAX as f64) as i32,
+                    // noise at the full range falls back to verbatim subframes
+                    noise(),
+                    if idx % 2 == 0 { MAX } else { MIN },
+                    (idx as i32 * 997) % MAX,
+                ]
+            })
+            .collect();
+
+        round_trip("surround", 5, &frames);
+
+        let mono: Vec<_> = frames.iter().map(|frame| vec![frame[1]]).collect();
+        round_trip("mono", 1, &mono);
+
+        round_trip("short", 2, &[vec![1, -1], vec![MAX, MIN], vec![0, 0]]);
+    }
+}
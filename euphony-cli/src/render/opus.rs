@@ -0,0 +1,264 @@
+//! Writes Opus packets in an Ogg container for compressed previews
+
+use audiopus_sys as sys;
+use core::ptr::NonNull;
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use std::{ffi::CStr, fs, io, path::Path};
+
+/// Ogg pages need a stream serial number but we only ever write one stream
+const SERIAL: u32 = u32::from_be_bytes(*b"euph");
+
+/// The granule positions are always counted at 48kHz, regardless of the input rate
+const GRANULE_RATE: u32 = 48000;
+
+/// The recommended maximum size of a packet
+const MAX_PACKET: usize = 4000;
+
+/// The sample rates supported by the encoder
+const SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+pub struct Encoder {
+    inner: Coder,
+    out: PacketWriter<io::BufWriter<fs::File>>,
+    channels: usize,
+    granule_scale: u64,
+    lookahead: u64,
+    /// The number of frames in each packet
+    packet_len: usize,
+    pending: Vec<f32>,
+    packet: Vec<u8>,
+    /// The frames passed to the encoder, including the padding for its lookahead
+    encoded: u64,
+    /// The frames written by the mixer
+    written: u64,
+}
+
+impl Encoder {
+    pub fn create(path: &Path, channels: u16, sample_rate: u32) -> io::Result<Self> {
+        if !SAMPLE_RATES.contains(&sample_rate) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "opus doesn't support a sample rate of {sample_rate}; \
+                     expected one of 8000, 12000, 16000, 24000 or 48000"
+                ),
+            ));
+        }
+
+        let mut inner = Coder::new(sample_rate, channels)?;
+        inner.set_bitrate(64_000 * channels as i32)?;
+        let lookahead = inner.lookahead()?;
+
+        let granule_scale = (GRANULE_RATE / sample_rate) as u64;
+
+        let out = fs::File::create(path)?;
+        let mut out = PacketWriter::new(io::BufWriter::new(out));
+
+        let mut head = vec![];
+        head.extend_from_slice(b"OpusHead");
+        head.push(1);
+        head.push(channels as u8);
+        head.extend_from_slice(&((lookahead * granule_scale) as u16).to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes());
+        // the output gain and channel mapping family
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+        out.write_packet(head.into(), SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+        let vendor = b"euphony";
+        let mut tags = vec![];
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        out.write_packet(tags.into(), SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+        // 20ms packets
+        let packet_len = sample_rate as usize / 50;
+
+        Ok(Self {
+            inner,
+            out,
+            channels: channels as usize,
+            granule_scale,
+            lookahead,
+            packet_len,
+            pending: Vec::with_capacity(packet_len * channels as usize),
+            packet: vec![0; MAX_PACKET],
+            encoded: 0,
+            written: 0,
+        })
+    }
+
+    /// Writes a single interleaved frame
+    #[inline]
+    pub fn write(&mut self, frame: &[f32]) -> io::Result<()> {
+        self.pending.extend_from_slice(frame);
+        self.written += 1;
+
+        if self.pending.len() == self.packet_len * self.channels {
+            self.encode(PacketWriteEndInfo::NormalPacket)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        // flush the encoder's lookahead with silence and trim it with the final granule position
+        let end = self.written + self.lookahead;
+
+        loop {
+            let is_last = self.encoded + self.packet_len as u64 >= end;
+            self.pending.resize(self.packet_len * self.channels, 0.0);
+
+            if is_last {
+                self.encode(PacketWriteEndInfo::EndStream)?;
+                break;
+            }
+
+            self.encode(PacketWriteEndInfo::NormalPacket)?;
+        }
+
+        let mut out = self.out.into_inner();
+        io::Write::flush(&mut out)?;
+
+        Ok(())
+    }
+
+    fn encode(&mut self, info: PacketWriteEndInfo) -> io::Result<()> {
+        let len = self
+            .inner
+            .encode(&self.pending, self.packet_len, &mut self.packet)?;
+        self.pending.clear();
+        self.encoded += self.packet_len as u64;
+
+        let mut granule = self.encoded;
+        if info == PacketWriteEndInfo::EndStream {
+            granule = self.written + self.lookahead;
+        }
+
+        let packet: Box<[u8]> = self.packet[..len].into();
+        self.out
+            .write_packet(packet, SERIAL, info, granule * self.granule_scale)?;
+
+        Ok(())
+    }
+}
+
+/// An encoder state allocated by libopus
+struct Coder(NonNull<sys::OpusEncoder>);
+
+// the state isn't shared with anything else
+unsafe impl Send for Coder {}
+
+impl Coder {
+    fn new(sample_rate: u32, channels: u16) -> io::Result<Self> {
+        let mut error = 0;
+        let state = unsafe {
+            sys::opus_encoder_create(
+                sample_rate as _,
+                channels as _,
+                sys::OPUS_APPLICATION_AUDIO,
+                &mut error,
+            )
+        };
+        check(error)?;
+        let state =
+            NonNull::new(state).ok_or_else(|| io::Error::other("opus: allocation failed"))?;
+        Ok(Self(state))
+    }
+
+    fn set_bitrate(&mut self, bits_per_second: i32) -> io::Result<()> {
+        let res = unsafe {
+            sys::opus_encoder_ctl(
+                self.0.as_ptr(),
+                sys::OPUS_SET_BITRATE_REQUEST,
+                bits_per_second,
+            )
+        };
+        check(res)
+    }
+
+    /// Returns the number of frames the encoder delays its output by
+    fn lookahead(&mut self) -> io::Result<u64> {
+        let mut frames: i32 = 0;
+        let res = unsafe {
+            sys::opus_encoder_ctl(
+                self.0.as_ptr(),
+                sys::OPUS_GET_LOOKAHEAD_REQUEST,
+                &mut frames as *mut i32,
+            )
+        };
+        check(res)?;
+        Ok(frames as u64)
+    }
+
+    /// Encodes `frames` interleaved frames into `packet`, returning the length of the packet
+    fn encode(&mut self, pcm: &[f32], frames: usize, packet: &mut [u8]) -> io::Result<usize> {
+        let len = unsafe {
+            sys::opus_encode_float(
+                self.0.as_ptr(),
+                pcm.as_ptr(),
+                frames as _,
+                packet.as_mut_ptr(),
+                packet.len() as _,
+            )
+        };
+        check(len)?;
+        Ok(len as usize)
+    }
+}
+
+impl Drop for Coder {
+    fn drop(&mut self) {
+        unsafe { sys::opus_encoder_destroy(self.0.as_ptr()) }
+    }
+}
+
+/// Converts the negative error codes returned by libopus
+fn check(code: i32) -> io::Result<()> {
+    if code >= 0 {
+        return Ok(());
+    }
+
+    let message = unsafe { CStr::from_ptr(sys::opus_strerror(code)) };
+    Err(io::Error::other(format!(
+        "opus: {}",
+        message.to_string_lossy()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ogg_test() {
+        let path = std::env::temp_dir().join(format!("euphony-opus-{}.opus", std::process::id()));
+
+        let mut encoder = Encoder::create(&path, 2, 48000).unwrap();
+        let lookahead = encoder.lookahead;
+        for idx in 0..48000 {
+            let sample = (idx as f32 * 440.0 / 48000.0 * core::f32::consts::TAU).sin();
+            encoder.write(&[sample, sample]).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let contents = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // the identification header starts the stream in its own page
+        assert_eq!(&contents[..4], b"OggS");
+        assert_eq!(&contents[28..36], b"OpusHead");
+
+        // the last page's granule position trims the padding for the lookahead
+        let last = contents
+            .windows(4)
+            .rposition(|window| window == b"OggS")
+            .unwrap();
+        let granule = u64::from_le_bytes(contents[last + 6..last + 14].try_into().unwrap());
+        assert_eq!(granule, 48000 + lookahead);
+
+        assert!(Encoder::create(&path, 2, 44100).is_err());
+    }
+}
@@ -47,6 +47,7 @@ async fn serve(serve: &Serve) -> Result<()> {
 
     // TODO return index view
 
+    // clients read the timelines and then fetch group overviews from `contents/<hash>`
     let routes = updates
         .or(warp::fs::dir(manifest.root.join("target/euphony")))
         .with(warp::cors().allow_any_origin().allow_method("GET"));
@@ -0,0 +1,192 @@
+use crate::{build::Build, compiler::Compiler, Result};
+use anyhow::{anyhow, bail};
+use euphony_compiler::Hash;
+use euphony_store::storage::Storage;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Builds the project twice and checks that both builds produce identical output
+#[derive(Debug, StructOpt)]
+pub struct Verify {
+    #[structopt(flatten)]
+    build: Build,
+
+    /// The number of rendering threads for each build
+    ///
+    /// Pass it twice to use a different number of threads for the second build.
+    #[structopt(long, number_of_values = 1, max_values = 2)]
+    threads: Vec<usize>,
+
+    /// Overrides `EUPHONY_SEED` for both builds
+    #[structopt(long)]
+    seed: Option<String>,
+}
+
+impl Verify {
+    pub fn run(&self) -> Result<()> {
+        if self.build.is_stdin() {
+            bail!("stdin can only be read once; pass a project or file to verify");
+        }
+
+        if let Some(seed) = self.seed.as_ref() {
+            std::env::set_var("EUPHONY_SEED", seed);
+        }
+
+        let threads = match self.threads.as_slice() {
+            [] => [0, 0],
+            [threads] => [*threads, *threads],
+            [first, second, ..] => [*first, *second],
+        };
+
+        let first = self.build_with_threads("a", threads[0])?;
+        let second = self.build_with_threads("b", threads[1])?;
+
+        if first.len() != second.len() {
+            bail!(
+                "the builds produced {} and {} timelines",
+                first.len(),
+                second.len()
+            );
+        }
+
+        let mut sinks = 0;
+        for (a, b) in first.iter().zip(&second) {
+            sinks += compare(a, b)?;
+        }
+
+        log::info!("verified {} timelines with {} sinks", first.len(), sinks);
+
+        Ok(())
+    }
+
+    fn build_with_threads(&self, name: &str, threads: usize) -> Result<Vec<Compiler>> {
+        let out_dir = PathBuf::from("target/euphony/verify").join(name);
+
+        // start from an empty store so every sink is rendered again
+        if out_dir.exists() {
+            fs::remove_dir_all(&out_dir)?;
+        }
+
+        let mut build = self.build.clone();
+        build.set_out_dir(out_dir);
+
+        // `0` lets rayon pick the number of threads
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?;
+
+        log::info!(
+            "building {:?} with {} threads",
+            name,
+            pool.current_num_threads()
+        );
+
+        pool.install(|| build.build())
+    }
+}
+
+/// Compares the timelines of both compilers, returning the number of sinks that were checked
+fn compare(a: &Compiler, b: &Compiler) -> Result<usize> {
+    let timeline = a.timeline_path().display();
+    let a = a.store();
+    let b = b.store();
+
+    if a.timeline.sample_rate != b.timeline.sample_rate {
+        bail!("{timeline}: the sample rates differ");
+    }
+
+    if a.timeline.groups.len() != b.timeline.groups.len() {
+        bail!(
+            "{timeline}: the builds produced {} and {} groups",
+            a.timeline.groups.len(),
+            b.timeline.groups.len()
+        );
+    }
+
+    let mut sinks = 0;
+
+    for (group_a, group_b) in a.timeline.groups.iter().zip(&b.timeline.groups) {
+        let group = &group_a.name;
+        if group != &group_b.name {
+            bail!(
+                "{timeline}: expected group {group:?} but found {:?}",
+                group_b.name
+            );
+        }
+
+        if group_a.midi.as_deref() != group_b.midi.as_deref() {
+            bail!("{timeline}: group {group:?} diverged in its midi output");
+        }
+
+        let entries_a = a.storage.open_group(&group_a.entries)?;
+        let mut entries_b = b.storage.open_group(&group_b.entries)?;
+
+        for entry_a in entries_a {
+            let entry_a = entry_a?;
+            let entry_b = entries_b
+                .next()
+                .transpose()?
+                .ok_or_else(|| anyhow!("{timeline}: group {group:?} has fewer sinks"))?;
+
+            if entry_a.sample_offset != entry_b.sample_offset || entry_a.hash != entry_b.hash {
+                bail!(
+                    "{timeline}: group {group:?} diverged at sink {} (sample {}); found sink {} (sample {})",
+                    display(&entry_a.hash),
+                    entry_a.sample_offset,
+                    display(&entry_b.hash),
+                    entry_b.sample_offset,
+                );
+            }
+
+            // the sink hashes only cover the inputs so compare the rendered samples
+            let samples_a = a.storage.open_sink(&entry_a.hash)?;
+            let mut samples_b = b.storage.open_sink(&entry_b.hash)?;
+
+            for (idx, sample_a) in samples_a.enumerate() {
+                let sample_a = sample_a?;
+                let sample_b = samples_b.next().transpose()?;
+
+                let is_equal = sample_b.is_some_and(|sample_b| {
+                    sample_a.value.to_bits() == sample_b.value.to_bits()
+                        && sample_a.coordinate == sample_b.coordinate
+                });
+
+                if !is_equal {
+                    bail!(
+                        "{timeline}: group {group:?} diverged in sink {} at sample {}",
+                        display(&entry_a.hash),
+                        entry_a.sample_offset + idx as u64,
+                    );
+                }
+            }
+
+            if samples_b.next().is_some() {
+                bail!(
+                    "{timeline}: group {group:?} diverged in sink {}: the second build rendered more samples",
+                    display(&entry_a.hash),
+                );
+            }
+
+            sinks += 1;
+        }
+
+        if entries_b.next().is_some() {
+            bail!("{timeline}: group {group:?} has more sinks in the second build");
+        }
+
+        if *group_a.entries != *group_b.entries {
+            bail!("{timeline}: group {group:?} diverged in its hash");
+        }
+    }
+
+    Ok(sinks)
+}
+
+fn display(hash: &Hash) -> String {
+    use std::fmt::Write;
+
+    hash.iter().fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{byte:02x}");
+        out
+    })
+}
@@ -13,7 +13,7 @@ pub trait Subscriptions<Context> {
 }
 
 pub fn watch_manifest<S: Subscriptions<Manifest>>(mut subs: S, mut manifest: Manifest) {
-    let (tx, rx) = channel();
+    let (tx, events) = channel();
 
     let mut watcher = watcher(tx, Duration::from_millis(100)).unwrap();
 
@@ -21,13 +21,33 @@ pub fn watch_manifest<S: Subscriptions<Manifest>>(mut subs: S, mut manifest: Man
         .watch(&manifest.root, RecursiveMode::Recursive)
         .unwrap();
 
-    // compile the project after we've set up watchers
-    let _ = manifest.compile();
-
     let mut updates = HashSet::new();
     let target_filter = manifest.root.join("target");
+
+    // forward the events while watching for source changes so any in-progress build can be
+    // cancelled as soon as a newer one is queued
+    let (tx, rx) = channel();
+    {
+        let progress = manifest.progress.clone();
+        let target_filter = target_filter.clone();
+        std::thread::spawn(move || {
+            for event in events {
+                if is_source_change(&event, &target_filter) {
+                    progress.cancel();
+                }
+
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
     let target_filter = Path::new(&target_filter);
 
+    // compile the project after we've set up watchers
+    let _ = manifest.compile();
+
     loop {
         fn map_event(event: DebouncedEvent) -> Option<PathBuf> {
             use DebouncedEvent::*;
@@ -83,6 +103,17 @@ pub fn watch_manifest<S: Subscriptions<Manifest>>(mut subs: S, mut manifest: Man
     }
 }
 
+fn is_source_change(event: &DebouncedEvent, target_filter: &Path) -> bool {
+    use DebouncedEvent::*;
+    match event {
+        Create(path) | Write(path) | Chmod(path) | Rename(_, path) => {
+            path.extension() != Some(OsStr::new("euph"))
+                && path.strip_prefix(target_filter).is_err()
+        }
+        _ => false,
+    }
+}
+
 // TODO use this in the remote version
 #[allow(dead_code)]
 pub fn watch_directory<S: Subscriptions<()>>(mut subs: S, root: PathBuf) {
@@ -14,7 +14,9 @@ pub fn set_stdout() {
 }
 
 pub fn set_writer<W: io::Write + 'static>(out: W) {
-    let out = handler::Writer(out);
+    let mut out = handler::Writer(out);
+    out.header(Header::default())
+        .expect("failed to emit header to output");
     let out = Box::new(out);
     scope::set(Some(out));
 }
@@ -45,6 +47,30 @@ emit!(set_timing(
     }
 ));
 
+emit!(ramp_timing(|nanos_per_tick: Duration, ticks: u64| {
+    RampTiming {
+        nanos_per_tick: nanos_per_tick.as_nanos() as _,
+        ticks,
+    }
+}));
+
+emit!(source_location(|location: &std::panic::Location| {
+    SourceLocation {
+        file: location.file().to_string(),
+        line: location.line(),
+        column: location.column(),
+    }
+}));
+
+emit!(marker(|name: &str| Marker {
+    name: name.to_string(),
+}));
+
+emit!(set_time_signature(|beats: u64, unit: u64| SetTimeSignature {
+    beats,
+    unit
+}));
+
 emit!(create_group(|id: u64, name: &str| CreateGroup {
     id,
     name: name.to_string(),
@@ -0,0 +1,824 @@
+//! Textual assembly form of the command stream
+//!
+//! Each line contains a single message: a mnemonic followed by comma-separated `key = value`
+//! fields. Leading whitespace is ignored and `#` starts a comment that runs to the end of the line.
+//!
+//! ```text
+//! HEADER version = 1, features = 0x0
+//!   SET_TIMING nanos_per_tick = 1000000, ticks_per_beat = 64
+//!   GROUP id = 1, name = "lead"
+//!   SPAWN id = 0, processor = sink, group = 1
+//!   SPAWN id = 1, processor = osc::Sine
+//!   SET node = 1, param = 0, value = 440
+//!   PIPE node = 0, param = 0, source = 1
+//!   MIDI data = [144, 60, 127], group = 1
+//! ADVANCE ticks = 64 # one beat
+//!   FIN node = 1
+//!   FIN node = 0
+//! ```
+//!
+//! | mnemonic             | fields                                |
+//! |----------------------|---------------------------------------|
+//! | `HEADER`             | `version`, `features`                 |
+//! | `ADVANCE`            | `ticks`                               |
+//! | `SET_TIMING`         | `nanos_per_tick`, `ticks_per_beat`    |
+//! | `RAMP_TIMING`        | `nanos_per_tick`, `ticks`             |
+//! | `LOCATION`           | `file`, `line`, `column`              |
+//! | `MARKER`             | `name`                                |
+//! | `SET_TIME_SIGNATURE` | `beats`, `unit`                       |
+//! | `GROUP`              | `id`, `name`                          |
+//! | `SPAWN`              | `id`, `processor`, `group` (optional) |
+//! | `FORK`               | `source`, `target`                    |
+//! | `MIDI`               | `data`, `group` (optional)            |
+//! | `SET`                | `node`, `param`, `value`              |
+//! | `PIPE`               | `node`, `param`, `source`             |
+//! | `FIN`                | `node`                                |
+//! | `INIT_BUF`           | `path`, `meta`                        |
+//! | `LOAD_BUF`           | `id`, `path`, `ext`                   |
+//! | `SET_BUFFER`         | `node`, `param`, `buffer`, `channel`  |
+//!
+//! Integers are written in decimal or as `0x`-prefixed hex. Strings are double-quoted with Rust
+//! escapes. Parameter values are decimal floats (including `inf`, `-inf` and `NaN`) or, for values
+//! that don't round trip through decimal, their raw bits as `0x`-prefixed hex.
+//!
+//! Processors are written by name when a [`Resolver`] knows about them and by id otherwise. The
+//! `sink` processor (id `0`) is always available.
+
+use crate::*;
+use std::io;
+
+const SINK: &str = "sink";
+
+/// Maps processor ids to names and back
+pub trait Resolver {
+    fn processor_name(&self, processor: u64) -> Option<&str>;
+    fn processor_id(&self, name: &str) -> Option<u64>;
+}
+
+/// Writes all processors by id
+impl Resolver for () {
+    fn processor_name(&self, _processor: u64) -> Option<&str> {
+        None
+    }
+
+    fn processor_id(&self, _name: &str) -> Option<u64> {
+        None
+    }
+}
+
+/// A [`Handler`] that writes the assembly form of each message
+pub struct Printer<O: io::Write, R: Resolver> {
+    output: O,
+    resolver: R,
+}
+
+impl<O: io::Write, R: Resolver> Printer<O, R> {
+    pub fn new(output: O, resolver: R) -> Self {
+        Self { output, resolver }
+    }
+
+    pub fn into_inner(self) -> O {
+        self.output
+    }
+
+    fn push<T: fmt::Display>(&mut self, msg: T) -> io::Result<()> {
+        writeln!(self.output, "{msg}")
+    }
+}
+
+struct Spawn<'a> {
+    msg: SpawnNode,
+    name: Option<&'a str>,
+}
+
+impl fmt::Display for Spawn<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(name) => self.msg.fmt_with_processor(f, name),
+            None => self.msg.fmt(f),
+        }
+    }
+}
+
+impl<O: io::Write, R: Resolver> Handler for Printer<O, R> {
+    fn header(&mut self, msg: Header) -> io::Result<()> {
+        self.push(msg)?;
+        msg.validate()
+    }
+
+    fn advance_time(&mut self, msg: AdvanceTime) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn set_timing(&mut self, msg: SetTiming) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn ramp_timing(&mut self, msg: RampTiming) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn source_location(&mut self, msg: SourceLocation) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn marker(&mut self, msg: Marker) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn set_time_signature(&mut self, msg: SetTimeSignature) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn create_group(&mut self, msg: CreateGroup) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn spawn_node(&mut self, msg: SpawnNode) -> io::Result<()> {
+        let name = if msg.processor == 0 {
+            Some(SINK)
+        } else {
+            self.resolver.processor_name(msg.processor)
+        };
+        writeln!(self.output, "{}", Spawn { msg, name })
+    }
+
+    fn fork_node(&mut self, msg: ForkNode) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn emit_midi(&mut self, msg: EmitMidi) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn set_parameter(&mut self, msg: SetParameter) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn pipe_parameter(&mut self, msg: PipeParameter) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn finish_node(&mut self, msg: FinishNode) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn init_buffer(&mut self, msg: InitBuffer) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn load_buffer(&mut self, msg: LoadBuffer) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn set_buffer(&mut self, msg: SetBuffer) -> io::Result<()> {
+        self.push(msg)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Parses the assembly form of a command stream, passing each message to the `handler`
+pub fn parse<R: Resolver, H: Handler>(
+    input: &str,
+    resolver: &R,
+    handler: &mut H,
+) -> io::Result<()> {
+    for (idx, line) in input.lines().enumerate() {
+        parse_line(line, resolver, handler).map_err(|err| {
+            let line = idx + 1;
+            if err.kind() == io::ErrorKind::InvalidData {
+                io::Error::new(err.kind(), format!("line {line}: {err}"))
+            } else {
+                err
+            }
+        })?;
+    }
+    Ok(())
+}
+
+/// Assembles the textual form into a binary command stream
+pub fn assemble<R: Resolver>(input: &str, resolver: &R) -> io::Result<Vec<u8>> {
+    let mut output = handler::Writer(vec![]);
+    parse(input, resolver, &mut output)?;
+    Ok(output.0)
+}
+
+macro_rules! error {
+    ($($tt:tt)*) => {
+        io::Error::new(io::ErrorKind::InvalidData, format!($($tt)*))
+    };
+}
+
+fn parse_line<R: Resolver, H: Handler>(
+    line: &str,
+    resolver: &R,
+    handler: &mut H,
+) -> io::Result<()> {
+    let mut lexer = Lexer::new(line);
+
+    let mnemonic = match lexer.next().transpose()? {
+        Some(Token::Word(mnemonic)) => mnemonic,
+        Some(token) => return Err(error!("expected mnemonic, found {token:?}")),
+        None => return Ok(()),
+    };
+
+    let mut fields = Fields::parse(lexer)?;
+
+    match mnemonic {
+        "HEADER" => {
+            let version = fields.int("version")?;
+            let features = fields.int("features")?;
+            fields.finish()?;
+            handler.header(Header { version, features })
+        }
+        "ADVANCE" => {
+            let ticks = fields.int("ticks")?;
+            fields.finish()?;
+            handler.advance_time(AdvanceTime { ticks })
+        }
+        "SET_TIMING" => {
+            let nanos_per_tick = fields.int("nanos_per_tick")?;
+            let ticks_per_beat = fields.int("ticks_per_beat")?;
+            fields.finish()?;
+            handler.set_timing(SetTiming {
+                nanos_per_tick,
+                ticks_per_beat,
+            })
+        }
+        "RAMP_TIMING" => {
+            let nanos_per_tick = fields.int("nanos_per_tick")?;
+            let ticks = fields.int("ticks")?;
+            fields.finish()?;
+            handler.ramp_timing(RampTiming {
+                nanos_per_tick,
+                ticks,
+            })
+        }
+        "LOCATION" => {
+            let file = fields.string("file")?;
+            let line = fields.int("line")?;
+            let column = fields.int("column")?;
+            fields.finish()?;
+            handler.source_location(SourceLocation { file, line, column })
+        }
+        "MARKER" => {
+            let name = fields.string("name")?;
+            fields.finish()?;
+            handler.marker(Marker { name })
+        }
+        "SET_TIME_SIGNATURE" => {
+            let beats = fields.int("beats")?;
+            let unit = fields.int("unit")?;
+            fields.finish()?;
+            handler.set_time_signature(SetTimeSignature { beats, unit })
+        }
+        "GROUP" => {
+            let id = fields.int("id")?;
+            let name = fields.string("name")?;
+            fields.finish()?;
+            handler.create_group(CreateGroup { id, name })
+        }
+        "SPAWN" => {
+            let id = fields.int("id")?;
+            let processor = fields.processor("processor", resolver)?;
+            let group = fields.opt_int("group")?;
+            fields.finish()?;
+            handler.spawn_node(SpawnNode {
+                id,
+                processor,
+                group,
+            })
+        }
+        "FORK" => {
+            let source = fields.int("source")?;
+            let target = fields.int("target")?;
+            fields.finish()?;
+            handler.fork_node(ForkNode { source, target })
+        }
+        "MIDI" => {
+            let data = fields.midi("data")?;
+            let group = fields.opt_int("group")?;
+            fields.finish()?;
+            handler.emit_midi(EmitMidi { data, group })
+        }
+        "SET" => {
+            let target_node = fields.int("node")?;
+            let target_parameter = fields.int("param")?;
+            let value = fields.value("value")?;
+            fields.finish()?;
+            handler.set_parameter(SetParameter {
+                target_node,
+                target_parameter,
+                value,
+            })
+        }
+        "PIPE" => {
+            let target_node = fields.int("node")?;
+            let target_parameter = fields.int("param")?;
+            let source_node = fields.int("source")?;
+            fields.finish()?;
+            handler.pipe_parameter(PipeParameter {
+                target_node,
+                target_parameter,
+                source_node,
+            })
+        }
+        "FIN" => {
+            let node = fields.int("node")?;
+            fields.finish()?;
+            handler.finish_node(FinishNode { node })
+        }
+        "INIT_BUF" => {
+            let source = fields.string("path")?;
+            let meta = fields.string("meta")?;
+            fields.finish()?;
+            handler.init_buffer(InitBuffer { source, meta })
+        }
+        "LOAD_BUF" => {
+            let id = fields.int("id")?;
+            let path = fields.string("path")?;
+            let ext = fields.string("ext")?;
+            fields.finish()?;
+            handler.load_buffer(LoadBuffer { id, path, ext })
+        }
+        "SET_BUFFER" => {
+            let target_node = fields.int("node")?;
+            let target_parameter = fields.int("param")?;
+            let buffer = fields.int("buffer")?;
+            let buffer_channel = fields.int("channel")?;
+            fields.finish()?;
+            handler.set_buffer(SetBuffer {
+                target_node,
+                target_parameter,
+                buffer,
+                buffer_channel,
+            })
+        }
+        _ => Err(error!("unknown mnemonic {mnemonic:?}")),
+    }
+}
+
+#[derive(Debug)]
+enum Field<'a> {
+    Word(&'a str),
+    Str(String),
+    List(Vec<&'a str>),
+}
+
+struct Fields<'a> {
+    fields: Vec<(&'a str, Field<'a>)>,
+}
+
+impl<'a> Fields<'a> {
+    fn parse(mut lexer: Lexer<'a>) -> io::Result<Self> {
+        let mut fields = vec![];
+
+        while let Some(token) = lexer.next().transpose()? {
+            let key = match token {
+                Token::Word(key) => key,
+                token => return Err(error!("expected field name, found {token:?}")),
+            };
+
+            match lexer.next().transpose()? {
+                Some(Token::Eq) => {}
+                token => return Err(error!("expected '=' after {key:?}, found {token:?}")),
+            }
+
+            let value = match lexer.next().transpose()? {
+                Some(Token::Word(value)) => Field::Word(value),
+                Some(Token::Str(value)) => Field::Str(value),
+                Some(Token::LBracket) => {
+                    let mut items = vec![];
+                    loop {
+                        match lexer.next().transpose()? {
+                            Some(Token::RBracket) if items.is_empty() => break,
+                            Some(Token::Word(item)) => items.push(item),
+                            token => return Err(error!("expected list item, found {token:?}")),
+                        }
+                        match lexer.next().transpose()? {
+                            Some(Token::Comma) => {}
+                            Some(Token::RBracket) => break,
+                            token => return Err(error!("expected ',' or ']', found {token:?}")),
+                        }
+                    }
+                    Field::List(items)
+                }
+                token => return Err(error!("expected value for {key:?}, found {token:?}")),
+            };
+
+            if fields.iter().any(|(k, _)| *k == key) {
+                return Err(error!("duplicate field {key:?}"));
+            }
+            fields.push((key, value));
+
+            match lexer.next().transpose()? {
+                Some(Token::Comma) | None => {}
+                Some(token) => return Err(error!("expected ',', found {token:?}")),
+            }
+        }
+
+        Ok(Self { fields })
+    }
+
+    fn take(&mut self, key: &str) -> Option<Field<'a>> {
+        let idx = self.fields.iter().position(|(k, _)| *k == key)?;
+        Some(self.fields.remove(idx).1)
+    }
+
+    fn word(&mut self, key: &str) -> io::Result<Option<&'a str>> {
+        match self.take(key) {
+            Some(Field::Word(value)) => Ok(Some(value)),
+            Some(value) => Err(error!("invalid value for {key:?}: {value:?}")),
+            None => Ok(None),
+        }
+    }
+
+    fn opt_int<T: Int>(&mut self, key: &str) -> io::Result<Option<T>> {
+        self.word(key)?
+            .map(|value| {
+                T::parse(value).ok_or_else(|| error!("invalid integer for {key:?}: {value:?}"))
+            })
+            .transpose()
+    }
+
+    fn int<T: Int>(&mut self, key: &str) -> io::Result<T> {
+        self.opt_int(key)?
+            .ok_or_else(|| error!("missing field {key:?}"))
+    }
+
+    fn string(&mut self, key: &str) -> io::Result<String> {
+        match self.take(key) {
+            Some(Field::Str(value)) => Ok(value),
+            Some(value) => Err(error!("invalid string for {key:?}: {value:?}")),
+            None => Err(error!("missing field {key:?}")),
+        }
+    }
+
+    fn value(&mut self, key: &str) -> io::Result<u64> {
+        let value = self
+            .word(key)?
+            .ok_or_else(|| error!("missing field {key:?}"))?;
+
+        if let Some(bits) = u64::parse_hex(value) {
+            return Ok(bits);
+        }
+
+        value
+            .parse::<f64>()
+            .map(f64::to_bits)
+            .map_err(|_| error!("invalid value for {key:?}: {value:?}"))
+    }
+
+    fn processor<R: Resolver>(&mut self, key: &str, resolver: &R) -> io::Result<u64> {
+        let value = self
+            .word(key)?
+            .ok_or_else(|| error!("missing field {key:?}"))?;
+
+        if value == SINK {
+            return Ok(0);
+        }
+
+        if let Some(id) = u64::parse(value) {
+            return Ok(id);
+        }
+
+        resolver
+            .processor_id(value)
+            .ok_or_else(|| error!("unknown processor {value:?}"))
+    }
+
+    fn midi(&mut self, key: &str) -> io::Result<[u8; 3]> {
+        let items = match self.take(key) {
+            Some(Field::List(items)) => items,
+            Some(value) => return Err(error!("invalid list for {key:?}: {value:?}")),
+            None => return Err(error!("missing field {key:?}")),
+        };
+
+        if items.len() != 3 {
+            return Err(error!(
+                "expected 3 bytes for {key:?}, found {}",
+                items.len()
+            ));
+        }
+
+        let mut data = [0; 3];
+        for (byte, item) in data.iter_mut().zip(items) {
+            *byte = u8::parse(item).ok_or_else(|| error!("invalid byte for {key:?}: {item:?}"))?;
+        }
+
+        Ok(data)
+    }
+
+    fn finish(self) -> io::Result<()> {
+        if let Some((key, _)) = self.fields.first() {
+            return Err(error!("unexpected field {key:?}"));
+        }
+        Ok(())
+    }
+}
+
+trait Int: Sized {
+    fn parse_hex(value: &str) -> Option<Self>;
+    fn parse(value: &str) -> Option<Self>;
+}
+
+macro_rules! int {
+    ($ty:ident) => {
+        impl Int for $ty {
+            fn parse_hex(value: &str) -> Option<Self> {
+                let value = value.strip_prefix("0x")?;
+                $ty::from_str_radix(value, 16).ok()
+            }
+
+            fn parse(value: &str) -> Option<Self> {
+                Self::parse_hex(value).or_else(|| value.parse().ok())
+            }
+        }
+    };
+}
+
+int!(u8);
+int!(u32);
+int!(u64);
+
+#[derive(Debug)]
+enum Token<'a> {
+    Word(&'a str),
+    Str(String),
+    Eq,
+    Comma,
+    LBracket,
+    RBracket,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let mut value = String::new();
+        let mut chars = self.input.char_indices();
+
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.input = &self.input[idx + 1..];
+                    return Ok(value);
+                }
+                '\\' => {
+                    let c = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('0') => '\0',
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('\'') => '\'',
+                        Some('u') => {
+                            let rest = chars.as_str();
+                            let end = rest
+                                .strip_prefix('{')
+                                .and_then(|rest| rest.find('}'))
+                                .ok_or_else(|| error!("invalid unicode escape"))?;
+                            let code = u32::from_str_radix(&rest[1..end + 1], 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| error!("invalid unicode escape"))?;
+                            chars = rest[end + 2..].char_indices();
+                            // the indices are now relative to the remaining input
+                            self.input = &rest[end + 2..];
+                            value.push(code);
+                            continue;
+                        }
+                        c => return Err(error!("invalid escape {c:?}")),
+                    };
+                    value.push(c);
+                }
+                c => value.push(c),
+            }
+        }
+
+        Err(error!("unterminated string"))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = io::Result<Token<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.input = self.input.trim_start();
+
+        let c = self.input.chars().next()?;
+
+        let token = match c {
+            '#' => {
+                self.input = "";
+                return None;
+            }
+            '=' => Token::Eq,
+            ',' => Token::Comma,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '"' => {
+                self.input = &self.input[1..];
+                return Some(self.string().map(Token::Str));
+            }
+            _ => {
+                let end = self
+                    .input
+                    .find(|c: char| c.is_whitespace() || "=,[]\"#".contains(c))
+                    .unwrap_or(self.input.len());
+                let (word, rest) = self.input.split_at(end);
+                self.input = rest;
+                return Some(Ok(Token::Word(word)));
+            }
+        };
+
+        self.input = &self.input[1..];
+        Some(Ok(token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bolero::check;
+    use std::io::Cursor;
+
+    struct Names;
+
+    impl Resolver for Names {
+        fn processor_name(&self, processor: u64) -> Option<&str> {
+            match processor {
+                100 => Some("osc::Sine"),
+                _ => None,
+            }
+        }
+
+        fn processor_id(&self, name: &str) -> Option<u64> {
+            match name {
+                "osc::Sine" => Some(100),
+                _ => None,
+            }
+        }
+    }
+
+    fn round_trip<T: Codec>(v: &T) {
+        let mut expected = vec![];
+        v.encode(&mut expected).unwrap();
+
+        let mut printer = Printer::new(vec![], Names);
+        decode(&mut Cursor::new(&expected), &mut printer).unwrap();
+        let text = String::from_utf8(printer.into_inner()).unwrap();
+
+        let actual = assemble(&text, &Names).unwrap();
+
+        assert_eq!(actual, expected, "{text}");
+    }
+
+    #[test]
+    fn header() {
+        check!()
+            .with_type::<Header>()
+            .filter(|v| v.validate().is_ok())
+            .for_each(round_trip);
+    }
+
+    #[test]
+    fn advance_time() {
+        check!().with_type::<AdvanceTime>().for_each(round_trip);
+    }
+
+    #[test]
+    fn set_timing() {
+        check!().with_type::<SetTiming>().for_each(round_trip);
+    }
+
+    #[test]
+    fn ramp_timing() {
+        check!().with_type::<RampTiming>().for_each(round_trip);
+    }
+
+    #[test]
+    fn source_location() {
+        check!().with_type::<SourceLocation>().for_each(round_trip);
+    }
+
+    #[test]
+    fn marker() {
+        check!().with_type::<Marker>().for_each(round_trip);
+    }
+
+    #[test]
+    fn set_time_signature() {
+        check!().with_type::<SetTimeSignature>().for_each(round_trip);
+    }
+
+    #[test]
+    fn create_group() {
+        check!().with_type::<CreateGroup>().for_each(round_trip);
+    }
+
+    #[test]
+    fn spawn_node() {
+        check!().with_type::<SpawnNode>().for_each(round_trip);
+    }
+
+    #[test]
+    fn fork_node() {
+        check!().with_type::<ForkNode>().for_each(round_trip);
+    }
+
+    #[test]
+    fn emit_midi() {
+        check!().with_type::<EmitMidi>().for_each(round_trip);
+    }
+
+    #[test]
+    fn set_parameter() {
+        check!().with_type::<SetParameter>().for_each(round_trip);
+    }
+
+    #[test]
+    fn pipe_parameter() {
+        check!().with_type::<PipeParameter>().for_each(round_trip);
+    }
+
+    #[test]
+    fn finish_node() {
+        check!().with_type::<FinishNode>().for_each(round_trip);
+    }
+
+    #[test]
+    fn init_buffer() {
+        check!().with_type::<InitBuffer>().for_each(round_trip);
+    }
+
+    #[test]
+    fn load_buffer() {
+        check!().with_type::<LoadBuffer>().for_each(round_trip);
+    }
+
+    #[test]
+    fn set_buffer() {
+        check!().with_type::<SetBuffer>().for_each(round_trip);
+    }
+
+    #[test]
+    fn names() {
+        let input = r#"
+            # a simple patch
+            SPAWN id = 0, processor = sink
+            SPAWN id = 1, processor = osc::Sine, group = 2 # with a group
+            SET node = 1, param = 0, value = 440
+            PIPE node = 0, param = 0, source = 1
+        "#;
+
+        let mut printer = Printer::new(vec![], Names);
+        parse(input, &Names, &mut printer).unwrap();
+        let output = String::from_utf8(printer.into_inner()).unwrap();
+
+        assert_eq!(
+            output,
+            concat!(
+                "  SPAWN id = 0, processor = sink\n",
+                "  SPAWN id = 1, processor = osc::Sine, group = 2\n",
+                "  SET node = 1, param = 0, value = 440\n",
+                "  PIPE node = 0, param = 0, source = 1\n",
+            )
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            "NOPE",
+            "ADVANCE",
+            "ADVANCE ticks",
+            "ADVANCE ticks = -1",
+            "ADVANCE ticks = 1, ticks = 2",
+            "ADVANCE ticks = 1, extra = 2",
+            "GROUP id = 1, name = \"unterminated",
+            "MIDI data = [1, 2]",
+            "SPAWN id = 1, processor = osc::Unknown",
+        ];
+
+        for case in cases {
+            let mut out = String::new();
+            let err = parse(case, &Names, &mut out).unwrap_err();
+            assert!(err.to_string().starts_with("line 1: "), "{case}: {err}");
+        }
+    }
+}
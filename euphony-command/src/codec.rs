@@ -19,6 +19,10 @@ pub fn decode_one<R: io::Read, H: Handler>(
     };
 
     match tag {
+        Header::TAG => {
+            let msg = Header::decode(tag, input)?;
+            handler.header(msg)?;
+        }
         AdvanceTime::TAG => {
             let msg = AdvanceTime::decode(tag, input)?;
             handler.advance_time(msg)?;
@@ -27,6 +31,22 @@ pub fn decode_one<R: io::Read, H: Handler>(
             let msg = SetTiming::decode(tag, input)?;
             handler.set_timing(msg)?;
         }
+        RampTiming::TAG => {
+            let msg = RampTiming::decode(tag, input)?;
+            handler.ramp_timing(msg)?;
+        }
+        SourceLocation::TAG => {
+            let msg = SourceLocation::decode(tag, input)?;
+            handler.source_location(msg)?;
+        }
+        Marker::TAG => {
+            let msg = Marker::decode(tag, input)?;
+            handler.marker(msg)?;
+        }
+        SetTimeSignature::TAG => {
+            let msg = SetTimeSignature::decode(tag, input)?;
+            handler.set_time_signature(msg)?;
+        }
         CreateGroup::TAG => {
             let msg = CreateGroup::decode(tag, input)?;
             handler.create_group(msg)?;
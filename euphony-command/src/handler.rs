@@ -2,8 +2,24 @@ use super::*;
 use std::io;
 
 pub trait Handler {
+    fn header(&mut self, msg: Header) -> io::Result<()> {
+        msg.validate()
+    }
     fn advance_time(&mut self, msg: AdvanceTime) -> io::Result<()>;
     fn set_timing(&mut self, msg: SetTiming) -> io::Result<()>;
+    fn ramp_timing(&mut self, msg: RampTiming) -> io::Result<()>;
+    fn source_location(&mut self, msg: SourceLocation) -> io::Result<()> {
+        let _ = msg;
+        Ok(())
+    }
+    fn marker(&mut self, msg: Marker) -> io::Result<()> {
+        let _ = msg;
+        Ok(())
+    }
+    fn set_time_signature(&mut self, msg: SetTimeSignature) -> io::Result<()> {
+        let _ = msg;
+        Ok(())
+    }
     fn create_group(&mut self, msg: CreateGroup) -> io::Result<()>;
     fn spawn_node(&mut self, msg: SpawnNode) -> io::Result<()>;
     fn fork_node(&mut self, msg: ForkNode) -> io::Result<()>;
@@ -29,6 +45,11 @@ fn push_msg<T: fmt::Display>(output: &mut String, v: T) -> io::Result<()> {
 }
 
 impl Handler for String {
+    fn header(&mut self, msg: Header) -> io::Result<()> {
+        push_msg(self, msg)?;
+        msg.validate()
+    }
+
     fn advance_time(&mut self, msg: AdvanceTime) -> io::Result<()> {
         push_msg(self, msg)
     }
@@ -37,6 +58,22 @@ impl Handler for String {
         push_msg(self, msg)
     }
 
+    fn ramp_timing(&mut self, msg: RampTiming) -> io::Result<()> {
+        push_msg(self, msg)
+    }
+
+    fn source_location(&mut self, msg: SourceLocation) -> io::Result<()> {
+        push_msg(self, msg)
+    }
+
+    fn marker(&mut self, msg: Marker) -> io::Result<()> {
+        push_msg(self, msg)
+    }
+
+    fn set_time_signature(&mut self, msg: SetTimeSignature) -> io::Result<()> {
+        push_msg(self, msg)
+    }
+
     fn create_group(&mut self, msg: CreateGroup) -> io::Result<()> {
         push_msg(self, msg)
     }
@@ -81,6 +118,10 @@ impl Handler for String {
 pub struct Writer<O: io::Write>(pub O);
 
 impl<O: io::Write> super::Handler for Writer<O> {
+    fn header(&mut self, msg: Header) -> io::Result<()> {
+        msg.encode(&mut self.0)
+    }
+
     fn advance_time(&mut self, msg: AdvanceTime) -> io::Result<()> {
         msg.encode(&mut self.0)
     }
@@ -89,6 +130,22 @@ impl<O: io::Write> super::Handler for Writer<O> {
         msg.encode(&mut self.0)
     }
 
+    fn ramp_timing(&mut self, msg: RampTiming) -> io::Result<()> {
+        msg.encode(&mut self.0)
+    }
+
+    fn source_location(&mut self, msg: SourceLocation) -> io::Result<()> {
+        msg.encode(&mut self.0)
+    }
+
+    fn marker(&mut self, msg: Marker) -> io::Result<()> {
+        msg.encode(&mut self.0)
+    }
+
+    fn set_time_signature(&mut self, msg: SetTimeSignature) -> io::Result<()> {
+        msg.encode(&mut self.0)
+    }
+
     fn create_group(&mut self, msg: CreateGroup) -> io::Result<()> {
         msg.encode(&mut self.0)
     }
@@ -2,6 +2,7 @@ use core::{fmt, ops::ControlFlow};
 use std::io;
 
 pub mod api;
+pub mod asm;
 mod codec;
 mod handler;
 
@@ -13,6 +14,106 @@ use codec::{ReadExt, WriteExt};
 #[cfg(test)]
 use bolero::generator::*;
 
+/// Preamble written at the start of every command stream
+///
+/// Streams produced before the header was introduced start directly with a message tag and are
+/// treated as version `0` with no features.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(test, derive(TypeGenerator))]
+pub struct Header {
+    pub version: u32,
+    pub features: u64,
+}
+
+impl Header {
+    const MAGIC: [u8; 4] = *b"EUPH";
+    const TAG: u8 = Self::MAGIC[0];
+
+    /// The stream version written by this crate
+    pub const VERSION: u32 = 1;
+
+    /// The stream may contain [`RampTiming`] messages
+    pub const TEMPO_RAMP: u64 = 1 << 0;
+
+    /// The stream may contain [`SourceLocation`] messages
+    pub const SOURCE_LOCATION: u64 = 1 << 1;
+
+    /// The stream may contain [`Marker`] and [`SetTimeSignature`] messages
+    pub const ANNOTATIONS: u64 = 1 << 2;
+
+    /// The feature flags understood by this crate
+    pub const FEATURES: u64 = Self::TEMPO_RAMP | Self::SOURCE_LOCATION | Self::ANNOTATIONS;
+
+    /// Returns an error if the stream requires a version or features this crate doesn't support
+    pub fn validate(&self) -> io::Result<()> {
+        if self.version > Self::VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "stream version {} is newer than the supported version {}; try upgrading euphony-cli",
+                    self.version,
+                    Self::VERSION
+                ),
+            ));
+        }
+
+        let unsupported = self.features & !Self::FEATURES;
+        if unsupported != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("stream requires unsupported features 0x{unsupported:x}"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            features: Self::FEATURES,
+        }
+    }
+}
+
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "HEADER version = {}, features = 0x{:x}",
+            self.version, self.features
+        )
+    }
+}
+
+impl Codec for Header {
+    #[inline]
+    fn encode<W: io::Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_all(&Self::MAGIC)?;
+        output.write_u32(self.version)?;
+        output.write_u64(self.features)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn decode<R: io::Read>(tag: u8, input: &mut R) -> io::Result<Self> {
+        debug_assert_eq!(Self::TAG, tag);
+        let mut magic = [0u8; 3];
+        input.read_exact(&mut magic)?;
+        if magic != Self::MAGIC[1..] {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid stream header magic",
+            ));
+        }
+        let version = input.read_u32()?;
+        let features = input.read_u64()?;
+        Ok(Self { version, features })
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(test, derive(TypeGenerator))]
 pub struct AdvanceTime {
@@ -87,6 +188,176 @@ impl Codec for SetTiming {
     }
 }
 
+/// Linearly changes the tick duration from its current value to `nanos_per_tick` over the next
+/// `ticks` ticks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(test, derive(TypeGenerator))]
+pub struct RampTiming {
+    pub nanos_per_tick: u64,
+    pub ticks: u64,
+}
+
+impl RampTiming {
+    const TAG: u8 = b'r';
+}
+
+impl fmt::Display for RampTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  RAMP_TIMING nanos_per_tick = {}, ticks = {}",
+            self.nanos_per_tick, self.ticks,
+        )
+    }
+}
+
+impl Codec for RampTiming {
+    #[inline]
+    fn encode<W: io::Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_u8(Self::TAG)?;
+        output.write_u64(self.nanos_per_tick)?;
+        output.write_u64(self.ticks)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn decode<R: io::Read>(tag: u8, input: &mut R) -> io::Result<Self> {
+        debug_assert_eq!(Self::TAG, tag);
+        let nanos_per_tick = input.read_u64()?;
+        let ticks = input.read_u64()?;
+        Ok(Self {
+            nanos_per_tick,
+            ticks,
+        })
+    }
+}
+
+/// Location of the composition code responsible for the messages that follow
+///
+/// The location applies until the next `SourceLocation` or [`FinishNode`] message.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(test, derive(TypeGenerator))]
+pub struct SourceLocation {
+    #[cfg_attr(test, generator(gen::<String>().with().len(0usize..64)))]
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl SourceLocation {
+    const TAG: u8 = b'l';
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  LOCATION file = {:?}, line = {}, column = {}",
+            self.file, self.line, self.column
+        )
+    }
+}
+
+impl Codec for SourceLocation {
+    #[inline]
+    fn encode<W: io::Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_u8(Self::TAG)?;
+        output.write_u32(self.file.len() as _)?;
+        output.write_all(self.file.as_bytes())?;
+        output.write_u32(self.line)?;
+        output.write_u32(self.column)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn decode<R: io::Read>(tag: u8, input: &mut R) -> io::Result<Self> {
+        debug_assert_eq!(Self::TAG, tag);
+        let len = input.read_u32()?;
+        let file = input.read_string(len as usize)?;
+        let line = input.read_u32()?;
+        let column = input.read_u32()?;
+        Ok(Self { file, line, column })
+    }
+}
+
+/// Names the current position in the composition, e.g. the start of a section
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(test, derive(TypeGenerator))]
+pub struct Marker {
+    #[cfg_attr(test, generator(gen::<String>().with().len(0usize..64)))]
+    pub name: String,
+}
+
+impl Marker {
+    const TAG: u8 = b'c';
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "  MARKER name = {:?}", self.name)
+    }
+}
+
+impl Codec for Marker {
+    #[inline]
+    fn encode<W: io::Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_u8(Self::TAG)?;
+        output.write_u32(self.name.len() as _)?;
+        output.write_all(self.name.as_bytes())?;
+        Ok(())
+    }
+
+    #[inline]
+    fn decode<R: io::Read>(tag: u8, input: &mut R) -> io::Result<Self> {
+        debug_assert_eq!(Self::TAG, tag);
+        let len = input.read_u32()?;
+        let name = input.read_string(len as usize)?;
+        Ok(Self { name })
+    }
+}
+
+/// Sets the time signature from the current position, with `beats` beats of `1/unit` per bar
+///
+/// The time signature only annotates the composition and doesn't affect the timing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(test, derive(TypeGenerator))]
+pub struct SetTimeSignature {
+    pub beats: u64,
+    pub unit: u64,
+}
+
+impl SetTimeSignature {
+    const TAG: u8 = b'b';
+}
+
+impl fmt::Display for SetTimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  SET_TIME_SIGNATURE beats = {}, unit = {}",
+            self.beats, self.unit
+        )
+    }
+}
+
+impl Codec for SetTimeSignature {
+    #[inline]
+    fn encode<W: io::Write>(&self, output: &mut W) -> io::Result<()> {
+        output.write_u8(Self::TAG)?;
+        output.write_u64(self.beats)?;
+        output.write_u64(self.unit)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn decode<R: io::Read>(tag: u8, input: &mut R) -> io::Result<Self> {
+        debug_assert_eq!(Self::TAG, tag);
+        let beats = input.read_u64()?;
+        let unit = input.read_u64()?;
+        Ok(Self { beats, unit })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(test, derive(TypeGenerator))]
 pub struct CreateGroup {
@@ -141,15 +412,23 @@ impl SpawnNode {
     const TAG_WITH_GROUP: u8 = b'N';
 }
 
+impl SpawnNode {
+    fn fmt_with_processor<P: fmt::Display>(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        processor: P,
+    ) -> fmt::Result {
+        write!(f, "  SPAWN id = {}, processor = {}", self.id, processor)?;
+        if let Some(group) = self.group {
+            write!(f, ", group = {group}")?;
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for SpawnNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // TODO map generator to name
-        write!(
-            f,
-            "  SPAWN id = {}, processor = {}",
-            self.id, self.processor
-        )?;
-        Ok(())
+        self.fmt_with_processor(f, self.processor)
     }
 }
 
@@ -240,6 +519,9 @@ impl EmitMidi {
 impl fmt::Display for EmitMidi {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "  MIDI data = {:?}", self.data)?;
+        if let Some(group) = self.group {
+            write!(f, ", group = {group}")?;
+        }
         Ok(())
     }
 }
@@ -292,7 +574,7 @@ impl fmt::Display for SetParameter {
             "  SET node = {}, param = {}, value = {}",
             self.target_node,
             self.target_parameter,
-            f64::from_bits(self.value)
+            Value(self.value)
         )
     }
 }
@@ -333,6 +615,24 @@ impl Codec for SetParameter {
     }
 }
 
+/// Displays a parameter value so that it parses back to the same bits
+///
+/// Values that don't survive a round trip through their decimal form (e.g. NaN payloads) are
+/// written as raw bits in hex.
+struct Value(u64);
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = f64::from_bits(self.0);
+        let decimal = value.to_string();
+        if decimal.parse::<f64>().map(f64::to_bits) == Ok(self.0) {
+            f.write_str(&decimal)
+        } else {
+            write!(f, "0x{:016x}", self.0)
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(test, derive(TypeGenerator))]
 pub struct PipeParameter {
@@ -442,7 +742,11 @@ impl InitBuffer {
 
 impl fmt::Display for InitBuffer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "  INIT_BUF path = {:?}", self.source)
+        write!(
+            f,
+            "  INIT_BUF path = {:?}, meta = {:?}",
+            self.source, self.meta
+        )
     }
 }
 
@@ -588,6 +892,54 @@ mod tests {
         assert_eq!(&actual, v);
     }
 
+    #[test]
+    fn header() {
+        check!().with_type::<Header>().for_each(round_trip);
+    }
+
+    #[test]
+    fn header_validation() {
+        assert!(Header::default().validate().is_ok());
+
+        // streams without a header are treated as version 0
+        let legacy = Header {
+            version: 0,
+            features: 0,
+        };
+        assert!(legacy.validate().is_ok());
+
+        let newer = Header {
+            version: Header::VERSION + 1,
+            ..Default::default()
+        };
+        assert!(newer.validate().is_err());
+
+        let features = Header {
+            features: !Header::FEATURES,
+            ..Default::default()
+        };
+        assert!(features.validate().is_err());
+    }
+
+    #[test]
+    fn header_stream() {
+        let mut buf = Cursor::new(vec![]);
+        Header {
+            version: Header::VERSION + 1,
+            features: 0,
+        }
+        .encode(&mut buf)
+        .unwrap();
+        AdvanceTime { ticks: 1 }.encode(&mut buf).unwrap();
+        buf.set_position(0);
+
+        let mut out = String::new();
+        let err = decode(&mut buf, &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // the header is still displayed before the stream is rejected
+        assert!(out.starts_with("HEADER"));
+    }
+
     #[test]
     fn advance_time() {
         check!().with_type::<AdvanceTime>().for_each(round_trip);
@@ -598,6 +950,26 @@ mod tests {
         check!().with_type::<SetTiming>().for_each(round_trip);
     }
 
+    #[test]
+    fn ramp_timing() {
+        check!().with_type::<RampTiming>().for_each(round_trip);
+    }
+
+    #[test]
+    fn source_location() {
+        check!().with_type::<SourceLocation>().for_each(round_trip);
+    }
+
+    #[test]
+    fn marker() {
+        check!().with_type::<Marker>().for_each(round_trip);
+    }
+
+    #[test]
+    fn set_time_signature() {
+        check!().with_type::<SetTimeSignature>().for_each(round_trip);
+    }
+
     #[test]
     fn create_group() {
         check!().with_type::<CreateGroup>().for_each(round_trip);
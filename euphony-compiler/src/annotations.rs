@@ -0,0 +1,94 @@
+use crate::sample::Offset;
+use euphony_units::time::Beat;
+
+/// Descriptive information about the composition that doesn't affect the rendered output
+#[derive(Clone, Debug, Default)]
+pub struct Annotations {
+    pub tempo_map: Vec<TempoChange>,
+    pub time_signatures: Vec<TimeSignatureChange>,
+    pub markers: Vec<Marker>,
+}
+
+/// The tempo from a position in the composition
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoChange {
+    pub sample: u64,
+    pub beat: f64,
+    /// The tempo in beats per minute
+    pub bpm: f64,
+    /// The tempo changes linearly until the next change, otherwise it's held
+    pub ramp: bool,
+}
+
+/// The time signature from a position in the composition
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeSignatureChange {
+    pub sample: u64,
+    pub beat: f64,
+    /// The number of beats in each bar
+    pub beats: u64,
+    /// The note value of each beat, e.g. `4` for quarter notes
+    pub unit: u64,
+}
+
+/// A named position in the composition
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    pub sample: u64,
+    pub beat: f64,
+    pub name: String,
+}
+
+impl Annotations {
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.tempo_map.clear();
+        self.time_signatures.clear();
+        self.markers.clear();
+    }
+
+    #[inline]
+    pub(crate) fn tempo(&mut self, sample: Offset, beat: Beat, bpm: f64, ramp: bool) {
+        let sample = sample.into();
+
+        // repeated calls to set the same tempo don't change anything
+        if let Some(prev) = self.tempo_map.last() {
+            if prev.sample == sample && prev.bpm == bpm && !prev.ramp && !ramp {
+                return;
+            }
+        }
+
+        self.tempo_map.push(TempoChange {
+            sample,
+            beat: beat.as_f64(),
+            bpm,
+            ramp,
+        });
+    }
+
+    #[inline]
+    pub(crate) fn time_signature(&mut self, sample: Offset, beat: Beat, beats: u64, unit: u64) {
+        self.time_signatures.push(TimeSignatureChange {
+            sample: sample.into(),
+            beat: beat.as_f64(),
+            beats,
+            unit,
+        });
+    }
+
+    #[inline]
+    pub(crate) fn marker(&mut self, sample: Offset, beat: Beat, name: String) {
+        self.markers.push(Marker {
+            sample: sample.into(),
+            beat: beat.as_f64(),
+            name,
+        });
+    }
+}
+
+/// Converts the tick timing to beats per minute
+#[inline]
+pub(crate) fn bpm(nanos_per_tick: u64, ticks_per_beat: u64) -> f64 {
+    let nanos_per_beat = nanos_per_tick as f64 * ticks_per_beat as f64;
+    60_000_000_000.0 / nanos_per_beat
+}
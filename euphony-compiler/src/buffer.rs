@@ -1,5 +1,5 @@
 use crate::{
-    sample::{DefaultRate, DefaultSample, Rate as _},
+    sample::{DefaultSample, SampleRate},
     CachedBuffer, Hash, Writer,
 };
 use euphony_buffer::{decode, symphonia};
@@ -15,6 +15,16 @@ impl Map {
     pub fn new(buffers: HashMap<(u64, u64), Buffer>) -> Self {
         Self { buffers }
     }
+
+    #[inline]
+    pub fn hash(&self, id: u64, channel: u64) -> Option<&Hash> {
+        self.buffers.get(&(id, channel)).map(|buffer| &buffer.hash)
+    }
+
+    #[inline]
+    pub fn hashes(&self) -> impl Iterator<Item = ((u64, u64), Hash)> + '_ {
+        self.buffers.iter().map(|(key, buffer)| (*key, buffer.hash))
+    }
 }
 
 impl BufferMap for Map {
@@ -47,17 +57,18 @@ impl Buffer {
         id: u64,
         path: &str,
         ext: &str,
+        sample_rate: SampleRate,
         cache: &W,
     ) -> symphonia::core::errors::Result<Vec<((u64, u64), Self)>> {
         let paths = cache.buffer::<_, symphonia::core::errors::Error>(
             path,
-            DefaultRate::COUNT,
+            sample_rate.count(),
             |reader| {
                 let mut stream = decode::reader(reader, ext)?;
                 let mut samples = decode::Samples::from_reader(&mut *stream)?;
 
                 samples
-                    .resample(DefaultRate::COUNT as _)
+                    .resample(sample_rate.into())
                     .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
 
                 Ok(samples.channels)
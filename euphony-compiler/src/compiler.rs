@@ -1,22 +1,26 @@
 use crate::{
+    annotations::{self, Annotations},
     buffer::Buffer,
+    graph::{self, Edge},
     group::{self, GroupMap},
     instruction::{Instructions, InternalInstruction},
     node::Node,
+    optimize::{self, Report},
     parallel::*,
-    sample::{default_samples_per_tick, samples_per_tick, Offset},
+    progress::Progress,
+    sample::{default_nanos_per_tick, default_samples_per_tick, samples_per_tick, Offset, Ramp},
     sink::{Sink, SinkMap},
-    Hash, Result, Writer,
+    Error, Hash, Location, Result, Writer,
 };
 use euphony_command::{self as message, Handler};
-use euphony_dsp::nodes;
+use euphony_dsp::{nodes, sample::SampleRate};
 use euphony_node::{BufferMap, ParameterValue as Value};
 use euphony_units::{ratio::Ratio, time::Beat};
-use petgraph::{
-    visit::{depth_first_search, DfsEvent},
-    Graph,
+use petgraph::visit::{depth_first_search, DfsEvent};
+use std::{
+    collections::{hash_map, BTreeSet, HashMap},
+    io,
 };
-use std::collections::{hash_map, BTreeSet, HashMap};
 
 #[derive(Debug)]
 pub struct Compiler {
@@ -25,22 +29,29 @@ pub struct Compiler {
     sinks: SinkMap,
     hashes: HashMap<Hash, u64>, // TODO use hash hasher
     active_nodes: BTreeSet<u64>,
-    connections: Graph<u64, Edge>,
+    connections: graph::Graph,
     instructions: BTreeSet<(Offset, InternalInstruction)>,
     samples: Offset,
     beats: Beat,
     ticks_per_beat: u64,
+    nanos_per_tick: u64,
+    /// The `(nanos_per_tick, ticks_per_beat)` in effect at the start of the composition
+    start_timing: (u64, u64),
     samples_per_tick: Ratio<u128>,
+    ramp: Option<Ramp>,
+    sample_rate: SampleRate,
     pending_buffers: HashMap<u64, (String, String)>,
-}
-
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Edge;
-
-impl core::fmt::Display for Edge {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        "".fmt(f)
-    }
+    /// The composition code responsible for the current messages
+    location: Option<Location>,
+    /// The graph from the last successful compilation, used to skip unchanged nodes
+    prev_nodes: HashMap<u64, Node>,
+    prev_sinks: SinkMap,
+    prev_buffers: HashMap<(u64, u64), Hash>,
+    /// The nodes removed by the last optimization pass
+    report: Report,
+    annotations: Annotations,
+    is_finalized: bool,
+    progress: Progress,
 }
 
 impl Default for Compiler {
@@ -56,8 +67,20 @@ impl Default for Compiler {
             samples: Default::default(),
             beats: Default::default(),
             ticks_per_beat: Beat::DEFAULT_TICKS_PER_BEAT,
-            samples_per_tick: default_samples_per_tick(),
+            nanos_per_tick: default_nanos_per_tick(),
+            start_timing: (default_nanos_per_tick(), Beat::DEFAULT_TICKS_PER_BEAT),
+            samples_per_tick: default_samples_per_tick(SampleRate::DEFAULT),
+            ramp: None,
+            sample_rate: SampleRate::DEFAULT,
             pending_buffers: Default::default(),
+            location: None,
+            prev_nodes: Default::default(),
+            prev_sinks: Default::default(),
+            prev_buffers: Default::default(),
+            report: Default::default(),
+            annotations: Default::default(),
+            is_finalized: false,
+            progress: Default::default(),
         }
     }
 }
@@ -66,23 +89,16 @@ impl Compiler {
     pub fn finalize<W: Writer>(&mut self, cache: &W) -> Result<Box<dyn BufferMap>> {
         let samples = self.samples;
 
-        // println!("{}", petgraph::dot::Dot::new(&self.connections));
-
-        let hasher = blake3::Hasher::new();
-
-        self.nodes.par_iter_mut().for_each(|(_id, node)| {
-            if node.end.is_none() {
-                let _ = node.finish(samples);
-            }
-
-            node.hash(&hasher);
-        });
+        let mut hasher = blake3::Hasher::new();
+        // sinks rendered at different rates shouldn't share a hash
+        hasher.update(&self.sample_rate.count().to_le_bytes());
 
+        let sample_rate = self.sample_rate;
         let buffers = self
             .pending_buffers
             .par_iter()
             .flat_map(
-                |(id, (path, ext))| match Buffer::load(*id, path, ext, cache) {
+                |(id, (path, ext))| match Buffer::load(*id, path, ext, sample_rate, cache) {
                     Ok(values) => values,
                     Err(err) => {
                         log::error!("could not load buffer {:?}: {}", path, err);
@@ -93,20 +109,101 @@ impl Compiler {
             .collect();
         let buffers = crate::buffer::Map::new(buffers);
 
-        // TODO iterate over each node and call optimize
+        for node in self.nodes.values_mut() {
+            if node.end.is_none() {
+                let _ = node.finish(samples);
+            }
+        }
+
+        // make sure every buffer loaded before the sinks try to read them
+        for node in self.nodes.values() {
+            for (buffer, channel) in node.buffers() {
+                if buffers.hash(buffer, channel).is_none() {
+                    let err = Error::MissingBuffer {
+                        node: node.id,
+                        buffer,
+                        channel,
+                    };
+                    return Err(err.at(node.location.as_ref()));
+                }
+            }
+        }
 
+        // make sure the signal can flow through each sink's graph
+        let roots = self.sinks.keys().map(|id| self.nodes[id].index);
+        if let Some(cycle) = graph::find_cycle(&self.connections, roots) {
+            let path = graph::describe_cycle(&self.connections, &self.nodes, &cycle);
+            // point at the node that closed the loop
+            let location = path
+                .last()
+                .and_then(|entry| self.nodes[&entry.node].location.as_ref());
+            return Err(Error::Cycle { path }.at(location));
+        }
+
+        optimize::optimize(
+            &mut self.connections,
+            &mut self.nodes,
+            &self.sinks,
+            &mut self.report,
+        );
+        log::debug!("{}", self.report);
+
+        let prev_nodes = &self.prev_nodes;
+        let prev_buffers = &self.prev_buffers;
+        self.nodes.par_iter_mut().for_each(|(id, node)| {
+            // nodes that didn't change since the last compilation keep their hash
+            let prev = prev_nodes.get(id).filter(|prev| {
+                node.is_unchanged(prev)
+                    && node.buffers().all(|(id, channel)| {
+                        let hash = buffers.hash(id, channel);
+                        hash.is_some() && prev_buffers.get(&(id, channel)) == hash
+                    })
+            });
+
+            if let Some(prev) = prev {
+                node.hash = prev.hash;
+                node.is_reused = true;
+            } else {
+                node.hash(&hasher);
+            }
+        });
+
+        let hashed = self.nodes.values().filter(|node| !node.is_reused).count();
+        log::debug!(
+            "reused {} of {} nodes",
+            self.nodes.len() - hashed,
+            self.nodes.len()
+        );
+        self.progress.add_nodes(hashed as _, self.nodes.len() as _);
+        self.progress.check()?;
+
+        let prev_sinks = &self.prev_sinks;
         self.sinks.par_iter_mut().for_each(|(id, sink)| {
             let node = &self.nodes[id];
             let index = node.index;
 
+            // if none of the members changed then the sink is the same as last time
+            if let Some(prev) = prev_sinks.get(id).filter(|prev| {
+                prev.members
+                    .iter()
+                    .all(|id| self.nodes.get(id).is_some_and(|node| node.is_reused))
+            }) {
+                sink.hash = prev.hash;
+                sink.members.clone_from(&prev.members);
+                sink.start = prev.start;
+                sink.end = prev.end;
+                sink.is_cached = cache.is_cached(&sink.hash);
+                return;
+            }
+
             let mut start = node.start;
             let mut end = None;
 
             let conns = &self.connections;
             let mut hasher = hasher.clone();
 
-            depth_first_search(conns, Some(index), |event| match event {
-                DfsEvent::Discover(dep, _time) => {
+            depth_first_search(conns, Some(index), |event| {
+                if let DfsEvent::Discover(dep, _time) = event {
                     let id = conns[dep];
                     sink.members.insert(id);
                     let dep = &self.nodes[&id];
@@ -140,10 +237,6 @@ impl Compiler {
                         }
                     }
                 }
-                DfsEvent::BackEdge(_, _) => {
-                    sink.is_acyclic = false;
-                }
-                _ => {}
             });
 
             sink.start = start;
@@ -153,10 +246,6 @@ impl Compiler {
         });
 
         for (id, sink) in &self.sinks {
-            if !sink.is_acyclic {
-                return Err(error!("acyclic sink {}", id));
-            }
-
             // the sink already exists
             if sink.is_cached {
                 continue;
@@ -185,11 +274,28 @@ impl Compiler {
             group.update_hash(&self.sinks);
         });
 
+        let end = self
+            .instructions
+            .last()
+            .map_or(0, |(offset, _)| (*offset).into());
+        self.progress.add_totals(self.hashes.len() as _, end);
+
+        self.prev_buffers.clear();
+        self.prev_buffers.extend(buffers.hashes());
+        self.is_finalized = true;
+
         Ok(Box::new(buffers))
     }
 
+    /// Clears the current state, keeping the graph from the last successful compilation around
+    /// so unchanged nodes can be reused
     #[inline]
     pub fn reset(&mut self) {
+        if core::mem::take(&mut self.is_finalized) {
+            core::mem::swap(&mut self.nodes, &mut self.prev_nodes);
+            core::mem::swap(&mut self.sinks, &mut self.prev_sinks);
+        }
+
         self.groups.clear();
         self.nodes.clear();
         self.sinks.clear();
@@ -198,8 +304,69 @@ impl Compiler {
         self.active_nodes.clear();
         self.instructions.clear();
         self.pending_buffers.clear();
+        self.report.clear();
+        self.annotations.clear();
+        self.location = None;
         self.samples = Offset::default();
-        self.samples_per_tick = default_samples_per_tick();
+        self.beats = Beat::default();
+        self.ticks_per_beat = Beat::DEFAULT_TICKS_PER_BEAT;
+        self.nanos_per_tick = default_nanos_per_tick();
+        self.start_timing = (self.nanos_per_tick, self.ticks_per_beat);
+        self.samples_per_tick = default_samples_per_tick(self.sample_rate);
+        self.ramp = None;
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, rate: SampleRate) {
+        // previous hashes were computed for a different rate
+        if self.sample_rate != rate {
+            self.prev_nodes.clear();
+            self.prev_sinks.clear();
+            self.prev_buffers.clear();
+        }
+
+        self.sample_rate = rate;
+        self.samples_per_tick = samples_per_tick(self.nanos_per_tick, rate);
+    }
+
+    #[inline]
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    #[inline]
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.progress = progress;
+    }
+
+    /// Returns the tempo at the start of the composition, in beats per minute
+    #[inline]
+    pub fn tempo(&self) -> f64 {
+        let (nanos_per_tick, ticks_per_beat) = self.start_timing;
+        annotations::bpm(nanos_per_tick, ticks_per_beat)
+    }
+
+    #[inline]
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Returns the paths of the buffers loaded by the composition
+    #[inline]
+    pub fn buffer_paths(&self) -> impl Iterator<Item = &str> + '_ {
+        self.pending_buffers
+            .values()
+            .map(|(path, _ext)| path.as_str())
+    }
+
+    #[inline]
+    pub fn report(&self) -> &Report {
+        &self.report
     }
 
     #[inline]
@@ -217,71 +384,199 @@ impl Compiler {
         }
     }
 
+    /// Writes the node graph in the Graphviz DOT format
+    #[inline]
+    pub fn write_dot<O: io::Write>(&self, output: &mut O) -> io::Result<()> {
+        graph::write_dot(&self.connections, &self.nodes, output)
+    }
+
+    /// Records the tempo at the current position
+    #[inline]
+    fn record_tempo(&mut self, nanos_per_tick: u64, ramp: bool) {
+        let bpm = annotations::bpm(nanos_per_tick, self.ticks_per_beat);
+        self.annotations.tempo(self.samples, self.beats, bpm, ramp);
+    }
+
     #[inline]
     fn node(&mut self, id: u64) -> Result<&mut Node> {
-        self.nodes
-            .get_mut(&id)
-            .ok_or_else(|| error!("missing node {}", id))
+        self.nodes.get_mut(&id).ok_or(Error::MissingNode { id })
+    }
+
+    /// Attaches the location of the current composition code to an error
+    #[inline]
+    fn locate(&self, err: Error) -> io::Error {
+        err.at(self.location.as_ref()).into()
     }
 }
 
 impl Handler for Compiler {
     #[inline]
-    fn advance_time(&mut self, msg: message::AdvanceTime) -> Result {
+    fn advance_time(&mut self, msg: message::AdvanceTime) -> io::Result<()> {
+        self.progress.check()?;
+
         if msg.ticks == 0 {
             return Ok(());
         }
 
+        let mut ticks = msg.ticks;
+
+        if let Some(mut ramp) = self.ramp.take() {
+            let remaining = ramp.ticks - ramp.elapsed;
+            let ramp_ticks = ticks.min(remaining);
+            ramp.elapsed += ramp_ticks;
+            ticks -= ramp_ticks;
+            self.beats += Beat(ramp_ticks, self.ticks_per_beat);
+
+            // compute the offset from the start of the ramp so rounding errors don't accumulate
+            let samples = ramp
+                .samples(ramp.elapsed, self.sample_rate)
+                .ok_or(Error::Overflow)?;
+            let mut offset = ramp.start;
+            self.samples = offset.checked_add(samples).ok_or(Error::Overflow)?;
+
+            if ramp.elapsed < ramp.ticks {
+                self.ramp = Some(ramp);
+            } else {
+                self.nanos_per_tick = ramp.end_nanos;
+                self.samples_per_tick = samples_per_tick(ramp.end_nanos, self.sample_rate);
+                self.record_tempo(ramp.end_nanos, false);
+            }
+
+            if ticks == 0 {
+                return Ok(());
+            }
+        }
+
+        self.beats += Beat(ticks, self.ticks_per_beat);
+
         let samples = self
             .samples_per_tick
             .0
-            .checked_mul(msg.ticks as u128)
-            .ok_or_else(|| error!("sample overflow"))?;
+            .checked_mul(ticks as u128)
+            .ok_or(Error::Overflow)?;
         let samples = samples / self.samples_per_tick.1;
         let samples = samples as u64;
 
         // limit the number of samples in testing so we don't churn indefinitely
         // TODO this should probably error out if the requested time exceeds an hour or something
         #[cfg(any(test, all(test, fuzz)))]
-        let samples = {
-            use euphony_dsp::sample::{DefaultRate, Rate};
-            samples.min(DefaultRate::COUNT)
-        };
-
-        self.samples = self
-            .samples
-            .checked_add(samples)
-            .ok_or_else(|| error!("sample overflow"))?;
+        let samples = samples.min(self.sample_rate.count());
 
-        self.beats += Beat(msg.ticks, self.ticks_per_beat);
+        self.samples = self.samples.checked_add(samples).ok_or(Error::Overflow)?;
 
         Ok(())
     }
 
     #[inline]
-    fn set_timing(&mut self, msg: message::SetTiming) -> Result {
+    fn set_timing(&mut self, msg: message::SetTiming) -> io::Result<()> {
         if msg.nanos_per_tick == 0 {
-            return Err(error!("nanos per tick must be non-zero"));
+            return Err(Error::InvalidTiming {
+                reason: "nanos per tick must be non-zero",
+            }
+            .into());
         }
 
         if msg.ticks_per_beat == 0 {
-            return Err(error!("ticks per beat must be non-zero"));
+            return Err(Error::InvalidTiming {
+                reason: "ticks per beat must be non-zero",
+            }
+            .into());
         }
 
-        self.samples_per_tick = samples_per_tick(msg.nanos_per_tick);
+        // a step change replaces any ramp in progress
+        if let Some(ramp) = self.ramp.take() {
+            self.record_tempo(ramp.nanos_per_tick(), false);
+        }
+
+        self.nanos_per_tick = msg.nanos_per_tick;
+        self.samples_per_tick = samples_per_tick(msg.nanos_per_tick, self.sample_rate);
 
         self.ticks_per_beat = msg.ticks_per_beat;
+        self.record_tempo(msg.nanos_per_tick, false);
+
+        if self.samples == Offset::default() {
+            self.start_timing = (msg.nanos_per_tick, msg.ticks_per_beat);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn ramp_timing(&mut self, msg: message::RampTiming) -> io::Result<()> {
+        if msg.nanos_per_tick == 0 {
+            return Err(Error::InvalidTiming {
+                reason: "nanos per tick must be non-zero",
+            }
+            .into());
+        }
+
+        // start from wherever the current ramp is at
+        let start_nanos = if let Some(ramp) = self.ramp.take() {
+            ramp.nanos_per_tick()
+        } else {
+            self.nanos_per_tick
+        };
+
+        if msg.ticks == 0 {
+            self.nanos_per_tick = msg.nanos_per_tick;
+            self.samples_per_tick = samples_per_tick(msg.nanos_per_tick, self.sample_rate);
+            self.record_tempo(msg.nanos_per_tick, false);
+            return Ok(());
+        }
+
+        self.record_tempo(start_nanos, true);
+
+        self.nanos_per_tick = start_nanos;
+        self.samples_per_tick = samples_per_tick(start_nanos, self.sample_rate);
+        self.ramp = Some(Ramp {
+            start: self.samples,
+            start_nanos,
+            end_nanos: msg.nanos_per_tick,
+            ticks: msg.ticks,
+            elapsed: 0,
+        });
+
+        Ok(())
+    }
+
+    #[inline]
+    fn source_location(&mut self, msg: message::SourceLocation) -> io::Result<()> {
+        self.location = Some(Location {
+            file: msg.file.into(),
+            line: msg.line,
+            column: msg.column,
+        });
         Ok(())
     }
 
     #[inline]
-    fn create_group(&mut self, msg: message::CreateGroup) -> Result {
+    fn marker(&mut self, msg: message::Marker) -> io::Result<()> {
+        self.annotations.marker(self.samples, self.beats, msg.name);
+        Ok(())
+    }
+
+    #[inline]
+    fn set_time_signature(&mut self, msg: message::SetTimeSignature) -> io::Result<()> {
+        if msg.beats == 0 || msg.unit == 0 {
+            return Err(Error::InvalidTiming {
+                reason: "time signatures must be non-zero",
+            }
+            .into());
+        }
+
+        self.annotations
+            .time_signature(self.samples, self.beats, msg.beats, msg.unit);
+        Ok(())
+    }
+
+    #[inline]
+    fn create_group(&mut self, msg: message::CreateGroup) -> io::Result<()> {
         self.groups.entry(msg.id).or_default().name = msg.name;
         Ok(())
     }
 
     #[inline]
-    fn spawn_node(&mut self, msg: message::SpawnNode) -> Result {
+    fn spawn_node(&mut self, msg: message::SpawnNode) -> io::Result<()> {
         let processor = msg.processor;
 
         if processor == 0 {
@@ -299,7 +594,7 @@ impl Handler for Compiler {
                 .sinks
                 .insert((self.samples, msg.id));
         } else if nodes::name(processor).is_none() {
-            return Err(error!("non-existant processor {}", processor));
+            return Err(self.locate(Error::UnknownProcessor { processor }));
         }
 
         let index = self.connections.add_node(msg.id);
@@ -307,6 +602,7 @@ impl Handler for Compiler {
         let prev = self.nodes.insert(
             msg.id,
             Node {
+                id: msg.id,
                 index,
                 processor,
                 inputs: Default::default(),
@@ -314,59 +610,63 @@ impl Handler for Compiler {
                 end: None,
                 fork_source: None,
                 hash: [0; 32],
+                is_reused: false,
+                location: self.location.clone(),
             },
         );
 
         if prev.is_some() {
-            return Err(error!("node id {} was reused", msg.id));
+            return Err(self.locate(Error::NodeReused { id: msg.id }));
         }
 
         Ok(())
     }
 
     #[inline]
-    fn fork_node(&mut self, msg: message::ForkNode) -> Result {
+    fn fork_node(&mut self, msg: message::ForkNode) -> io::Result<()> {
         let source = msg.source;
         let target = msg.target;
-        let source_n = self
-            .nodes
-            .get(&source)
-            .ok_or_else(|| error!("node id {source} was not found"))?;
+        let source_n = match self.nodes.get(&source) {
+            Some(node) => node,
+            None => return Err(self.locate(Error::MissingNode { id: source })),
+        };
 
-        if source_n.processor == 0 {
-            return Err(error!("sink nodes cannot be forked"));
-        }
+        let reason = if source_n.processor == 0 {
+            Some("sink nodes cannot be forked")
+        } else if source_n.start != self.samples {
+            Some("only freshly spawned nodes can be forked at this time")
+        } else {
+            None
+        };
 
-        if source_n.start != self.samples {
-            return Err(error!(
-                "only freshly spawned nodes can be forked at this time"
-            ));
+        if let Some(reason) = reason {
+            return Err(self.locate(Error::InvalidFork { source, reason }));
         }
 
         let index = self.connections.add_node(target);
 
-        let prev = self.nodes.insert(
-            target,
-            Node {
-                index,
-                processor: source_n.processor,
-                inputs: source_n.inputs.clone(),
-                start: self.samples,
-                end: None,
-                fork_source: Some(source),
-                hash: [0; 32],
-            },
-        );
+        let node = Node {
+            id: target,
+            index,
+            processor: source_n.processor,
+            inputs: source_n.inputs.clone(),
+            start: self.samples,
+            end: None,
+            fork_source: Some(source),
+            hash: [0; 32],
+            is_reused: false,
+            location: self.location.clone(),
+        };
 
-        if prev.is_some() {
-            return Err(error!("node id {target} was reused"));
+        if self.nodes.insert(target, node).is_some() {
+            return Err(self.locate(Error::NodeReused { id: target }));
         }
 
         Ok(())
     }
 
     #[inline]
-    fn emit_midi(&mut self, msg: message::EmitMidi) -> Result {
+    fn emit_midi(&mut self, msg: message::EmitMidi) -> io::Result<()> {
         let group = msg.group.unwrap_or(0);
         self.groups
             .entry(group)
@@ -377,7 +677,7 @@ impl Handler for Compiler {
     }
 
     #[inline]
-    fn set_parameter(&mut self, msg: message::SetParameter) -> Result {
+    fn set_parameter(&mut self, msg: message::SetParameter) -> io::Result<()> {
         let message::SetParameter {
             target_node,
             target_parameter,
@@ -385,14 +685,13 @@ impl Handler for Compiler {
         } = msg;
 
         let samples = self.samples;
-        let node = self.node(target_node)?;
-        node.set(target_parameter, value, samples)?;
-
-        Ok(())
+        self.node(target_node)
+            .and_then(|node| node.set(target_parameter, value, samples))
+            .map_err(|err| self.locate(err))
     }
 
     #[inline]
-    fn pipe_parameter(&mut self, msg: message::PipeParameter) -> Result {
+    fn pipe_parameter(&mut self, msg: message::PipeParameter) -> io::Result<()> {
         let message::PipeParameter {
             target_node,
             target_parameter,
@@ -400,23 +699,37 @@ impl Handler for Compiler {
         } = msg;
 
         if self.sinks.contains_key(&source_node) {
-            return Err(error!("cannot connect sink output to another node"));
+            return Err(self.locate(Error::InvalidConnection {
+                node: target_node,
+                source: source_node,
+                reason: "sink outputs cannot be connected to another node",
+            }));
         }
 
         let samples = self.samples;
-        let source_idx = self.node(source_node)?.index;
-
-        let node = self.node(target_node)?;
-        node.connect(target_parameter, source_node, samples)?;
-        let target_idx = node.index;
+        let source_idx = match self.node(source_node) {
+            Ok(source) => source.index,
+            Err(err) => return Err(self.locate(err)),
+        };
+        let target_idx = self.node(target_node).and_then(|node| {
+            node.connect(target_parameter, source_node, samples)?;
+            Ok(node.index)
+        });
+        let target_idx = target_idx.map_err(|err| self.locate(err))?;
 
-        self.connections.add_edge(target_idx, source_idx, Edge);
+        let edge = Edge {
+            parameter: target_parameter,
+        };
+        self.connections.add_edge(target_idx, source_idx, edge);
 
         Ok(())
     }
 
     #[inline]
-    fn finish_node(&mut self, msg: message::FinishNode) -> Result {
+    fn finish_node(&mut self, msg: message::FinishNode) -> io::Result<()> {
+        // nodes are usually finished when they're dropped so the location no longer applies
+        self.location = None;
+
         let samples = self.samples;
         let node = self.node(msg.node)?;
         node.finish(samples)?;
@@ -424,13 +737,13 @@ impl Handler for Compiler {
     }
 
     #[inline]
-    fn init_buffer(&mut self, msg: message::InitBuffer) -> std::io::Result<()> {
+    fn init_buffer(&mut self, msg: message::InitBuffer) -> io::Result<()> {
         euphony_buffer::Buffer::init(msg)?;
         Ok(())
     }
 
     #[inline]
-    fn load_buffer(&mut self, msg: message::LoadBuffer) -> std::io::Result<()> {
+    fn load_buffer(&mut self, msg: message::LoadBuffer) -> io::Result<()> {
         let message::LoadBuffer { id, path, ext } = msg;
 
         self.pending_buffers.insert(id, (path, ext));
@@ -439,7 +752,7 @@ impl Handler for Compiler {
     }
 
     #[inline]
-    fn set_buffer(&mut self, msg: message::SetBuffer) -> std::io::Result<()> {
+    fn set_buffer(&mut self, msg: message::SetBuffer) -> io::Result<()> {
         let message::SetBuffer {
             target_node,
             target_parameter,
@@ -447,10 +760,17 @@ impl Handler for Compiler {
             buffer_channel,
         } = msg;
 
-        let samples = self.samples;
-        let node = self.node(target_node)?;
-        node.set_buffer(target_parameter, buffer, buffer_channel, samples)?;
+        if !self.pending_buffers.contains_key(&buffer) {
+            return Err(self.locate(Error::MissingBuffer {
+                node: target_node,
+                buffer,
+                channel: buffer_channel,
+            }));
+        }
 
-        Ok(())
+        let samples = self.samples;
+        self.node(target_node)
+            .and_then(|node| node.set_buffer(target_parameter, buffer, buffer_channel, samples))
+            .map_err(|err| self.locate(err))
     }
 }
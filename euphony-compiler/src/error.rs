@@ -0,0 +1,225 @@
+use core::fmt;
+use std::{io, sync::Arc};
+
+/// Location of the composition code responsible for a node or message
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub file: Arc<str>,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// A node that is part of a cycle in the graph
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleEntry {
+    pub node: u64,
+    pub processor: u64,
+    /// The parameter fed by the previous node in the cycle
+    pub parameter: u64,
+}
+
+impl CycleEntry {
+    #[inline]
+    pub fn processor_name(&self) -> String {
+        crate::graph::processor_name(self.processor)
+    }
+
+    #[inline]
+    pub fn parameter_name(&self) -> String {
+        crate::graph::parameter_name(self.processor, self.parameter)
+    }
+}
+
+impl fmt::Display for CycleEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node {} ({})", self.node, self.processor_name())
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A message referenced a node that was never spawned
+    MissingNode {
+        id: u64,
+    },
+    /// A node id was spawned more than once
+    NodeReused {
+        id: u64,
+    },
+    /// A node was modified after it was finished
+    NodeFinished {
+        id: u64,
+    },
+    /// A node was spawned with a processor that doesn't exist
+    UnknownProcessor {
+        processor: u64,
+    },
+    /// A parameter doesn't exist on the processor or can't accept the value
+    InvalidParameter {
+        node: u64,
+        processor: u64,
+        parameter: u64,
+        reason: String,
+    },
+    /// Two nodes can't be connected
+    InvalidConnection {
+        node: u64,
+        source: u64,
+        reason: &'static str,
+    },
+    /// A node can't be forked
+    InvalidFork {
+        source: u64,
+        reason: &'static str,
+    },
+    /// A node references a buffer that was never loaded
+    MissingBuffer {
+        node: u64,
+        buffer: u64,
+        channel: u64,
+    },
+    /// The graph feeding into a sink contains a cycle
+    ///
+    /// Each entry is fed by the one before it and the first entry is fed by the last.
+    Cycle {
+        path: Vec<CycleEntry>,
+    },
+    /// The timing parameters are out of range
+    InvalidTiming {
+        reason: &'static str,
+    },
+    /// The composition is too long to be represented in samples
+    Overflow,
+    /// The renderer rejected an instruction
+    Render(crate::render::Error),
+    /// The compilation was cancelled through its [`Progress`](crate::Progress) handle
+    Cancelled,
+    /// The error was caused by the composition code at `location`
+    Located {
+        location: Location,
+        error: Box<Error>,
+    },
+    Io(io::Error),
+}
+
+impl Error {
+    /// Returns the location in the composition that caused the error, if known
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Self::Located { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Attaches a location to the error, unless it already has one
+    pub(crate) fn at(self, location: Option<&Location>) -> Self {
+        match location {
+            Some(location) if self.location().is_none() => Self::Located {
+                location: location.clone(),
+                error: Box::new(self),
+            },
+            _ => self,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingNode { id } => write!(f, "missing node {id}"),
+            Self::NodeReused { id } => write!(f, "node id {id} was reused"),
+            Self::NodeFinished { id } => write!(f, "node {id} has already been finished"),
+            Self::UnknownProcessor { processor } => {
+                write!(f, "non-existant processor {processor}")
+            }
+            Self::InvalidParameter {
+                node,
+                processor,
+                parameter,
+                reason,
+            } => write!(
+                f,
+                "invalid parameter {parameter} for node {node} (processor {processor}): {reason}"
+            ),
+            Self::InvalidConnection {
+                node,
+                source,
+                reason,
+            } => write!(f, "cannot connect node {source} to node {node}: {reason}"),
+            Self::InvalidFork { source, reason } => {
+                write!(f, "cannot fork node {source}: {reason}")
+            }
+            Self::MissingBuffer {
+                node,
+                buffer,
+                channel,
+            } => write!(
+                f,
+                "node {node} references missing buffer {buffer} channel {channel}"
+            ),
+            Self::Cycle { path } => {
+                write!(f, "the graph contains a cycle: ")?;
+                if let Some(last) = path.last() {
+                    write!(f, "{last}")?;
+                }
+                for entry in path {
+                    write!(f, " -> {entry}.{}", entry.parameter_name())?;
+                }
+                write!(
+                    f,
+                    "; use a `delay::Bus` to feed a signal back into the graph"
+                )
+            }
+            Self::InvalidTiming { reason } => write!(f, "invalid timing: {reason}"),
+            Self::Overflow => write!(f, "sample overflow"),
+            Self::Render(err) => write!(f, "invalid instruction {err:?}"),
+            Self::Cancelled => write!(f, "compilation was cancelled"),
+            Self::Located { location, error } => write!(f, "{location}: {error}"),
+            Self::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Located { error, .. } => error.source(),
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        // unwrap any errors that had to pass through the command handler
+        if err.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            return *err.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
+
+        Self::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            Error::Cancelled => io::Error::new(io::ErrorKind::Interrupted, err),
+            err => io::Error::new(io::ErrorKind::InvalidInput, err),
+        }
+    }
+}
+
+impl From<crate::render::Error> for Error {
+    fn from(err: crate::render::Error) -> Self {
+        Self::Render(err)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{error::CycleEntry, node::Node};
+use euphony_dsp::nodes;
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::{depth_first_search, Control, DfsEvent, EdgeRef, IntoEdgeReferences},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io,
+};
+
+pub type Graph = petgraph::stable_graph::StableGraph<u64, Edge>;
+
+/// A connection from a node's parameter to the node that feeds it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edge {
+    pub parameter: u64,
+}
+
+const SINK_PARAMETERS: [&str; 4] = ["input", "azimuth", "incline", "radius"];
+
+/// Returns the name of the processor, falling back to the id if it isn't known
+pub fn processor_name(processor: u64) -> String {
+    if processor == 0 {
+        return "sink".to_string();
+    }
+
+    nodes::path(processor).map_or_else(|| processor.to_string(), str::to_string)
+}
+
+/// Returns the name of the parameter, falling back to the id if it isn't known
+pub fn parameter_name(processor: u64, parameter: u64) -> String {
+    let name = if processor == 0 {
+        SINK_PARAMETERS.get(parameter as usize).copied()
+    } else {
+        nodes::parameter_name(processor, parameter)
+    };
+
+    name.map_or_else(|| parameter.to_string(), str::to_string)
+}
+
+/// Searches the nodes reachable from `roots` for a cycle
+///
+/// The cycle is returned as the list of edges in the order the signal flows through them, ending
+/// with the edge that closes the loop.
+pub fn find_cycle<R>(graph: &Graph, roots: R) -> Option<Vec<EdgeIndex>>
+where
+    R: IntoIterator<Item = NodeIndex>,
+{
+    let mut parents = HashMap::new();
+
+    let cycle = depth_first_search(graph, roots, |event| match event {
+        DfsEvent::TreeEdge(target, source) => {
+            parents.insert(source, target);
+            Control::Continue
+        }
+        DfsEvent::BackEdge(target, source) => Control::Break((target, source)),
+        _ => Control::Continue,
+    });
+
+    let (mut target, source) = cycle.break_value()?;
+
+    let mut edges = vec![graph.find_edge(target, source)?];
+    while target != source {
+        let parent = parents[&target];
+        edges.push(graph.find_edge(parent, target)?);
+        target = parent;
+    }
+
+    // the closing edge was found first
+    edges.rotate_left(1);
+
+    Some(edges)
+}
+
+/// Describes each node in the cycle along with the parameter fed by the previous node
+pub fn describe_cycle(
+    graph: &Graph,
+    nodes: &HashMap<u64, Node>,
+    edges: &[EdgeIndex],
+) -> Vec<CycleEntry> {
+    edges
+        .iter()
+        .map(|edge| {
+            let (target, _source) = graph.edge_endpoints(*edge).unwrap();
+            let node = graph[target];
+            CycleEntry {
+                node,
+                processor: nodes[&node].processor,
+                parameter: graph[*edge].parameter,
+            }
+        })
+        .collect()
+}
+
+/// Writes the graph in the Graphviz DOT format
+///
+/// Edges that are part of a cycle are highlighted.
+pub fn write_dot<O: io::Write>(
+    graph: &Graph,
+    nodes: &HashMap<u64, Node>,
+    output: &mut O,
+) -> io::Result<()> {
+    let cycle: BTreeSet<_> = find_cycle(graph, graph.node_indices())
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    writeln!(output, "digraph {{")?;
+
+    let mut ids: Vec<_> = graph.node_indices().map(|index| graph[index]).collect();
+    ids.sort_unstable();
+
+    for id in ids {
+        let processor = nodes[&id].processor;
+        let shape = if processor == 0 {
+            "doublecircle"
+        } else {
+            "box"
+        };
+        let name = processor_name(processor);
+        writeln!(output, "    n{id} [label=\"{id}: {name}\", shape={shape}];")?;
+    }
+
+    // the same parameter can be piped several times so only write each connection once
+    let mut connections = BTreeMap::<_, bool>::new();
+    for edge in graph.edge_references() {
+        // edges point from the target to the source
+        let target = graph[edge.source()];
+        let source = graph[edge.target()];
+        let in_cycle = connections
+            .entry((source, target, edge.weight().parameter))
+            .or_default();
+        *in_cycle |= cycle.contains(&edge.id());
+    }
+
+    for ((source, target, parameter), in_cycle) in connections {
+        let name = parameter_name(nodes[&target].processor, parameter);
+        let color = if in_cycle { ", color=red" } else { "" };
+        writeln!(
+            output,
+            "    n{source} -> n{target} [label=\"{name}\"{color}];"
+        )?;
+    }
+
+    writeln!(output, "}}")?;
+
+    Ok(())
+}
@@ -1,10 +1,4 @@
-use std::{io, sync::Arc};
-
-macro_rules! error {
-    ($fmt:literal $($tt:tt)*) => {
-        ::std::io::Error::new(::std::io::ErrorKind::InvalidInput, format!($fmt $($tt)*))
-    };
-}
+use std::{collections::HashSet, io, sync::Arc};
 
 pub trait Writer: Sync {
     fn is_cached(&self, hash: &Hash) -> bool;
@@ -49,21 +43,45 @@ pub mod sample {
     pub use euphony_dsp::sample::*;
 }
 
-// TODO better error?
-pub type Error = std::io::Error;
 pub type Result<T = (), E = Error> = core::result::Result<T, E>;
 pub type Hash = [u8; 32];
 
+mod annotations;
 mod buffer;
 mod compiler;
+mod error;
+mod graph;
 mod group;
 mod instruction;
 pub mod midi;
 mod node;
+mod optimize;
 mod parallel;
+mod progress;
 mod render;
 mod sink;
 
+use instruction::Instruction;
+
+pub use annotations::{Annotations, Marker, TempoChange, TimeSignatureChange};
+pub use error::{CycleEntry, Error, Location};
+pub use optimize::Report;
+pub use progress::{Progress, Snapshot};
+
+/// Resolves processor names in the textual command format through the `euphony-dsp` reflection
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Processors;
+
+impl euphony_command::asm::Resolver for Processors {
+    fn processor_name(&self, processor: u64) -> Option<&str> {
+        euphony_dsp::nodes::path(processor)
+    }
+
+    fn processor_id(&self, name: &str) -> Option<u64> {
+        euphony_dsp::nodes::id(name)
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Compiler {
     compiler: compiler::Compiler,
@@ -71,6 +89,56 @@ pub struct Compiler {
 }
 
 impl Compiler {
+    #[inline]
+    pub fn sample_rate(&self) -> sample::SampleRate {
+        self.compiler.sample_rate()
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, rate: sample::SampleRate) {
+        self.compiler.set_sample_rate(rate);
+    }
+
+    /// Returns a handle for observing and cancelling compilations
+    #[inline]
+    pub fn progress(&self) -> Progress {
+        self.compiler.progress().clone()
+    }
+
+    /// Replaces the progress handle, usually to share it between several compilers
+    #[inline]
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.compiler.set_progress(progress);
+    }
+
+    /// Returns the nodes that were optimized out of the last compilation
+    #[inline]
+    pub fn report(&self) -> &Report {
+        self.compiler.report()
+    }
+
+    /// Returns the tempo at the start of the last compilation, in beats per minute
+    #[inline]
+    pub fn tempo(&self) -> f64 {
+        self.compiler.tempo()
+    }
+
+    /// Returns the tempo map, time signatures and markers from the last compilation
+    #[inline]
+    pub fn annotations(&self) -> &Annotations {
+        self.compiler.annotations()
+    }
+
+    /// Returns the paths of the buffers loaded by the last compilation
+    #[inline]
+    pub fn buffers(&self) -> impl Iterator<Item = &str> + '_ {
+        self.compiler.buffer_paths()
+    }
+
+    /// Compiles the command stream into `output`
+    ///
+    /// Nodes that are unchanged from the previous call reuse their hashes so only the sinks that
+    /// depend on modified nodes are re-hashed and rendered.
     pub fn compile<I: io::Read, O: Writer>(&mut self, input: &mut I, output: &mut O) -> Result {
         // clear everything out first
         self.compiler.reset();
@@ -79,11 +147,29 @@ impl Compiler {
         euphony_command::decode(input, &mut self.compiler)?;
         let buffers = self.compiler.finalize(output)?;
         self.render.set_buffers(buffers);
+        self.render.set_sample_rate(self.sample_rate());
+
+        let progress = self.compiler.progress();
+        let mut sinks = HashSet::new();
 
         for instruction in self.compiler.instructions() {
-            self.render
-                .push(instruction, output)
-                .map_err(|err| error!("invalid instruction {:?}", err))?;
+            // check for cancellation before each block of samples
+            if let Instruction::AdvanceSamples { .. } = instruction {
+                progress.check()?;
+            }
+
+            self.render.push(instruction, output)?;
+
+            match instruction {
+                Instruction::AdvanceSamples { count } => progress.add_samples(count),
+                Instruction::SpawnSink { id, .. } => {
+                    sinks.insert(id);
+                }
+                Instruction::FinishNode { node } if sinks.remove(&node) => {
+                    progress.sink_rendered();
+                }
+                _ => {}
+            }
         }
 
         for (_id, group, entries) in self.compiler.groups() {
@@ -93,6 +179,20 @@ impl Compiler {
         Ok(())
     }
 
+    /// Writes the graph of nodes in the command stream in the Graphviz DOT format
+    ///
+    /// Cycles are highlighted rather than reported as errors.
+    pub fn graph<I: io::Read, O: io::Write>(&mut self, input: &mut I, output: &mut O) -> Result {
+        // clear everything out first
+        self.compiler.reset();
+        self.render.reset();
+
+        euphony_command::decode(input, &mut self.compiler)?;
+        self.compiler.write_dot(output)?;
+
+        Ok(())
+    }
+
     pub fn display<I: io::Read, O: io::Write>(&mut self, input: &mut I, output: &mut O) -> Result {
         // clear everything out first
         self.compiler.reset();
@@ -152,7 +252,7 @@ impl Compiler {
 mod tests {
     use super::*;
     use bolero::check;
-    use std::io::Cursor;
+    use std::{collections::HashSet, io::Cursor};
 
     struct Output;
 
@@ -186,6 +286,358 @@ mod tests {
         }
     }
 
+    type RecordedGroup = (String, Hash, Vec<(u64, Hash)>);
+
+    #[derive(Default)]
+    struct Recorder {
+        cached: HashSet<Hash>,
+        rendered: usize,
+        groups: Vec<RecordedGroup>,
+    }
+
+    impl Writer for Recorder {
+        fn is_cached(&self, hash: &Hash) -> bool {
+            self.cached.contains(hash)
+        }
+
+        fn sink(&mut self, hash: &Hash) -> euphony_node::BoxProcessor {
+            self.cached.insert(*hash);
+            self.rendered += 1;
+            // any processor with a first parameter will do
+            euphony_dsp::nodes::load(100).unwrap()
+        }
+
+        fn group<I: Iterator<Item = Entry>>(
+            &mut self,
+            name: &str,
+            hash: &Hash,
+            entries: I,
+            _midi: &midi::Writer,
+        ) {
+            let entries = entries.map(|e| (e.sample_offset, e.hash)).collect();
+            self.groups.push((name.to_string(), *hash, entries));
+        }
+
+        fn buffer<F: FnOnce(Box<dyn BufferReader>) -> Result<Vec<ConvertedBuffer>, E>, E>(
+            &self,
+            _path: &str,
+            _sample_rate: u64,
+            _init: F,
+        ) -> Result<Vec<CachedBuffer>, E> {
+            Ok(vec![])
+        }
+    }
+
+    fn assemble(freqs: [u32; 2]) -> Vec<u8> {
+        let input = format!(
+            r#"
+            GROUP id = 1, name = "a"
+            GROUP id = 2, name = "b"
+            SPAWN id = 0, processor = sink, group = 1
+            SPAWN id = 1, processor = osc::Sine
+            SET node = 1, param = 0, value = {}
+            PIPE node = 0, param = 0, source = 1
+            SPAWN id = 2, processor = sink, group = 2
+            SPAWN id = 3, processor = osc::Sine
+            SET node = 3, param = 0, value = {}
+            PIPE node = 2, param = 0, source = 3
+            ADVANCE ticks = 64
+            FIN node = 1
+            FIN node = 0
+            FIN node = 3
+            FIN node = 2
+            "#,
+            freqs[0], freqs[1]
+        );
+        euphony_command::asm::assemble(&input, &Processors).unwrap()
+    }
+
+    #[test]
+    fn incremental() {
+        let mut compiler = Compiler::default();
+        let mut output = Recorder::default();
+
+        compiler
+            .compile(&mut Cursor::new(assemble([440, 220])), &mut output)
+            .unwrap();
+        assert_eq!(output.rendered, 2);
+
+        // only the modified sink should be rendered again
+        output.groups.clear();
+        compiler
+            .compile(&mut Cursor::new(assemble([440, 330])), &mut output)
+            .unwrap();
+        assert_eq!(output.rendered, 3);
+
+        // the results should match a fresh compilation
+        let mut fresh = Recorder::default();
+        Compiler::default()
+            .compile(&mut Cursor::new(assemble([440, 330])), &mut fresh)
+            .unwrap();
+        assert_eq!(output.groups, fresh.groups);
+
+        // a failed compilation shouldn't disturb the previous results
+        output.groups.clear();
+        let _ = compiler.compile(&mut Cursor::new(&[0xff][..]), &mut output);
+        output.groups.clear();
+        compiler
+            .compile(&mut Cursor::new(assemble([440, 330])), &mut output)
+            .unwrap();
+        assert_eq!(output.rendered, 3);
+        assert_eq!(output.groups, fresh.groups);
+    }
+
+    #[test]
+    fn progress() {
+        let mut compiler = Compiler::default();
+        let mut output = Recorder::default();
+        let progress = compiler.progress();
+
+        compiler
+            .compile(&mut Cursor::new(assemble([440, 220])), &mut output)
+            .unwrap();
+
+        let snapshot = progress.snapshot();
+        assert_eq!(snapshot.nodes_hashed, 4);
+        assert_eq!(snapshot.sinks_total, 2);
+        assert_eq!(snapshot.sinks_rendered, 2);
+        assert!(snapshot.samples_total > 0);
+        assert!(snapshot.is_finished());
+
+        // a cancelled handle aborts the compilation
+        progress.reset();
+        progress.cancel();
+        let err = compiler
+            .compile(&mut Cursor::new(assemble([440, 330])), &mut output)
+            .unwrap_err();
+        assert!(matches!(err, Error::Cancelled));
+        assert_eq!(progress.snapshot().sinks_rendered, 0);
+    }
+
+    #[test]
+    fn located_errors() {
+        let compile = |input: &str| {
+            let input = euphony_command::asm::assemble(input, &Processors).unwrap();
+            Compiler::default()
+                .compile(&mut Cursor::new(input), &mut Recorder::default())
+                .unwrap_err()
+        };
+
+        let err = compile(
+            r#"
+            SPAWN id = 0, processor = sink
+            LOCATION file = "src/main.rs", line = 4, column = 12
+            SPAWN id = 1, processor = osc::Sine
+            FIN node = 1
+            LOCATION file = "src/main.rs", line = 7, column = 5
+            SET node = 1, param = 0, value = 440
+            "#,
+        );
+        assert_eq!(err.location().unwrap().to_string(), "src/main.rs:7:5");
+        assert_eq!(
+            err.to_string(),
+            "src/main.rs:7:5: node 1 has already been finished"
+        );
+
+        // messages without a location still produce a typed error
+        let err = compile("PIPE node = 0, param = 0, source = 1");
+        assert!(err.location().is_none());
+        assert!(matches!(err, Error::MissingNode { id: 1 }));
+
+        // the location is forgotten once a node is finished
+        let err = compile(
+            r#"
+            LOCATION file = "src/main.rs", line = 4, column = 12
+            SPAWN id = 1, processor = osc::Sine
+            FIN node = 1
+            SPAWN id = 2, processor = 9999
+            "#,
+        );
+        assert!(err.location().is_none());
+        assert!(matches!(err, Error::UnknownProcessor { processor: 9999 }));
+    }
+
+    #[test]
+    fn annotations() {
+        let input = euphony_command::asm::assemble(
+            r#"
+            SET_TIMING nanos_per_tick = 15625000, ticks_per_beat = 32
+            SET_TIME_SIGNATURE beats = 3, unit = 4
+            MARKER name = "intro"
+            ADVANCE ticks = 32
+            RAMP_TIMING nanos_per_tick = 7812500, ticks = 64
+            ADVANCE ticks = 64
+            MARKER name = "verse"
+            "#,
+            &Processors,
+        )
+        .unwrap();
+
+        let mut compiler = Compiler::default();
+        compiler
+            .compile(&mut Cursor::new(input), &mut Output)
+            .unwrap();
+
+        let annotations = compiler.annotations();
+
+        let tempo: Vec<_> = annotations
+            .tempo_map
+            .iter()
+            .map(|change| (change.sample, change.beat, change.bpm, change.ramp))
+            .collect();
+        assert_eq!(
+            tempo,
+            [
+                (0, 0.0, 120.0, false),
+                (24000, 1.0, 120.0, true),
+                (60000, 3.0, 240.0, false),
+            ]
+        );
+
+        assert_eq!(
+            annotations.time_signatures,
+            [TimeSignatureChange {
+                sample: 0,
+                beat: 0.0,
+                beats: 3,
+                unit: 4,
+            }]
+        );
+
+        let markers: Vec<_> = annotations
+            .markers
+            .iter()
+            .map(|marker| (marker.sample, marker.beat, marker.name.as_str()))
+            .collect();
+        assert_eq!(markers, [(0, 0.0, "intro"), (60000, 3.0, "verse")]);
+    }
+
+    #[test]
+    fn optimize() {
+        let input = euphony_command::asm::assemble(
+            r#"
+            SPAWN id = 0, processor = sink
+            SPAWN id = 1, processor = binary::Mul
+            SET node = 1, param = 0, value = 2
+            SET node = 1, param = 1, value = 3
+            SPAWN id = 2, processor = unary::Neg
+            PIPE node = 2, param = 0, source = 1
+            SPAWN id = 3, processor = osc::Sine
+            PIPE node = 3, param = 0, source = 2
+            SPAWN id = 4, processor = osc::Sine
+            PIPE node = 4, param = 0, source = 2
+            SPAWN id = 5, processor = binary::Add
+            PIPE node = 5, param = 0, source = 3
+            PIPE node = 5, param = 1, source = 4
+            PIPE node = 0, param = 0, source = 5
+            SPAWN id = 6, processor = osc::Sine
+            ADVANCE ticks = 64
+            "#,
+            &Processors,
+        )
+        .unwrap();
+
+        let mut compiler = Compiler::default();
+        let mut output = Recorder::default();
+        compiler
+            .compile(&mut Cursor::new(input), &mut output)
+            .unwrap();
+
+        let report = compiler.report();
+        assert_eq!(report.unreachable, [6]);
+        // the constant chain is folded into the sine's frequency
+        assert_eq!(report.folded, [(1, 6.0), (2, -6.0)]);
+        // which makes both of the sines identical
+        assert_eq!(report.merged, [(4, 3)]);
+        assert_eq!(
+            report.to_string(),
+            "removed 1 unreachable, folded 2 constant and merged 1 duplicate nodes"
+        );
+
+        let instructions: Vec<_> = compiler
+            .compiler
+            .instructions()
+            .map(|instruction| instruction.to_string())
+            .filter(|instruction| !instruction.starts_with("  SNK"))
+            .collect();
+        assert_eq!(
+            instructions,
+            [
+                "  SPN 3,100",
+                "  SPN 5,50",
+                "  SET 3,0,-6",
+                "  PIP 0,0,5",
+                "  PIP 5,0,3",
+                "  PIP 5,1,3",
+                "ADV 374",
+                "  FIN 0",
+                "  FIN 3",
+                "  FIN 5",
+            ]
+        );
+    }
+
+    const FEEDBACK: &str = r#"
+        SPAWN id = 0, processor = sink
+        LOCATION file = "src/main.rs", line = 4, column = 12
+        SPAWN id = 1, processor = osc::Sine
+        PIPE node = 0, param = 0, source = 1
+        LOCATION file = "src/main.rs", line = 5, column = 12
+        SPAWN id = 2, processor = binary::Mul
+        PIPE node = 2, param = 1, source = 1
+        PIPE node = 1, param = 0, source = 2
+        ADVANCE ticks = 64
+    "#;
+
+    #[test]
+    fn cycle_diagnostics() {
+        let input = euphony_command::asm::assemble(FEEDBACK, &Processors).unwrap();
+        let err = Compiler::default()
+            .compile(&mut Cursor::new(input), &mut Recorder::default())
+            .unwrap_err();
+
+        let Error::Located { location, error } = &err else {
+            panic!("expected a location: {err:?}");
+        };
+        assert_eq!(location.line, 5);
+
+        let Error::Cycle { path } = &**error else {
+            panic!("expected a cycle: {error:?}");
+        };
+        let path: Vec<_> = path.iter().map(|e| (e.node, e.parameter)).collect();
+        assert_eq!(path, [(1, 0), (2, 1)]);
+
+        assert_eq!(
+            err.to_string(),
+            "src/main.rs:5:12: the graph contains a cycle: \
+            node 2 (binary::Mul) -> node 1 (osc::Sine).frequency -> node 2 (binary::Mul).lhs; \
+            use a `delay::Bus` to feed a signal back into the graph"
+        );
+    }
+
+    #[test]
+    fn graph() {
+        let input = euphony_command::asm::assemble(FEEDBACK, &Processors).unwrap();
+        let mut output = vec![];
+        Compiler::default()
+            .graph(&mut Cursor::new(input), &mut output)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "digraph {\n",
+                "    n0 [label=\"0: sink\", shape=doublecircle];\n",
+                "    n1 [label=\"1: osc::Sine\", shape=box];\n",
+                "    n2 [label=\"2: binary::Mul\", shape=box];\n",
+                "    n1 -> n0 [label=\"input\"];\n",
+                "    n1 -> n2 [label=\"lhs\", color=red];\n",
+                "    n2 -> n1 [label=\"frequency\", color=red];\n",
+                "}\n",
+            )
+        );
+    }
+
     #[test]
     #[ignore] // this is currently broken
     fn fuzz() {
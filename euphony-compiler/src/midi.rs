@@ -1,4 +1,4 @@
-use crate::{sample::Offset, Hash};
+use crate::{sample::Offset, Hash, TempoChange};
 use core::ops::Deref;
 use euphony_units::time::Beat;
 use std::io;
@@ -73,8 +73,13 @@ impl<R: io::Read> Reader<R> {
         }
     }
 
-    pub fn write_smf<W: io::Write + io::Seek>(&mut self, out: W) -> io::Result<()> {
-        smf::write(self, out)
+    /// Writes the messages as a standard MIDI file with the composition's tempo map
+    pub fn write_smf<W: io::Write + io::Seek>(
+        &mut self,
+        tempo_map: &[TempoChange],
+        out: W,
+    ) -> io::Result<()> {
+        smf::write(self, tempo_map, out)
     }
 
     fn read_u64(&mut self) -> io::Result<u64> {
@@ -127,3 +132,43 @@ impl<R: io::Read> Iterator for Reader<R> {
         self.try_next().transpose()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smf_tempo_map() {
+        let mut writer = Writer::default();
+        writer.write(Offset::new(0), Beat(0, 1), [0x90, 60, 100]);
+        writer.write(Offset::new(48000), Beat(2, 1), [0x80, 60, 0]);
+
+        let change = |sample, beat, bpm, ramp| TempoChange {
+            sample,
+            beat,
+            bpm,
+            ramp,
+        };
+        let tempo_map = [
+            change(0, 0.0, 120.0, false),
+            change(24000, 1.0, 120.0, true),
+            change(30000, 1.25, 180.0, false),
+            change(96000, 4.0, 90.0, false),
+        ];
+
+        let mut out = io::Cursor::new(vec![]);
+        Reader::new(&writer[..])
+            .write_smf(&tempo_map, &mut out)
+            .unwrap();
+
+        let dump: Vec<String> = out
+            .into_inner()
+            .chunks(16)
+            .map(|line| {
+                let bytes: Vec<String> = line.iter().map(|byte| format!("{byte:02x}")).collect();
+                bytes.join(" ")
+            })
+            .collect();
+        insta::assert_snapshot!(dump.join("\n"));
+    }
+}
@@ -2,13 +2,18 @@ use euphony_units::time::Beat;
 use std::io;
 
 use super::Reader;
+use crate::TempoChange;
 
 const TICKS_PER_BEAT: u64 = 1 << 13;
 
 const MAX_DELTA: u64 = (1 << 28) - 1;
 
+/// SMF tempos can't ramp so ramps are written as a change every 16th of a beat
+const RAMP_STEP: u64 = TICKS_PER_BEAT / 16;
+
 pub fn write<R: io::Read, W: io::Write + io::Seek>(
     reader: &mut Reader<R>,
+    tempo_map: &[TempoChange],
     mut out: W,
 ) -> io::Result<()> {
     // "MThd" 4 bytes
@@ -40,21 +45,67 @@ pub fn write<R: io::Read, W: io::Write + io::Seek>(
     //     a sequenced track event.
 
     let mut beats = Beat(0, 1);
+    let mut tempos = tempo_events(tempo_map).into_iter().peekable();
 
     while let Some((_offset, beat, data)) = reader.try_next()? {
+        while let Some((tempo_beat, bpm)) = tempos.next_if(|(tempo_beat, _)| *tempo_beat <= beat) {
+            let delta = seek(&mut beats, tempo_beat, &mut out)?;
+            write_tempo(delta, bpm, &mut out)?;
+        }
+
         let delta = seek(&mut beats, beat, &mut out)?;
         write_message(delta, data, &mut out)?;
     }
 
+    // keep the changes after the last message so the file lines up with the rest of the timeline
+    for (tempo_beat, bpm) in tempos {
+        let delta = seek(&mut beats, tempo_beat, &mut out)?;
+        write_tempo(delta, bpm, &mut out)?;
+    }
+
+    // FF 2F 00
+    //
+    // This event is not optional. It is used to give the track a clearly defined length.
+    write_varlen(0, &mut out)?;
+    out.write_all(&[0xff, 0x2f, 0x00])?;
+
     let end_pos = out.stream_position()?;
     out.seek(io::SeekFrom::Start(len_pos))?;
 
-    let track_len = (end_pos - len_pos) as u32;
+    // the length doesn't include itself
+    let track_len = (end_pos - len_pos - 4) as u32;
     out.write_all(&track_len.to_be_bytes())?;
 
     Ok(())
 }
 
+/// Returns the beat and tempo of each change, with the ramps broken into steps
+fn tempo_events(tempo_map: &[TempoChange]) -> Vec<(Beat, f64)> {
+    let ticks = |beat: f64| (beat.max(0.0) * TICKS_PER_BEAT as f64).round() as u64;
+    let mut events = vec![];
+
+    for (idx, change) in tempo_map.iter().enumerate() {
+        let start = ticks(change.beat);
+        events.push((Beat(start, TICKS_PER_BEAT), change.bpm));
+
+        let next = match tempo_map.get(idx + 1) {
+            Some(next) if change.ramp => next,
+            _ => continue,
+        };
+
+        let end = ticks(next.beat);
+        let mut tick = start + RAMP_STEP;
+        while tick < end {
+            let t = (tick - start) as f64 / (end - start) as f64;
+            let bpm = change.bpm + (next.bpm - change.bpm) * t;
+            events.push((Beat(tick, TICKS_PER_BEAT), bpm));
+            tick += RAMP_STEP;
+        }
+    }
+
+    events
+}
+
 fn seek<W: io::Write>(beats: &mut Beat, target: Beat, out: &mut W) -> io::Result<u32> {
     let mut diff = ((target.as_ratio() - beats.as_ratio()) * TICKS_PER_BEAT).whole();
 
@@ -82,6 +133,17 @@ fn write_message<W: io::Write>(delta: u32, data: [u8; 3], out: &mut W) -> io::Re
     Ok(())
 }
 
+fn write_tempo<W: io::Write>(delta: u32, bpm: f64, out: &mut W) -> io::Result<()> {
+    // FF 51 03 tttttt
+    //
+    // tttttt is the number of microseconds in each quarter note
+    let micros = (60_000_000.0 / bpm).round().clamp(1.0, 0xff_ffff as f64) as u32;
+    write_varlen(delta, out)?;
+    out.write_all(&[0xff, 0x51, 0x03])?;
+    out.write_all(&micros.to_be_bytes()[1..])?;
+    Ok(())
+}
+
 fn write_varlen<W: io::Write>(value: u32, out: &mut W) -> io::Result<()> {
     let mut writing = false;
 
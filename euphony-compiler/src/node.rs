@@ -2,7 +2,7 @@ use crate::{
     instruction::InternalInstruction,
     sample::{Offset, RelOffset},
     sink::SinkMap,
-    Hash, Result,
+    Error, Hash, Location, Result,
 };
 use blake3::Hasher;
 use euphony_dsp::nodes;
@@ -12,6 +12,7 @@ use std::collections::BTreeMap;
 
 #[derive(Debug, Default)]
 pub struct Node {
+    pub id: u64,
     pub index: NodeIndex,
     pub inputs: BTreeMap<(RelOffset, u64, InputType), Value>,
     pub processor: u64,
@@ -19,6 +20,10 @@ pub struct Node {
     pub end: Option<RelOffset>,
     pub fork_source: Option<u64>,
     pub hash: Hash,
+    /// Set when the hash was carried over from the previous compilation
+    pub is_reused: bool,
+    /// The composition code that spawned the node, if known
+    pub location: Option<Location>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
@@ -69,7 +74,7 @@ impl Node {
 
     pub fn finish(&mut self, sample: Offset) -> Result {
         if self.end.is_some() {
-            return Err(error!("node has already been finished"));
+            return Err(Error::NodeFinished { id: self.id });
         }
 
         self.end = Some(sample.since(self.start));
@@ -77,6 +82,23 @@ impl Node {
         Ok(())
     }
 
+    /// Returns `true` if the node is identical to `prev` from the previous compilation
+    pub fn is_unchanged(&self, prev: &Self) -> bool {
+        self.processor == prev.processor
+            && self.start == prev.start
+            && self.end == prev.end
+            && self.fork_source == prev.fork_source
+            && self.inputs == prev.inputs
+    }
+
+    #[inline]
+    pub fn buffers(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.inputs.values().filter_map(|value| match value {
+            Value::Buffer(key) => Some(*key),
+            _ => None,
+        })
+    }
+
     pub fn hash(&mut self, hasher: &Hasher) {
         let mut hasher = hasher.clone();
         hasher.update(&self.processor.to_le_bytes());
@@ -161,23 +183,31 @@ impl Node {
 
     fn validate(&self, parameter: u64, value: euphony_node::ParameterValue) -> Result {
         if self.end.is_some() {
-            return Err(error!("node has already been finished"));
+            return Err(Error::NodeFinished { id: self.id });
         }
 
         // special case the sink
         if self.processor == 0 {
             if parameter > 4 {
-                return Err(error!(
-                    "invalid parameter for {}: {}",
-                    self.processor, parameter
-                ));
+                return Err(
+                    self.invalid_parameter(parameter, "sinks only have 5 parameters".into())
+                );
             }
             return Ok(());
         }
 
         nodes::validate_parameter(self.processor, parameter, value)
-            .map_err(|err| error!("invalid parameter for {}: {}", self.processor, err))?;
+            .map_err(|reason| self.invalid_parameter(parameter, reason))?;
 
         Ok(())
     }
+
+    fn invalid_parameter(&self, parameter: u64, reason: String) -> Error {
+        Error::InvalidParameter {
+            node: self.id,
+            processor: self.processor,
+            parameter,
+            reason,
+        }
+    }
 }
@@ -0,0 +1,353 @@
+use crate::{
+    graph::Graph,
+    node::{InputType, Node},
+    sample::Offset,
+    sink::SinkMap,
+};
+use core::fmt;
+use euphony_dsp::nodes;
+use euphony_node::{Context, ParameterValue as Value};
+use petgraph::{algo::toposort, visit::EdgeRef, Direction};
+use std::collections::{BTreeSet, HashMap};
+
+/// Describes the nodes that were removed from the graph by [`optimize`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// Nodes that don't feed into any sink
+    pub unreachable: Vec<u64>,
+    /// Nodes that only ever output a constant, along with the value that replaced them
+    pub folded: Vec<(u64, f64)>,
+    /// Nodes that were identical to another node, along with the node that replaced them
+    pub merged: Vec<(u64, u64)>,
+}
+
+impl Report {
+    /// Returns the total number of removed nodes
+    #[inline]
+    pub fn removed(&self) -> usize {
+        self.unreachable.len() + self.folded.len() + self.merged.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.removed() == 0
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.unreachable.clear();
+        self.folded.clear();
+        self.merged.clear();
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "removed {} unreachable, folded {} constant and merged {} duplicate nodes",
+            self.unreachable.len(),
+            self.folded.len(),
+            self.merged.len()
+        )
+    }
+}
+
+/// Simplifies the graph before it's hashed
+///
+/// The graph must not contain any cycles reachable from the sinks and every node must be
+/// finished.
+pub fn optimize(
+    graph: &mut Graph,
+    nodes: &mut HashMap<u64, Node>,
+    sinks: &SinkMap,
+    report: &mut Report,
+) {
+    remove_unreachable(graph, nodes, sinks, report);
+
+    // the graph is acyclic now that only the sink trees remain
+    let Ok(mut order) = toposort(&*graph, None) else {
+        return;
+    };
+    // edges point from the target to the source so reverse it to visit the sources first
+    order.reverse();
+    let order: Vec<_> = order.into_iter().map(|index| graph[index]).collect();
+
+    fold_constants(graph, nodes, &order, report);
+    merge_duplicates(graph, nodes, &order, report);
+}
+
+/// Removes all of the nodes that can't reach a sink
+fn remove_unreachable(
+    graph: &mut Graph,
+    nodes: &mut HashMap<u64, Node>,
+    sinks: &SinkMap,
+    report: &mut Report,
+) {
+    let mut reachable = BTreeSet::new();
+    let mut stack: Vec<_> = sinks.keys().copied().collect();
+
+    while let Some(id) = stack.pop() {
+        if !reachable.insert(id) {
+            continue;
+        }
+
+        let node = &nodes[&id];
+        stack.extend(node.sources());
+        // forked nodes need their source to be spawned first
+        stack.extend(node.fork_source);
+    }
+
+    nodes.retain(|id, node| {
+        if reachable.contains(id) {
+            return true;
+        }
+        graph.remove_node(node.index);
+        report.unreachable.push(*id);
+        false
+    });
+
+    report.unreachable.sort_unstable();
+}
+
+/// Replaces nodes with constant outputs with the value they produce
+fn fold_constants(
+    graph: &mut Graph,
+    nodes: &mut HashMap<u64, Node>,
+    order: &[u64],
+    report: &mut Report,
+) {
+    // fork targets inherit connections without any edges in the graph so look at the inputs
+    let mut consumers = HashMap::<u64, BTreeSet<u64>>::new();
+    for (id, node) in nodes.iter() {
+        for source in node.sources() {
+            consumers.entry(source).or_default().insert(*id);
+        }
+    }
+
+    let forked: BTreeSet<_> = nodes.values().filter_map(|node| node.fork_source).collect();
+
+    for id in order {
+        let Some(node) = nodes.get(id) else {
+            continue;
+        };
+
+        if !is_pure(node.processor) || node.fork_source.is_some() || forked.contains(id) {
+            continue;
+        }
+
+        // the output is only constant if the inputs never change
+        let is_constant = node.inputs.iter().all(|((sample, _param, ty), value)| {
+            u64::from(*sample) == 0
+                && *ty == InputType::Signal
+                && matches!(value, Value::Constant(_))
+        });
+        if !is_constant {
+            continue;
+        }
+
+        let end = node.start + node.end.unwrap();
+        let targets = consumers.remove(id).unwrap_or_default();
+
+        let is_foldable = targets.iter().all(|target| {
+            let target = &nodes[target];
+
+            // keep the sink's length the same by leaving its direct inputs alone
+            if target.processor == 0 {
+                return false;
+            }
+
+            // the node needs to be alive when it's connected
+            target.connections(*id).all(|(sample, _)| end > sample)
+        });
+
+        let value = if is_foldable { evaluate(node) } else { None };
+
+        let Some(value) = value else {
+            if !targets.is_empty() {
+                consumers.insert(*id, targets);
+            }
+            continue;
+        };
+
+        for target in &targets {
+            nodes
+                .get_mut(target)
+                .unwrap()
+                .replace_connection(*id, value, end);
+        }
+
+        let node = nodes.remove(id).unwrap();
+        graph.remove_node(node.index);
+        report.folded.push((*id, value));
+    }
+}
+
+/// Replaces nodes with a previous node that has identical inputs over the same time span
+fn merge_duplicates(
+    graph: &mut Graph,
+    nodes: &mut HashMap<u64, Node>,
+    order: &[u64],
+    report: &mut Report,
+) {
+    let forked: BTreeSet<_> = nodes.values().filter_map(|node| node.fork_source).collect();
+    let mut hashes = HashMap::new();
+    let mut replacements = HashMap::new();
+
+    for id in order {
+        let Some(node) = nodes.get(id) else {
+            continue;
+        };
+
+        if node.processor == 0 || node.fork_source.is_some() || forked.contains(id) {
+            continue;
+        }
+
+        let hash = node.structural_hash(&replacements);
+
+        let Some(replacement) = hashes.get(&hash).copied() else {
+            hashes.insert(hash, *id);
+            continue;
+        };
+
+        let index = node.index;
+        let replacement_index = nodes[&replacement].index;
+
+        // move the connections over to the replacement
+        let edges: Vec<_> = graph
+            .edges_directed(index, Direction::Incoming)
+            .map(|edge| (edge.source(), *edge.weight()))
+            .collect();
+        for (target, edge) in edges {
+            graph.add_edge(target, replacement_index, edge);
+        }
+
+        graph.remove_node(index);
+        nodes.remove(id);
+        replacements.insert(*id, replacement);
+        report.merged.push((*id, replacement));
+    }
+
+    if replacements.is_empty() {
+        return;
+    }
+
+    for node in nodes.values_mut() {
+        for value in node.inputs.values_mut() {
+            if let Value::Node(source) = value {
+                if let Some(replacement) = replacements.get(source) {
+                    *source = *replacement;
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if the processor's output only depends on its current inputs
+fn is_pure(processor: u64) -> bool {
+    nodes::path(processor).is_some_and(|path| {
+        ["unary::", "binary::", "tertiary::"]
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    })
+}
+
+/// Computes the first sample of a node with constant inputs
+fn evaluate(node: &Node) -> Option<f64> {
+    let mut graph = euphony_graph::Graph::<euphony_node::Config>::default();
+    graph.insert(node.id, nodes::load(node.processor)?);
+
+    for ((_sample, parameter, _type), value) in &node.inputs {
+        if let Value::Constant(value) = value {
+            let value = euphony_node::Value::Constant(*value);
+            graph.set(node.id, *parameter, value).ok()?;
+        }
+    }
+
+    graph.update().ok()?;
+
+    let context = Context {
+        partial: Some(1),
+        ..Default::default()
+    };
+    graph.process(&context);
+
+    graph.get(node.id).ok().map(|output| output[0])
+}
+
+impl Node {
+    /// Returns the nodes connected to the inputs
+    fn sources(&self) -> impl Iterator<Item = u64> + '_ {
+        self.inputs.values().filter_map(|value| match value {
+            Value::Node(source) => Some(*source),
+            _ => None,
+        })
+    }
+
+    /// Returns the absolute sample and parameter of each connection to `source`
+    fn connections(&self, source: u64) -> impl Iterator<Item = (Offset, u64)> + '_ {
+        self.inputs
+            .iter()
+            .filter(move |(_, value)| **value == Value::Node(source))
+            .map(|((sample, parameter, _type), _)| (self.start + *sample, *parameter))
+    }
+
+    /// Replaces each connection to `source` with `value`
+    ///
+    /// The renderer resets a parameter to `0.0` when its source finishes so that's preserved if
+    /// the parameter is still in use at `source_end`.
+    fn replace_connection(&mut self, source: u64, value: f64, source_end: Offset) {
+        let end = self.start + self.end.unwrap();
+        let connections: Vec<_> = self.connections(source).collect();
+
+        for (sample, parameter) in connections {
+            let key = (sample.since(self.start), parameter, InputType::Signal);
+            self.inputs.insert(key, Value::Constant(value));
+
+            let next = self
+                .inputs
+                .keys()
+                .filter(|(next, param, _type)| *param == parameter && self.start + *next > sample)
+                .map(|(next, _param, _type)| self.start + *next)
+                .min()
+                .unwrap_or(end);
+
+            if source_end < next {
+                let key = (source_end.since(self.start), parameter, InputType::Signal);
+                self.inputs.insert(key, Value::Constant(0.0));
+            }
+        }
+    }
+
+    /// Hashes the node's behavior, treating nodes in `replacements` as their replacements
+    fn structural_hash(&self, replacements: &HashMap<u64, u64>) -> crate::Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.processor.to_le_bytes());
+        hasher.update(&self.start.to_bytes());
+        hasher.update(&self.end.unwrap().to_bytes());
+
+        for ((sample, param, _type), value) in &self.inputs {
+            hasher.update(&sample.to_bytes());
+            hasher.update(&param.to_le_bytes());
+
+            match value {
+                Value::Constant(value) => {
+                    hasher.update(&[0]);
+                    hasher.update(&value.to_le_bytes());
+                }
+                Value::Node(source) => {
+                    let source = replacements.get(source).unwrap_or(source);
+                    hasher.update(&[1]);
+                    hasher.update(&source.to_le_bytes());
+                }
+                Value::Buffer((buffer, channel)) => {
+                    hasher.update(&[2]);
+                    hasher.update(&buffer.to_le_bytes());
+                    hasher.update(&channel.to_le_bytes());
+                }
+            }
+        }
+
+        *hasher.finalize().as_bytes()
+    }
+}
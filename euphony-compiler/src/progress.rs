@@ -0,0 +1,127 @@
+use crate::{Error, Result};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+/// Shared handle for observing and cancelling a compilation
+///
+/// Handles are cheap to clone and can be shared between several compilers, in which case the
+/// counters accumulate across all of them. Call [`Progress::reset`] before starting a new build.
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    nodes_hashed: AtomicU64,
+    nodes_total: AtomicU64,
+    sinks_rendered: AtomicU64,
+    sinks_total: AtomicU64,
+    samples: AtomicU64,
+    samples_total: AtomicU64,
+    is_cancelled: AtomicBool,
+}
+
+/// A point-in-time view of a [`Progress`] handle
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub nodes_hashed: u64,
+    pub nodes_total: u64,
+    pub sinks_rendered: u64,
+    pub sinks_total: u64,
+    pub samples: u64,
+    pub samples_total: u64,
+}
+
+impl Snapshot {
+    /// Returns the rendered fraction, between `0.0` and `1.0`
+    #[inline]
+    pub fn ratio(&self) -> f64 {
+        if self.samples_total == 0 {
+            return if self.sinks_rendered == self.sinks_total {
+                1.0
+            } else {
+                0.0
+            };
+        }
+
+        (self.samples as f64 / self.samples_total as f64).min(1.0)
+    }
+
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.sinks_rendered == self.sinks_total && self.samples >= self.samples_total
+    }
+}
+
+impl Progress {
+    #[inline]
+    pub fn snapshot(&self) -> Snapshot {
+        let inner = &*self.inner;
+        Snapshot {
+            nodes_hashed: inner.nodes_hashed.load(Ordering::Relaxed),
+            nodes_total: inner.nodes_total.load(Ordering::Relaxed),
+            sinks_rendered: inner.sinks_rendered.load(Ordering::Relaxed),
+            sinks_total: inner.sinks_total.load(Ordering::Relaxed),
+            samples: inner.samples.load(Ordering::Relaxed),
+            samples_total: inner.samples_total.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Requests that any compilations using this handle stop as soon as possible
+    #[inline]
+    pub fn cancel(&self) {
+        self.inner.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Clears the counters and any pending cancellation
+    pub fn reset(&self) {
+        let inner = &*self.inner;
+        inner.nodes_hashed.store(0, Ordering::Relaxed);
+        inner.nodes_total.store(0, Ordering::Relaxed);
+        inner.sinks_rendered.store(0, Ordering::Relaxed);
+        inner.sinks_total.store(0, Ordering::Relaxed);
+        inner.samples.store(0, Ordering::Relaxed);
+        inner.samples_total.store(0, Ordering::Relaxed);
+        inner.is_cancelled.store(false, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn check(&self) -> Result {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn add_nodes(&self, hashed: u64, total: u64) {
+        let inner = &*self.inner;
+        inner.nodes_hashed.fetch_add(hashed, Ordering::Relaxed);
+        inner.nodes_total.fetch_add(total, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn add_totals(&self, sinks: u64, samples: u64) {
+        let inner = &*self.inner;
+        inner.sinks_total.fetch_add(sinks, Ordering::Relaxed);
+        inner.samples_total.fetch_add(samples, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn sink_rendered(&self) {
+        self.inner.sinks_rendered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn add_samples(&self, samples: u64) {
+        self.inner.samples.fetch_add(samples, Ordering::Relaxed);
+    }
+}
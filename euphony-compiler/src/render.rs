@@ -1,7 +1,7 @@
 use crate::{instruction::Instruction, Hash, Writer};
-use euphony_dsp::nodes::load as load_dsp;
+use euphony_dsp::{nodes::load as load_dsp, sample::SampleRate};
 use euphony_graph::Graph;
-use euphony_node::{BufferMap, Config, Context, ParameterValue, Value};
+use euphony_node::{rate, BufferMap, Config, Context, ParameterValue, Value};
 
 pub type Error = euphony_graph::Error<u64>;
 pub type Result<T = (), E = Error> = core::result::Result<T, E>;
@@ -19,8 +19,19 @@ impl Renderer {
         self.context.buffers = buffers;
     }
 
+    #[inline]
+    pub fn set_sample_rate(&mut self, rate: SampleRate) {
+        self.context.sample_rate = rate.into();
+    }
+
     #[inline]
     pub fn push<W: Writer>(&mut self, instr: Instruction, writer: &mut W) -> Result {
+        // nodes also read the rate when they're constructed and triggered
+        rate::scope(self.context.sample_rate, || self.apply(instr, writer))
+    }
+
+    #[inline]
+    fn apply<W: Writer>(&mut self, instr: Instruction, writer: &mut W) -> Result {
         match instr {
             Instruction::AdvanceSamples { count } => self.advance(count),
             Instruction::SpawnNode { id, processor } => self.spawn(id, processor),
@@ -112,7 +123,7 @@ impl Renderer {
 
     #[inline]
     fn finish_node(&mut self, id: u64) -> Result {
-        self.graph.remove(id)?;
+        self.graph.remove(id)?.finish();
         Ok(())
     }
 }
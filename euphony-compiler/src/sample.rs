@@ -1,22 +1,75 @@
 use core::ops;
-use euphony_dsp::sample::{DefaultRate as Rate, Rate as _};
+use euphony_dsp::sample::SampleRate;
 use euphony_units::{
     ratio::Ratio,
     time::{Beat, Tempo},
 };
 
 #[inline]
-pub fn default_samples_per_tick() -> Ratio<u128> {
+pub fn default_nanos_per_tick() -> u64 {
     let duration = Tempo::DEFAULT * Beat::DEFAULT_RESOLUTION;
-    samples_per_tick(duration.as_nanos() as _)
+    duration.as_nanos() as _
 }
 
 #[inline]
-pub fn samples_per_tick(nanos_per_tick: u64) -> Ratio<u128> {
-    let Ratio(a, b) = Rate::NANOS_PER_SAMPLE;
+pub fn default_samples_per_tick(rate: SampleRate) -> Ratio<u128> {
+    samples_per_tick(default_nanos_per_tick(), rate)
+}
+
+#[inline]
+pub fn samples_per_tick(nanos_per_tick: u64, rate: SampleRate) -> Ratio<u128> {
+    let Ratio(a, b) = rate.nanos_per_sample();
     Ratio(nanos_per_tick as u128, 1) / Ratio(a as u128, b as u128)
 }
 
+/// A linear change in tick duration over a span of ticks
+#[derive(Clone, Copy, Debug)]
+pub struct Ramp {
+    /// The sample offset at the start of the ramp
+    pub start: Offset,
+    pub start_nanos: u64,
+    pub end_nanos: u64,
+    pub ticks: u64,
+    /// The number of ticks that have elapsed since the start of the ramp
+    pub elapsed: u64,
+}
+
+impl Ramp {
+    /// Returns the tick duration at the current position in the ramp
+    #[inline]
+    pub fn nanos_per_tick(&self) -> u64 {
+        let start = self.start_nanos as u128;
+        let end = self.end_nanos as u128;
+        let ticks = self.ticks as u128;
+        let elapsed = self.elapsed as u128;
+        ((start * (ticks - elapsed) + end * elapsed) / ticks) as u64
+    }
+
+    /// Returns the number of samples between the start of the ramp and `ticks` into it
+    ///
+    /// The tick duration changes linearly, so the elapsed time is the integral
+    /// `d0 * x + (d1 - d0) * x^2 / 2T`, which is computed exactly before rounding down to a
+    /// sample.
+    #[inline]
+    pub fn samples(&self, ticks: u64, rate: SampleRate) -> Option<u64> {
+        debug_assert!(ticks <= self.ticks);
+
+        let start = self.start_nanos as u128;
+        let end = self.end_nanos as u128;
+        let len = self.ticks as u128;
+        let x = ticks as u128;
+
+        let nanos = start
+            .checked_mul(2 * len - x)?
+            .checked_add(end.checked_mul(x)?)?
+            .checked_mul(x)?;
+
+        let Ratio(a, b) = rate.nanos_per_sample();
+        let samples = nanos.checked_mul(b as u128)? / (2 * len).checked_mul(a as u128)?;
+        samples.try_into().ok()
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Offset(u64);
 
@@ -81,3 +134,53 @@ impl From<RelOffset> for u64 {
         sample.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_samples() {
+        // one second worth of ticks should produce exactly `rate` samples
+        for hz in [44_100u32, 48_000, 96_000] {
+            let rate = SampleRate::new(hz).unwrap();
+            let Ratio(a, b) = samples_per_tick(1_000_000_000, rate);
+            assert_eq!(a / b, hz as u128);
+            assert_eq!(a % b, 0);
+        }
+    }
+
+    #[test]
+    fn ramp_samples() {
+        let rate = SampleRate::DEFAULT;
+        let nanos = default_nanos_per_tick();
+        let Ratio(a, b) = default_samples_per_tick(rate);
+
+        // a ramp that doesn't change the tempo matches the fixed rate
+        let ramp = Ramp {
+            start: Offset::default(),
+            start_nanos: nanos,
+            end_nanos: nanos,
+            ticks: 4096,
+            elapsed: 0,
+        };
+        for ticks in [0, 1, 100, 4096] {
+            let expected = (a * ticks as u128 / b) as u64;
+            assert_eq!(ramp.samples(ticks, rate), Some(expected));
+        }
+
+        // doubling the tick duration over the ramp averages to 1.5x the duration
+        let ramp = Ramp {
+            end_nanos: nanos * 2,
+            ..ramp
+        };
+        let expected = (a * 4096 * 3 / (b * 2)) as u64;
+        assert_eq!(ramp.samples(4096, rate), Some(expected));
+
+        let ramp = Ramp {
+            elapsed: 2048,
+            ..ramp
+        };
+        assert_eq!(ramp.nanos_per_tick(), nanos * 3 / 2);
+    }
+}
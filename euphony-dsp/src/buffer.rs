@@ -45,3 +45,467 @@ impl Play {
         }
     }
 }
+
+#[derive(Debug, Default, Node)]
+#[node(id = 501, module = "buffer")]
+#[buffer(buffer)]
+#[input(rate, default = 1.0)]
+#[input(start)]
+#[input(end)]
+#[input(crossfade)]
+#[input(looped, trigger = set_looped)]
+#[input(samples, trigger = set_samples)]
+#[input(reset, trigger = set_reset)]
+/// Plays a slice of a buffer at a variable rate
+///
+/// `start` and `end` are in seconds, or in samples when `samples` is set. An `end` of zero plays
+/// to the end of the buffer. Negative rates play the slice in reverse. When `looped` is set, the
+/// last `crossfade` seconds before the loop point are faded into the audio leading up to the
+/// other side of the slice, limited to how much of that audio is in the buffer.
+pub struct Sampler {
+    position: f64,
+    looped: bool,
+    samples: bool,
+    is_started: bool,
+    is_finished: bool,
+}
+
+impl Sampler {
+    fn set_looped(&mut self, value: f64) {
+        self.looped = value >= 1.0;
+    }
+
+    fn set_samples(&mut self, value: f64) {
+        self.samples = value >= 1.0;
+    }
+
+    fn set_reset(&mut self, _value: f64) {
+        self.is_started = false;
+        self.is_finished = false;
+    }
+
+    #[inline]
+    pub fn render(
+        &mut self,
+        rate: Input,
+        start: Input,
+        end: Input,
+        crossfade: Input,
+        buffer: Buffer,
+        output: &mut [Sample],
+    ) {
+        let samples = buffer.samples;
+        if samples.is_empty() {
+            output.fill(0.0);
+            return;
+        }
+
+        let len = samples.len() as f64;
+        let sample_rate = Rate::value();
+        let scale = if self.samples { 1.0 } else { sample_rate };
+
+        for (rate, start, end, crossfade, output) in
+            (rate, start, end, crossfade, output.iter_mut()).zip()
+        {
+            let start = (start * scale).clamp(0.0, len);
+            let mut end = (end * scale).min(len);
+            if end <= start {
+                end = len;
+            }
+            let span = end - start;
+
+            if !self.is_started {
+                self.position = if rate < 0.0 { end - 1.0 } else { start };
+                self.is_started = true;
+            }
+
+            if self.is_finished || span <= 0.0 {
+                *output = 0.0;
+                continue;
+            }
+
+            let mut position = self.position;
+            if position < start || position >= end {
+                if !self.looped {
+                    self.is_finished = true;
+                    *output = 0.0;
+                    continue;
+                }
+
+                position = start + (position - start).rem_euclid(span);
+            }
+
+            let mut value = interpolate(samples, position);
+
+            let fade = (crossfade * sample_rate).min(span * 0.5);
+            if self.looped && fade > 0.0 {
+                // the distance to the loop point, where the audio continues on the other side and
+                // how much audio there is leading up to it
+                let (distance, other, preroll) = if rate < 0.0 {
+                    (position - start, position + span, len - end)
+                } else {
+                    (end - position, position - span, start)
+                };
+
+                // fading into audio from outside of the buffer would fade to silence
+                let fade = fade.min(preroll);
+
+                if distance < fade {
+                    let t = (1.0 - distance / fade) * core::f64::consts::FRAC_PI_2;
+                    value = value * t.cos() + interpolate(samples, other) * t.sin();
+                }
+            }
+
+            *output = value;
+            self.position = position + rate;
+        }
+    }
+}
+
+#[derive(Debug, Node)]
+#[node(id = 502, module = "buffer")]
+#[buffer(buffer)]
+#[input(position)]
+#[input(size, default = 0.05)]
+#[input(density, default = 20.0)]
+#[input(pitch, default = 1.0)]
+#[input(spray)]
+#[input(jitter)]
+#[input(window, default = 1.0)]
+#[input(seed, trigger = set_seed)]
+/// Granular synthesis over a buffer
+///
+/// Grains of `size` seconds are started `density` times per second at `position`, a fraction of
+/// the buffer's length, and played back at `pitch`. `spray` randomly offsets each grain's start
+/// by up to that many seconds and `jitter` randomizes the time between grains as a fraction of
+/// the interval. `window` tapers the grains, from a rectangle at 0 to a Hann window at 1.
+pub struct Granular {
+    grains: Vec<Grain>,
+    /// The number of samples until the next grain starts
+    countdown: f64,
+    rng: Rng,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Grain {
+    position: f64,
+    rate: f64,
+    age: usize,
+    len: usize,
+    taper: f64,
+}
+
+impl Grain {
+    /// Returns the grain's envelope as a Tukey window
+    #[inline]
+    fn window(&self) -> f64 {
+        let taper = self.taper;
+        if taper <= 0.0 {
+            return 1.0;
+        }
+
+        let t = self.age as f64 / self.len as f64;
+        let edge = taper * 0.5;
+        let t = if t < edge {
+            t / edge
+        } else if t > 1.0 - edge {
+            (1.0 - t) / edge
+        } else {
+            return 1.0;
+        };
+
+        0.5 - 0.5 * (core::f64::consts::PI * t).cos()
+    }
+}
+
+/// Grains that would go over the limit are dropped rather than allocating on the audio thread
+const MAX_GRAINS: usize = 128;
+
+impl Default for Granular {
+    fn default() -> Self {
+        Self {
+            grains: Vec::with_capacity(MAX_GRAINS),
+            countdown: 0.0,
+            rng: Rng::new(0.0),
+        }
+    }
+}
+
+impl Granular {
+    fn set_seed(&mut self, value: f64) {
+        self.rng = Rng::new(value);
+    }
+
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        position: Input,
+        size: Input,
+        density: Input,
+        pitch: Input,
+        spray: Input,
+        jitter: Input,
+        window: Input,
+        buffer: Buffer,
+        output: &mut [Sample],
+    ) {
+        let samples = buffer.samples;
+        if samples.is_empty() {
+            self.grains.clear();
+            output.fill(0.0);
+            return;
+        }
+
+        let len = samples.len() as f64;
+        let sample_rate = Rate::value();
+
+        for (position, size, density, pitch, spray, jitter, window, output) in (
+            position,
+            size,
+            density,
+            pitch,
+            spray,
+            jitter,
+            window,
+            output.iter_mut(),
+        )
+            .zip()
+        {
+            let grain_len = (size * sample_rate).max(1.0);
+
+            if density > 0.0 {
+                self.countdown -= 1.0;
+
+                if self.countdown <= 0.0 {
+                    let interval = sample_rate / density;
+                    let jitter = jitter.clamp(0.0, 1.0) * self.rng.signed();
+                    self.countdown += (interval * (1.0 + jitter)).max(1.0);
+
+                    if self.grains.len() < MAX_GRAINS {
+                        let offset = spray * sample_rate * self.rng.signed();
+                        let start = position.fract().abs() * len + offset;
+                        // grains played in reverse start from their end
+                        let start = if pitch < 0.0 {
+                            start + grain_len * -pitch
+                        } else {
+                            start
+                        };
+
+                        self.grains.push(Grain {
+                            position: start.rem_euclid(len),
+                            rate: pitch,
+                            age: 0,
+                            len: grain_len as usize,
+                            taper: window.clamp(0.0, 1.0),
+                        });
+                    }
+                }
+            }
+
+            // keep the level steady as the grains overlap
+            let overlap = (density * grain_len / sample_rate).max(1.0);
+            let gain = overlap.sqrt().recip();
+
+            let mut value = 0.0;
+            let mut idx = 0;
+            while let Some(grain) = self.grains.get_mut(idx) {
+                value += interpolate(samples, grain.position) * grain.window();
+
+                grain.position += grain.rate;
+                grain.age += 1;
+
+                if grain.age >= grain.len {
+                    self.grains.swap_remove(idx);
+                } else {
+                    idx += 1;
+                }
+            }
+
+            *output = value * gain;
+        }
+    }
+}
+
+/// A xorshift64* generator so grains are placed the same way for the same seed
+#[derive(Clone, Copy, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    #[inline]
+    fn new(seed: f64) -> Self {
+        // xorshift gets stuck on a zero state
+        let state = seed.to_bits() ^ 0x9e37_79b9_7f4a_7c15;
+        Self(if state == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            state
+        })
+    }
+
+    /// Returns a value in `-1.0..1.0`
+    #[inline]
+    fn signed(&mut self) -> f64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        let value = x.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11;
+        (value as f64 / (1u64 << 53) as f64) * 2.0 - 1.0
+    }
+}
+
+/// Reads a fractional position with 4-point Hermite interpolation
+///
+/// Positions outside of the buffer read as silence.
+#[inline]
+fn interpolate(samples: &[Sample], position: f64) -> Sample {
+    let index = position.floor();
+    let fract = position - index;
+    let index = index as isize;
+
+    let get = |offset: isize| {
+        let idx = index + offset;
+        if idx < 0 {
+            return 0.0;
+        }
+        samples.get(idx as usize).copied().unwrap_or(0.0)
+    };
+
+    let (y0, y1, y2, y3) = (get(-1), get(0), get(1), get(2));
+
+    let c1 = 0.5 * (y2 - y0);
+    let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+
+    ((c3 * fract + c2) * fract + c1) * fract + y1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp() -> Vec<Sample> {
+        (0..100).map(|v| v as Sample).collect()
+    }
+
+    fn render(sampler: &mut Sampler, rate: f64, start: f64, end: f64, len: usize) -> Vec<f64> {
+        let samples = ramp();
+        let mut out = vec![0.0; len];
+        sampler.render(
+            rate.into(),
+            start.into(),
+            end.into(),
+            0.0.into(),
+            samples[..].into(),
+            &mut out,
+        );
+        out
+    }
+
+    #[test]
+    fn rates() {
+        let mut sampler = Sampler::new();
+        sampler.set_samples(1.0);
+        let out = render(&mut sampler, 0.5, 10.0, 0.0, 4);
+        assert_eq!(out, [10.0, 10.5, 11.0, 11.5]);
+
+        let mut sampler = Sampler::new();
+        let out = render(&mut sampler, -2.0, 0.0, 0.0, 4);
+        assert_eq!(out, [99.0, 97.0, 95.0, 93.0]);
+    }
+
+    #[test]
+    fn slices() {
+        let mut sampler = Sampler::new();
+        sampler.set_samples(1.0);
+        let out = render(&mut sampler, 1.0, 10.0, 13.0, 5);
+        assert_eq!(out, [10.0, 11.0, 12.0, 0.0, 0.0]);
+
+        sampler.set_looped(1.0);
+        sampler.set_reset(1.0);
+        let out = render(&mut sampler, 1.0, 10.0, 13.0, 5);
+        assert_eq!(out, [10.0, 11.0, 12.0, 10.0, 11.0]);
+
+        sampler.set_reset(1.0);
+        let out = render(&mut sampler, -1.0, 10.0, 13.0, 5);
+        assert_eq!(out, [12.0, 11.0, 10.0, 12.0, 11.0]);
+    }
+
+    #[test]
+    fn crossfade() {
+        let samples = ramp();
+        let looped = |rate: f64, start: f64, end: f64| {
+            let mut sampler = Sampler::new();
+            sampler.set_samples(1.0);
+            sampler.set_looped(1.0);
+            let mut out = vec![0.0; 20];
+            sampler.render(
+                rate.into(),
+                start.into(),
+                end.into(),
+                (4.0 / Rate::value()).into(),
+                samples[..].into(),
+                &mut out,
+            );
+            out
+        };
+
+        // the end of the loop is faded into the audio leading up to its start
+        let out = looped(1.0, 10.0, 20.0);
+        assert_eq!(out[..6], [10.0, 11.0, 12.0, 13.0, 14.0, 15.0]);
+        assert!(out[7..10].iter().all(|v| *v > 9.0 && *v < 19.0));
+        assert_eq!(out[10], 10.0);
+
+        // there's nothing before the start of the buffer to fade into
+        let out = looped(1.0, 0.0, 10.0);
+        let expected: Vec<_> = (0..20).map(|v| (v % 10) as f64).collect();
+        assert_eq!(out, expected);
+
+        // or after the end of it in reverse
+        let out = looped(-1.0, 90.0, 100.0);
+        let expected: Vec<_> = (0..20).map(|v| (99 - v % 10) as f64).collect();
+        assert_eq!(out, expected);
+    }
+
+    fn granulate(seed: f64, density: f64) -> Vec<f64> {
+        let samples = ramp();
+        let mut granular = Granular::new();
+        granular.set_seed(seed);
+        let mut out = vec![0.0; 4800];
+        granular.render(
+            0.5.into(),
+            0.01.into(),
+            density.into(),
+            1.0.into(),
+            0.001.into(),
+            0.5.into(),
+            1.0.into(),
+            samples[..].into(),
+            &mut out,
+        );
+        out
+    }
+
+    #[test]
+    fn grains() {
+        let out = granulate(1.0, 200.0);
+        assert!(out.iter().any(|v| *v > 0.0));
+        assert!(out.iter().all(|v| v.is_finite() && *v < 100.0));
+
+        // the same seed places the grains in the same spots
+        assert_eq!(out, granulate(1.0, 200.0));
+        assert_ne!(out, granulate(2.0, 200.0));
+
+        assert!(granulate(1.0, 0.0).iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn rng_zero_state() {
+        // this seed cancels out the mixing constant
+        let mut rng = Rng::new(f64::from_bits(0x9e37_79b9_7f4a_7c15));
+        assert_ne!(rng.0, 0);
+        assert_ne!(rng.signed(), rng.signed());
+    }
+}
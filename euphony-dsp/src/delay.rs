@@ -14,7 +14,7 @@ struct Buffer {
 impl Buffer {
     #[inline]
     fn write(&mut self, sample: Sample, delay: Sample, idx: usize) {
-        let sample_delay = (delay * Rate::VALUE).round() as usize;
+        let sample_delay = (delay * Rate::value()).round() as usize;
         let sample_delay = sample_delay + idx;
 
         if self.samples.len() <= sample_delay {
@@ -150,7 +150,7 @@ mod tests {
             before.render(&mut before_out);
             assert_eq!(before_out, [0.0; LEN]);
 
-            let delay = (LEN + offset) as f64 / Rate::VALUE;
+            let delay = (LEN + offset) as f64 / Rate::value();
             let mut output = [0.0; LEN];
             write.render((&signal).into(), delay.into(), &mut output);
             assert_eq!(output, [0.0; LEN]);
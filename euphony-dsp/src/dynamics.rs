@@ -0,0 +1,459 @@
+use crate::prelude::*;
+
+/// The quietest level the detectors track; reducing a signal this far silences it
+const MIN_DB: f64 = -120.0;
+
+#[inline]
+fn to_db(value: Sample) -> f64 {
+    let value = value.abs();
+    if value > 0.0 {
+        (20.0 * value.log10()).max(MIN_DB)
+    } else {
+        MIN_DB
+    }
+}
+
+#[inline]
+fn to_gain(db: f64) -> f64 {
+    if db <= MIN_DB {
+        0.0
+    } else {
+        10f64.powf(db / 20.0)
+    }
+}
+
+/// Returns the one-pole coefficient to settle over `time` seconds
+#[inline]
+fn coefficient(time: f64, sample_rate: f64) -> f64 {
+    if time > 0.0 {
+        (-1.0 / (time * sample_rate)).exp()
+    } else {
+        0.0
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Mode {
+    Compress,
+    Limit,
+    Expand,
+    Gate,
+}
+
+impl Mode {
+    /// Computes the gain reduction, in dB, for a level `over` the threshold
+    ///
+    /// The knee is centered on the threshold and blends the two slopes quadratically.
+    #[inline]
+    fn reduction(self, over: f64, ratio: f64, knee: f64) -> f64 {
+        let knee = knee.max(0.0);
+        let is_knee = knee > 0.0 && 2.0 * over.abs() <= knee;
+
+        let reduction = match self {
+            Self::Compress | Self::Limit => {
+                let slope = match self {
+                    Self::Compress => 1.0 - ratio.max(1.0).recip(),
+                    _ => 1.0,
+                };
+                if is_knee {
+                    -slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+                } else if over > 0.0 {
+                    -slope * over
+                } else {
+                    0.0
+                }
+            }
+            Self::Expand => {
+                let slope = ratio.max(1.0) - 1.0;
+                if is_knee {
+                    -slope * (over - knee / 2.0).powi(2) / (2.0 * knee)
+                } else if over < 0.0 {
+                    slope * over
+                } else {
+                    0.0
+                }
+            }
+            Self::Gate => {
+                if is_knee {
+                    let closed = 0.5 - over / knee;
+                    MIN_DB * closed * closed
+                } else if over < 0.0 {
+                    MIN_DB
+                } else {
+                    0.0
+                }
+            }
+        };
+
+        reduction.max(MIN_DB)
+    }
+
+    /// Returns `true` if moving to the `target` reduction responds to a rising level
+    #[inline]
+    fn is_attack(self, target: f64, current: f64) -> bool {
+        match self {
+            Self::Compress | Self::Limit => target < current,
+            Self::Expand | Self::Gate => target > current,
+        }
+    }
+}
+
+/// Smooths the gain reduction shared by all of the dynamics processors
+#[derive(Clone, Copy, Debug, Default)]
+struct Envelope {
+    reduction: f64,
+}
+
+impl Envelope {
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    fn render(
+        &mut self,
+        mode: Mode,
+        signal: Input,
+        sidechain: Input,
+        threshold: Input,
+        ratio: Input,
+        attack: Input,
+        release: Input,
+        knee: Input,
+        makeup: Input,
+        output: &mut [Sample],
+    ) {
+        let sample_rate = Rate::value();
+        // only listen to the sidechain when another node is driving it
+        let is_external = matches!(sidechain, Input::Buffer(_));
+
+        for (signal, sidechain, threshold, ratio, attack, release, knee, makeup, output) in (
+            signal,
+            sidechain,
+            threshold,
+            ratio,
+            attack,
+            release,
+            knee,
+            makeup,
+            output.iter_mut(),
+        )
+            .zip()
+        {
+            let key = if is_external { sidechain } else { signal };
+            let over = to_db(key) - threshold;
+            let target = mode.reduction(over, ratio, knee);
+
+            let time = if mode.is_attack(target, self.reduction) {
+                attack
+            } else {
+                release
+            };
+            let coefficient = coefficient(time, sample_rate);
+            self.reduction = target + (self.reduction - target) * coefficient;
+
+            *output = signal * to_gain(self.reduction) * to_gain(makeup);
+        }
+    }
+}
+
+#[derive(Debug, Default, Node)]
+#[node(id = 270, module = "dynamics")]
+#[input(signal, default = 0.0)]
+#[input(sidechain)]
+#[input(threshold, default = -18.0)]
+#[input(ratio, default = 4.0)]
+#[input(attack, default = 0.01)]
+#[input(release, default = 0.1)]
+#[input(knee, default = 6.0)]
+#[input(makeup)]
+/// Reduces the level of the signal above a threshold
+///
+/// `threshold`, `knee` and `makeup` are in dB, `ratio` is the input change for each dB of output
+/// change, and `attack` and `release` are in seconds. When `sidechain` is connected to another
+/// node, its level drives the compression instead of the signal's.
+pub struct Compressor {
+    envelope: Envelope,
+}
+
+impl Compressor {
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        signal: Input,
+        sidechain: Input,
+        threshold: Input,
+        ratio: Input,
+        attack: Input,
+        release: Input,
+        knee: Input,
+        makeup: Input,
+        output: &mut [Sample],
+    ) {
+        self.envelope.render(
+            Mode::Compress,
+            signal,
+            sidechain,
+            threshold,
+            ratio,
+            attack,
+            release,
+            knee,
+            makeup,
+            output,
+        );
+    }
+}
+
+#[derive(Debug, Default, Node)]
+#[node(id = 271, module = "dynamics")]
+#[input(signal, default = 0.0)]
+#[input(sidechain)]
+#[input(threshold, default = -1.0)]
+#[input(attack)]
+#[input(release, default = 0.05)]
+#[input(knee)]
+#[input(makeup)]
+/// Keeps the level of the signal under a threshold
+///
+/// With the default `attack` of zero and no `knee`, the signal never goes over `threshold` before
+/// `makeup` is applied. When `sidechain` is connected to another node, its level drives the
+/// limiting instead of the signal's.
+pub struct Limiter {
+    envelope: Envelope,
+}
+
+impl Limiter {
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        signal: Input,
+        sidechain: Input,
+        threshold: Input,
+        attack: Input,
+        release: Input,
+        knee: Input,
+        makeup: Input,
+        output: &mut [Sample],
+    ) {
+        self.envelope.render(
+            Mode::Limit,
+            signal,
+            sidechain,
+            threshold,
+            Input::Constant(1.0),
+            attack,
+            release,
+            knee,
+            makeup,
+            output,
+        );
+    }
+}
+
+#[derive(Debug, Default, Node)]
+#[node(id = 272, module = "dynamics")]
+#[input(signal, default = 0.0)]
+#[input(sidechain)]
+#[input(threshold, default = -40.0)]
+#[input(attack, default = 0.001)]
+#[input(release, default = 0.05)]
+#[input(knee)]
+#[input(makeup)]
+/// Silences the signal below a threshold
+///
+/// `attack` is how long the gate takes to open and `release` is how long it takes to close. When
+/// `sidechain` is connected to another node, its level opens the gate instead of the signal's.
+pub struct Gate {
+    envelope: Envelope,
+}
+
+impl Gate {
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        signal: Input,
+        sidechain: Input,
+        threshold: Input,
+        attack: Input,
+        release: Input,
+        knee: Input,
+        makeup: Input,
+        output: &mut [Sample],
+    ) {
+        self.envelope.render(
+            Mode::Gate,
+            signal,
+            sidechain,
+            threshold,
+            Input::Constant(1.0),
+            attack,
+            release,
+            knee,
+            makeup,
+            output,
+        );
+    }
+}
+
+#[derive(Debug, Default, Node)]
+#[node(id = 273, module = "dynamics")]
+#[input(signal, default = 0.0)]
+#[input(sidechain)]
+#[input(threshold, default = -40.0)]
+#[input(ratio, default = 2.0)]
+#[input(attack, default = 0.001)]
+#[input(release, default = 0.05)]
+#[input(knee, default = 6.0)]
+#[input(makeup)]
+/// Reduces the level of the signal below a threshold
+///
+/// Each dB the level falls under `threshold` lowers the output by `ratio` dB. When `sidechain` is
+/// connected to another node, its level drives the expansion instead of the signal's.
+pub struct Expander {
+    envelope: Envelope,
+}
+
+impl Expander {
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        signal: Input,
+        sidechain: Input,
+        threshold: Input,
+        ratio: Input,
+        attack: Input,
+        release: Input,
+        knee: Input,
+        makeup: Input,
+        output: &mut [Sample],
+    ) {
+        self.envelope.render(
+            Mode::Expand,
+            signal,
+            sidechain,
+            threshold,
+            ratio,
+            attack,
+            release,
+            knee,
+            makeup,
+            output,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euphony_node::LEN;
+
+    fn level(output: &[Sample]) -> f64 {
+        to_db(output.iter().fold(0.0f64, |peak, s| peak.max(s.abs())))
+    }
+
+    #[test]
+    fn curves_test() {
+        // a hard knee compressor at 4:1 turns 8dB over into 2dB over
+        assert_eq!(Mode::Compress.reduction(8.0, 4.0, 0.0), -6.0);
+        assert_eq!(Mode::Compress.reduction(-8.0, 4.0, 0.0), 0.0);
+        assert_eq!(Mode::Limit.reduction(3.0, 1.0, 0.0), -3.0);
+        assert_eq!(Mode::Expand.reduction(-10.0, 2.0, 0.0), -10.0);
+        assert_eq!(Mode::Expand.reduction(10.0, 2.0, 0.0), 0.0);
+        assert_eq!(Mode::Gate.reduction(-1.0, 1.0, 0.0), MIN_DB);
+
+        // the knees meet the slopes at their edges
+        for mode in [Mode::Compress, Mode::Limit, Mode::Expand, Mode::Gate] {
+            for edge in [-3.0, 3.0] {
+                let inside = mode.reduction(edge, 4.0, 6.0);
+                let outside = mode.reduction(edge, 4.0, 0.0);
+                assert!((inside - outside).abs() < 1e-9, "{mode:?} {edge}");
+            }
+        }
+    }
+
+    #[test]
+    fn limiter_test() {
+        let signal: Vec<Sample> = (0..LEN).map(|idx| (idx as f64 * 0.1).sin()).collect();
+        let signal: [Sample; LEN] = signal.try_into().unwrap();
+
+        let mut limiter = Limiter::default();
+        let mut output = [0.0; LEN];
+        limiter.render(
+            (&signal).into(),
+            0.0.into(),
+            (-6.0).into(),
+            0.0.into(),
+            0.05.into(),
+            0.0.into(),
+            0.0.into(),
+            &mut output,
+        );
+
+        assert!(level(&output) <= -6.0 + 1e-9);
+    }
+
+    #[test]
+    fn sidechain_test() {
+        let signal = [0.5; LEN];
+        let mut kick = [0.0; LEN];
+        kick[..LEN / 2].fill(1.0);
+
+        let mut compressor = Compressor::default();
+        let mut output = [0.0; LEN];
+        compressor.render(
+            (&signal).into(),
+            (&kick).into(),
+            (-20.0).into(),
+            10.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            &mut output,
+        );
+
+        // the signal ducks while the kick is playing and recovers when it stops
+        let ducked = to_gain(-20.0 * (1.0 - 10f64.recip()));
+        assert!((output[0] - 0.5 * ducked).abs() < 1e-9);
+        assert_eq!(output[LEN - 1], 0.5);
+
+        // without a sidechain, the signal drives its own detector
+        let mut compressor = Compressor::default();
+        compressor.render(
+            (&signal).into(),
+            0.0.into(),
+            0.0.into(),
+            10.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            &mut output,
+        );
+        assert_eq!(output, signal);
+    }
+
+    #[test]
+    fn gate_test() {
+        let mut signal = [0.001; LEN];
+        signal[LEN / 2..].fill(0.5);
+
+        let mut gate = Gate::default();
+        let mut output = [0.0; LEN];
+        gate.render(
+            (&signal).into(),
+            0.0.into(),
+            (-40.0).into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            &mut output,
+        );
+
+        assert!(output[..LEN / 2].iter().all(|s| *s == 0.0));
+        assert_eq!(&output[LEN / 2..], &signal[LEN / 2..]);
+    }
+}
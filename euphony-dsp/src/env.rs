@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use euphony_node::{Error, ParameterValue};
 
 #[derive(Debug, Node)]
 #[node(id = 200, module = "env")]
@@ -55,7 +56,7 @@ impl Linear {
     #[inline]
     fn update(&mut self) {
         let diff = self.target - self.value;
-        let samples = (self.duration * Rate::VALUE).round();
+        let samples = (self.duration * Rate::value()).round();
         self.samples = samples as _;
         if self.samples > 0 {
             self.step = diff / samples;
@@ -126,6 +127,241 @@ impl Linear {
     }
 }
 
+#[derive(Debug, Default, Node)]
+#[node(id = 201, module = "env", validate = "validate")]
+#[input(start)]
+#[input(attack, default = 0.01)]
+#[input(peak, default = 1.0)]
+#[input(decay, default = 0.1)]
+#[input(sustain, default = 0.5)]
+#[input(release, default = 0.1)]
+#[input(end)]
+#[input(attack_curve)]
+#[input(decay_curve)]
+#[input(release_curve)]
+#[input(shape, trigger = set_shape)]
+#[input(gate, trigger = set_gate)]
+#[input(retrigger, trigger = set_retrigger)]
+/// Attack, decay, sustain and release envelope
+///
+/// Opening the `gate` ramps from `start` to `peak` over `attack` seconds, then to `sustain` over
+/// `decay` seconds, where it stays until the gate is closed. Closing the gate ramps to `end` over
+/// `release` seconds. Opening or closing the gate again ramps from the current value, while
+/// `retrigger` restarts the attack from `start`. Negative stage lengths are rejected.
+///
+/// Each stage's curve bends the ramp: 0 is linear, positive values start slowly and negative
+/// values start quickly. `shape` selects the exponential (0) or power (1) family of curves.
+pub struct Adsr {
+    value: f64,
+    stage: Stage,
+    segment: Segment,
+    shape: Shape,
+    pending: Option<Stage>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Stage {
+    #[default]
+    Idle,
+    Attack,
+    Retrigger,
+    Decay,
+    Sustain,
+    Release,
+    Done,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Shape {
+    #[default]
+    Exponential,
+    Power,
+}
+
+impl Shape {
+    #[inline]
+    fn apply(self, t: f64, curve: f64) -> f64 {
+        match self {
+            Self::Exponential => {
+                if curve.abs() < 1e-3 {
+                    return t;
+                }
+                (1.0 - (curve * t).exp()) / (1.0 - curve.exp())
+            }
+            Self::Power => {
+                if curve >= 0.0 {
+                    t.powf(1.0 + curve)
+                } else {
+                    1.0 - (1.0 - t).powf(1.0 - curve)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Segment {
+    from: f64,
+    to: f64,
+    curve: f64,
+    position: usize,
+    len: usize,
+}
+
+impl Segment {
+    #[inline]
+    fn new(from: f64, to: f64, duration: f64, curve: f64) -> Self {
+        Self {
+            from,
+            to,
+            curve,
+            position: 0,
+            len: (duration * Rate::value()).round().max(0.0) as usize,
+        }
+    }
+
+    /// Returns the next value or `None` once the target is reached
+    #[inline]
+    fn next(&mut self, shape: Shape) -> Option<f64> {
+        if self.position >= self.len {
+            return None;
+        }
+
+        let t = self.position as f64 / self.len as f64;
+        self.position += 1;
+        Some(self.from + (self.to - self.from) * shape.apply(t, self.curve))
+    }
+}
+
+impl Adsr {
+    /// Rejects stage lengths that can't be played
+    pub fn validate(param: u64, value: ParameterValue) -> Result<(), Error> {
+        // attack, decay and release
+        if let (1 | 3 | 5, ParameterValue::Constant(value)) = (param, value) {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(format!(
+                    "stage lengths must be at least 0 seconds, got {value}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn set_shape(&mut self, value: f64) {
+        self.shape = if value >= 1.0 {
+            Shape::Power
+        } else {
+            Shape::Exponential
+        };
+    }
+
+    fn set_gate(&mut self, value: f64) {
+        if value >= 1.0 {
+            self.pending = Some(Stage::Attack);
+        } else if self.stage != Stage::Idle || self.pending.is_some() {
+            // the gate is initialized as closed so only release once it's been opened
+            self.pending = Some(Stage::Release);
+        }
+    }
+
+    fn set_retrigger(&mut self, value: f64) {
+        if value >= 1.0 {
+            self.pending = Some(Stage::Retrigger);
+        }
+    }
+
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        start: Input,
+        attack: Input,
+        peak: Input,
+        decay: Input,
+        sustain: Input,
+        release: Input,
+        end: Input,
+        attack_curve: Input,
+        decay_curve: Input,
+        release_curve: Input,
+        output: &mut [Sample],
+    ) {
+        for (
+            start,
+            attack,
+            peak,
+            decay,
+            sustain,
+            release,
+            end,
+            attack_curve,
+            decay_curve,
+            release_curve,
+            output,
+        ) in (
+            start,
+            attack,
+            peak,
+            decay,
+            sustain,
+            release,
+            end,
+            attack_curve,
+            decay_curve,
+            release_curve,
+            output.iter_mut(),
+        )
+            .zip()
+        {
+            if let Some(stage) = self.pending.take() {
+                // the first attack starts from `start` rather than the initial value
+                let from = if self.stage == Stage::Idle {
+                    start
+                } else {
+                    self.value
+                };
+                self.stage = stage;
+                self.segment = match stage {
+                    Stage::Attack => Segment::new(from, peak, attack, attack_curve),
+                    Stage::Retrigger => {
+                        self.stage = Stage::Attack;
+                        Segment::new(start, peak, attack, attack_curve)
+                    }
+                    Stage::Release => Segment::new(from, end, release, release_curve),
+                    _ => self.segment,
+                };
+            }
+
+            // move through the stages that have finished, including any with zero length
+            loop {
+                let value = match self.stage {
+                    Stage::Idle => Some(start),
+                    Stage::Sustain => Some(sustain),
+                    Stage::Done => Some(end),
+                    _ => self.segment.next(self.shape),
+                };
+
+                if let Some(value) = value {
+                    self.value = value;
+                    break;
+                }
+
+                self.value = self.segment.to;
+                self.stage = match self.stage {
+                    Stage::Attack | Stage::Retrigger => {
+                        self.segment = Segment::new(self.value, sustain, decay, decay_curve);
+                        Stage::Decay
+                    }
+                    Stage::Decay => Stage::Sustain,
+                    _ => Stage::Done,
+                };
+            }
+
+            *output = self.value;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +380,86 @@ mod tests {
         assert_eq!(out[0], 1.0);
         assert_eq!(out[511], 0.0);
     }
+
+    fn render_adsr(env: &mut Adsr, sustain: f64, out: &mut [f64]) {
+        env.render(
+            0.0.into(),
+            0.001.into(),
+            1.0.into(),
+            0.001.into(),
+            sustain.into(),
+            0.001.into(),
+            0.0.into(),
+            0.0.into(),
+            2.0.into(),
+            (-2.0).into(),
+            out,
+        );
+    }
+
+    #[test]
+    fn adsr_test() {
+        let mut env = Adsr::new();
+        let mut out = [0.0; 256];
+
+        render_adsr(&mut env, 0.5, &mut out);
+        assert!(out.iter().all(|v| *v == 0.0));
+
+        env.set_gate(1.0);
+        render_adsr(&mut env, 0.5, &mut out);
+        assert_eq!(out[0], 0.0);
+        // 1ms of attack at 48kHz
+        assert_eq!(out[48], 1.0);
+        // the decay curve starts slowly
+        assert!(out[60] > 0.9);
+        assert_eq!(out[255], 0.5);
+
+        env.set_gate(0.0);
+        render_adsr(&mut env, 0.5, &mut out);
+        assert_eq!(out[0], 0.5);
+        // the release curve starts quickly
+        assert!(out[24] < 0.15);
+        assert_eq!(out[255], 0.0);
+
+        env.set_retrigger(1.0);
+        env.set_shape(1.0);
+        render_adsr(&mut env, 0.25, &mut out);
+        assert!(out[12] > 0.0 && out[12] < 1.0);
+        assert_eq!(out[255], 0.25);
+    }
+
+    #[test]
+    fn adsr_start_test() {
+        // a falling ramp from 1 to 0, like `envgen!([1, 0], [...])`
+        let mut env = Adsr::new();
+        env.set_gate(1.0);
+        let mut out = [0.0; 256];
+        env.render(
+            1.0.into(),
+            0.001.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            0.0.into(),
+            &mut out,
+        );
+        assert_eq!(out[0], 1.0);
+        assert!(out[24] > 0.0 && out[24] < 1.0);
+        assert_eq!(out[48], 0.0);
+        assert_eq!(out[255], 0.0);
+    }
+
+    #[test]
+    fn adsr_validate_test() {
+        assert!(Adsr::validate(1, ParameterValue::Constant(0.5)).is_ok());
+        assert!(Adsr::validate(5, ParameterValue::Constant(-1.0)).is_err());
+        assert!(Adsr::validate(3, ParameterValue::Constant(f64::NAN)).is_err());
+        assert!(Adsr::validate(3, ParameterValue::Node(1)).is_ok());
+        // the levels can be negative
+        assert!(Adsr::validate(2, ParameterValue::Constant(-1.0)).is_ok());
+    }
 }
@@ -4,6 +4,6 @@ pub use hacker::*;
 
 #[inline]
 pub fn an<T: AudioNode>(mut an: An<T>) -> T {
-    an.set_sample_rate(Rate::VALUE);
+    an.set_sample_rate(Rate::value());
     an.0
 }
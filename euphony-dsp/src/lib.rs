@@ -27,10 +27,12 @@ pub mod sample;
 mod binary;
 mod buffer;
 mod delay;
+mod dynamics;
 mod env;
 mod filter;
 mod noise;
 mod osc;
+mod reverb;
 mod tertiary;
 mod unary;
 
@@ -85,7 +85,15 @@ pub fn load(processor: u64) -> Option<BoxProcessor> {
         151 => Some(crate::noise::Perlin::spawn()),
         152 => Some(crate::noise::OpenSimplex::spawn()),
         200 => Some(crate::env::Linear::spawn()),
+        201 => Some(crate::env::Adsr::spawn()),
         250 => Some(crate::delay::Bus::spawn()),
+        260 => Some(crate::reverb::Freeverb::spawn()),
+        261 => Some(crate::reverb::Fdn::spawn()),
+        262 => Some(crate::reverb::Convolution::spawn()),
+        270 => Some(crate::dynamics::Compressor::spawn()),
+        271 => Some(crate::dynamics::Limiter::spawn()),
+        272 => Some(crate::dynamics::Gate::spawn()),
+        273 => Some(crate::dynamics::Expander::spawn()),
         300 => Some(crate::filter::Butterpass::spawn()),
         301 => Some(crate::filter::Lowpole::spawn()),
         302 => Some(crate::filter::Allpole::spawn()),
@@ -105,6 +113,8 @@ pub fn load(processor: u64) -> Option<BoxProcessor> {
         316 => Some(crate::filter::Highshelf::spawn()),
         317 => Some(crate::osc::wave::Shaper::spawn()),
         500 => Some(crate::buffer::Play::spawn()),
+        501 => Some(crate::buffer::Sampler::spawn()),
+        502 => Some(crate::buffer::Granular::spawn()),
         _ => None,
     }
 }
@@ -193,7 +203,15 @@ pub fn name(processor: u64) -> Option<&'static str> {
         151 => Some("Perlin"),
         152 => Some("OpenSimplex"),
         200 => Some("Linear"),
+        201 => Some("Adsr"),
         250 => Some("Bus"),
+        260 => Some("Freeverb"),
+        261 => Some("Fdn"),
+        262 => Some("Convolution"),
+        270 => Some("Compressor"),
+        271 => Some("Limiter"),
+        272 => Some("Gate"),
+        273 => Some("Expander"),
         300 => Some("Butterpass"),
         301 => Some("Lowpole"),
         302 => Some("Allpole"),
@@ -213,6 +231,514 @@ pub fn name(processor: u64) -> Option<&'static str> {
         316 => Some("Highshelf"),
         317 => Some("Shaper"),
         500 => Some("Play"),
+        501 => Some("Sampler"),
+        502 => Some("Granular"),
+        _ => None,
+    }
+}
+
+#[rustfmt::skip]
+#[inline]
+pub fn path(processor: u64) -> Option<&'static str> {
+    match processor {
+        1 => Some("unary::Abs"),
+        2 => Some("unary::Acos"),
+        3 => Some("unary::Acosh"),
+        4 => Some("unary::Asin"),
+        5 => Some("unary::Asinh"),
+        6 => Some("unary::Atan"),
+        7 => Some("unary::Atanh"),
+        8 => Some("unary::Cbrt"),
+        9 => Some("unary::Ceil"),
+        10 => Some("unary::Cos"),
+        11 => Some("unary::Cosh"),
+        12 => Some("unary::Exp"),
+        13 => Some("unary::Exp2"),
+        14 => Some("unary::ExpM1"),
+        15 => Some("unary::Floor"),
+        16 => Some("unary::Fract"),
+        17 => Some("unary::Ln"),
+        18 => Some("unary::Ln1p"),
+        19 => Some("unary::Log10"),
+        20 => Some("unary::Log2"),
+        21 => Some("unary::Norm"),
+        22 => Some("unary::Recip"),
+        23 => Some("unary::Round"),
+        24 => Some("unary::Signum"),
+        25 => Some("unary::Sin"),
+        26 => Some("unary::Sinh"),
+        27 => Some("unary::Sqrt"),
+        28 => Some("unary::Tan"),
+        29 => Some("unary::Tanh"),
+        30 => Some("unary::ToDegrees"),
+        31 => Some("unary::ToRadians"),
+        32 => Some("unary::Trunc"),
+        33 => Some("unary::Neg"),
+        34 => Some("unary::Pass"),
+        50 => Some("binary::Add"),
+        51 => Some("binary::Atan2"),
+        52 => Some("binary::Copysign"),
+        53 => Some("binary::Div"),
+        54 => Some("binary::DivEuclid"),
+        55 => Some("binary::Hypot"),
+        56 => Some("binary::Log"),
+        57 => Some("binary::Max"),
+        58 => Some("binary::Min"),
+        59 => Some("binary::Mul"),
+        60 => Some("binary::Powf"),
+        61 => Some("binary::Powi"),
+        62 => Some("binary::Rem"),
+        63 => Some("binary::RemEuclid"),
+        64 => Some("binary::Sub"),
+        65 => Some("binary::Gt"),
+        66 => Some("binary::Gte"),
+        67 => Some("binary::Lt"),
+        68 => Some("binary::Lte"),
+        69 => Some("binary::Eq"),
+        70 => Some("binary::Ne"),
+        75 => Some("tertiary::AddMul"),
+        76 => Some("tertiary::Clamp"),
+        77 => Some("tertiary::MulAdd"),
+        78 => Some("tertiary::Select"),
+        100 => Some("osc::Sine"),
+        101 => Some("osc::SineFast"),
+        102 => Some("osc::SineFaster"),
+        103 => Some("osc::Pulse"),
+        104 => Some("osc::Sawtooth"),
+        105 => Some("osc::Triangle"),
+        106 => Some("osc::Silence"),
+        107 => Some("osc::Phase"),
+        108 => Some("osc::nes::Pulse"),
+        109 => Some("osc::nes::Triangle"),
+        110 => Some("osc::noise::White"),
+        111 => Some("osc::noise::Mls"),
+        112 => Some("osc::noise::Pink"),
+        113 => Some("osc::noise::Brown"),
+        114 => Some("osc::Wave"),
+        115 => Some("osc::Morph"),
+        116 => Some("osc::Impulse"),
+        150 => Some("noise::Simplex"),
+        151 => Some("noise::Perlin"),
+        152 => Some("noise::OpenSimplex"),
+        200 => Some("env::Linear"),
+        201 => Some("env::Adsr"),
+        250 => Some("delay::Bus"),
+        260 => Some("reverb::Freeverb"),
+        261 => Some("reverb::Fdn"),
+        262 => Some("reverb::Convolution"),
+        270 => Some("dynamics::Compressor"),
+        271 => Some("dynamics::Limiter"),
+        272 => Some("dynamics::Gate"),
+        273 => Some("dynamics::Expander"),
+        300 => Some("filter::Butterpass"),
+        301 => Some("filter::Lowpole"),
+        302 => Some("filter::Allpole"),
+        303 => Some("filter::Highpole"),
+        304 => Some("filter::Resonator"),
+        305 => Some("filter::Moog"),
+        306 => Some("filter::Morph"),
+        307 => Some("filter::Pinkpass"),
+        308 => Some("filter::Lowpass"),
+        309 => Some("filter::Highpass"),
+        310 => Some("filter::Bandpass"),
+        311 => Some("filter::Notch"),
+        312 => Some("filter::Peak"),
+        313 => Some("filter::Allpass"),
+        314 => Some("filter::Bell"),
+        315 => Some("filter::Lowshelf"),
+        316 => Some("filter::Highshelf"),
+        317 => Some("filter::Shaper"),
+        500 => Some("buffer::Play"),
+        501 => Some("buffer::Sampler"),
+        502 => Some("buffer::Granular"),
+        _ => None,
+    }
+}
+
+#[rustfmt::skip]
+#[inline]
+pub fn id(path: &str) -> Option<u64> {
+    match path {
+        "unary::Abs" => Some(1),
+        "unary::Acos" => Some(2),
+        "unary::Acosh" => Some(3),
+        "unary::Asin" => Some(4),
+        "unary::Asinh" => Some(5),
+        "unary::Atan" => Some(6),
+        "unary::Atanh" => Some(7),
+        "unary::Cbrt" => Some(8),
+        "unary::Ceil" => Some(9),
+        "unary::Cos" => Some(10),
+        "unary::Cosh" => Some(11),
+        "unary::Exp" => Some(12),
+        "unary::Exp2" => Some(13),
+        "unary::ExpM1" => Some(14),
+        "unary::Floor" => Some(15),
+        "unary::Fract" => Some(16),
+        "unary::Ln" => Some(17),
+        "unary::Ln1p" => Some(18),
+        "unary::Log10" => Some(19),
+        "unary::Log2" => Some(20),
+        "unary::Norm" => Some(21),
+        "unary::Recip" => Some(22),
+        "unary::Round" => Some(23),
+        "unary::Signum" => Some(24),
+        "unary::Sin" => Some(25),
+        "unary::Sinh" => Some(26),
+        "unary::Sqrt" => Some(27),
+        "unary::Tan" => Some(28),
+        "unary::Tanh" => Some(29),
+        "unary::ToDegrees" => Some(30),
+        "unary::ToRadians" => Some(31),
+        "unary::Trunc" => Some(32),
+        "unary::Neg" => Some(33),
+        "unary::Pass" => Some(34),
+        "binary::Add" => Some(50),
+        "binary::Atan2" => Some(51),
+        "binary::Copysign" => Some(52),
+        "binary::Div" => Some(53),
+        "binary::DivEuclid" => Some(54),
+        "binary::Hypot" => Some(55),
+        "binary::Log" => Some(56),
+        "binary::Max" => Some(57),
+        "binary::Min" => Some(58),
+        "binary::Mul" => Some(59),
+        "binary::Powf" => Some(60),
+        "binary::Powi" => Some(61),
+        "binary::Rem" => Some(62),
+        "binary::RemEuclid" => Some(63),
+        "binary::Sub" => Some(64),
+        "binary::Gt" => Some(65),
+        "binary::Gte" => Some(66),
+        "binary::Lt" => Some(67),
+        "binary::Lte" => Some(68),
+        "binary::Eq" => Some(69),
+        "binary::Ne" => Some(70),
+        "tertiary::AddMul" => Some(75),
+        "tertiary::Clamp" => Some(76),
+        "tertiary::MulAdd" => Some(77),
+        "tertiary::Select" => Some(78),
+        "osc::Sine" => Some(100),
+        "osc::SineFast" => Some(101),
+        "osc::SineFaster" => Some(102),
+        "osc::Pulse" => Some(103),
+        "osc::Sawtooth" => Some(104),
+        "osc::Triangle" => Some(105),
+        "osc::Silence" => Some(106),
+        "osc::Phase" => Some(107),
+        "osc::nes::Pulse" => Some(108),
+        "osc::nes::Triangle" => Some(109),
+        "osc::noise::White" => Some(110),
+        "osc::noise::Mls" => Some(111),
+        "osc::noise::Pink" => Some(112),
+        "osc::noise::Brown" => Some(113),
+        "osc::Wave" => Some(114),
+        "osc::Morph" => Some(115),
+        "osc::Impulse" => Some(116),
+        "noise::Simplex" => Some(150),
+        "noise::Perlin" => Some(151),
+        "noise::OpenSimplex" => Some(152),
+        "env::Linear" => Some(200),
+        "env::Adsr" => Some(201),
+        "delay::Bus" => Some(250),
+        "reverb::Freeverb" => Some(260),
+        "reverb::Fdn" => Some(261),
+        "reverb::Convolution" => Some(262),
+        "dynamics::Compressor" => Some(270),
+        "dynamics::Limiter" => Some(271),
+        "dynamics::Gate" => Some(272),
+        "dynamics::Expander" => Some(273),
+        "filter::Butterpass" => Some(300),
+        "filter::Lowpole" => Some(301),
+        "filter::Allpole" => Some(302),
+        "filter::Highpole" => Some(303),
+        "filter::Resonator" => Some(304),
+        "filter::Moog" => Some(305),
+        "filter::Morph" => Some(306),
+        "filter::Pinkpass" => Some(307),
+        "filter::Lowpass" => Some(308),
+        "filter::Highpass" => Some(309),
+        "filter::Bandpass" => Some(310),
+        "filter::Notch" => Some(311),
+        "filter::Peak" => Some(312),
+        "filter::Allpass" => Some(313),
+        "filter::Bell" => Some(314),
+        "filter::Lowshelf" => Some(315),
+        "filter::Highshelf" => Some(316),
+        "filter::Shaper" => Some(317),
+        "buffer::Play" => Some(500),
+        "buffer::Sampler" => Some(501),
+        "buffer::Granular" => Some(502),
+        _ => None,
+    }
+}
+
+#[rustfmt::skip]
+#[inline]
+pub fn parameter_name(processor: u64, parameter: u64) -> Option<&'static str> {
+    match (processor, parameter) {
+        (1, 0) => Some("input"),
+        (2, 0) => Some("input"),
+        (3, 0) => Some("input"),
+        (4, 0) => Some("input"),
+        (5, 0) => Some("input"),
+        (6, 0) => Some("input"),
+        (7, 0) => Some("input"),
+        (8, 0) => Some("input"),
+        (9, 0) => Some("input"),
+        (10, 0) => Some("input"),
+        (11, 0) => Some("input"),
+        (12, 0) => Some("input"),
+        (13, 0) => Some("input"),
+        (14, 0) => Some("input"),
+        (15, 0) => Some("input"),
+        (16, 0) => Some("input"),
+        (17, 0) => Some("input"),
+        (18, 0) => Some("input"),
+        (19, 0) => Some("input"),
+        (20, 0) => Some("input"),
+        (21, 0) => Some("input"),
+        (22, 0) => Some("input"),
+        (23, 0) => Some("input"),
+        (24, 0) => Some("input"),
+        (25, 0) => Some("input"),
+        (26, 0) => Some("input"),
+        (27, 0) => Some("input"),
+        (28, 0) => Some("input"),
+        (29, 0) => Some("input"),
+        (30, 0) => Some("input"),
+        (31, 0) => Some("input"),
+        (32, 0) => Some("input"),
+        (33, 0) => Some("input"),
+        (34, 0) => Some("input"),
+        (50, 0) => Some("rhs"),
+        (50, 1) => Some("lhs"),
+        (51, 0) => Some("rhs"),
+        (51, 1) => Some("lhs"),
+        (52, 0) => Some("rhs"),
+        (52, 1) => Some("lhs"),
+        (53, 0) => Some("rhs"),
+        (53, 1) => Some("lhs"),
+        (54, 0) => Some("rhs"),
+        (54, 1) => Some("lhs"),
+        (55, 0) => Some("rhs"),
+        (55, 1) => Some("lhs"),
+        (56, 0) => Some("rhs"),
+        (56, 1) => Some("lhs"),
+        (57, 0) => Some("rhs"),
+        (57, 1) => Some("lhs"),
+        (58, 0) => Some("rhs"),
+        (58, 1) => Some("lhs"),
+        (59, 0) => Some("rhs"),
+        (59, 1) => Some("lhs"),
+        (60, 0) => Some("rhs"),
+        (60, 1) => Some("lhs"),
+        (61, 0) => Some("rhs"),
+        (61, 1) => Some("lhs"),
+        (62, 0) => Some("rhs"),
+        (62, 1) => Some("lhs"),
+        (63, 0) => Some("rhs"),
+        (63, 1) => Some("lhs"),
+        (64, 0) => Some("rhs"),
+        (64, 1) => Some("lhs"),
+        (65, 0) => Some("rhs"),
+        (65, 1) => Some("lhs"),
+        (66, 0) => Some("rhs"),
+        (66, 1) => Some("lhs"),
+        (67, 0) => Some("rhs"),
+        (67, 1) => Some("lhs"),
+        (68, 0) => Some("rhs"),
+        (68, 1) => Some("lhs"),
+        (69, 0) => Some("rhs"),
+        (69, 1) => Some("lhs"),
+        (70, 0) => Some("rhs"),
+        (70, 1) => Some("lhs"),
+        (75, 0) => Some("input"),
+        (75, 1) => Some("add"),
+        (75, 2) => Some("mul"),
+        (76, 0) => Some("input"),
+        (76, 1) => Some("min"),
+        (76, 2) => Some("max"),
+        (77, 0) => Some("input"),
+        (77, 1) => Some("mul"),
+        (77, 2) => Some("add"),
+        (78, 0) => Some("cond"),
+        (78, 1) => Some("a"),
+        (78, 2) => Some("b"),
+        (100, 0) => Some("frequency"),
+        (100, 1) => Some("phase"),
+        (101, 0) => Some("frequency"),
+        (101, 1) => Some("phase"),
+        (102, 0) => Some("frequency"),
+        (102, 1) => Some("phase"),
+        (103, 0) => Some("frequency"),
+        (103, 1) => Some("phase"),
+        (104, 0) => Some("frequency"),
+        (104, 1) => Some("phase"),
+        (105, 0) => Some("frequency"),
+        (105, 1) => Some("phase"),
+        (107, 0) => Some("frequency"),
+        (107, 1) => Some("phase"),
+        (108, 0) => Some("frequency"),
+        (108, 1) => Some("duty_cycle"),
+        (108, 2) => Some("decay"),
+        (108, 3) => Some("phase"),
+        (109, 0) => Some("frequency"),
+        (109, 1) => Some("phase"),
+        (110, 0) => Some("seed"),
+        (111, 0) => Some("seed"),
+        (111, 1) => Some("length"),
+        (112, 0) => Some("seed"),
+        (113, 0) => Some("seed"),
+        (114, 0) => Some("frequency"),
+        (114, 1) => Some("phase"),
+        (115, 0) => Some("frequency"),
+        (115, 1) => Some("position"),
+        (115, 2) => Some("phase"),
+        (116, 0) => Some("frequency"),
+        (116, 1) => Some("phase"),
+        (150, 0) => Some("x"),
+        (150, 1) => Some("y"),
+        (150, 2) => Some("z"),
+        (150, 3) => Some("w"),
+        (150, 4) => Some("seed"),
+        (151, 0) => Some("x"),
+        (151, 1) => Some("y"),
+        (151, 2) => Some("z"),
+        (151, 3) => Some("w"),
+        (151, 4) => Some("seed"),
+        (152, 0) => Some("x"),
+        (152, 1) => Some("y"),
+        (152, 2) => Some("z"),
+        (152, 3) => Some("w"),
+        (152, 4) => Some("seed"),
+        (200, 0) => Some("target"),
+        (200, 1) => Some("duration"),
+        (200, 2) => Some("value"),
+        (201, 0) => Some("start"),
+        (201, 1) => Some("attack"),
+        (201, 2) => Some("peak"),
+        (201, 3) => Some("decay"),
+        (201, 4) => Some("sustain"),
+        (201, 5) => Some("release"),
+        (201, 6) => Some("end"),
+        (201, 7) => Some("attack_curve"),
+        (201, 8) => Some("decay_curve"),
+        (201, 9) => Some("release_curve"),
+        (201, 10) => Some("shape"),
+        (201, 11) => Some("gate"),
+        (201, 12) => Some("retrigger"),
+        (250, 0) => Some("signal"),
+        (250, 1) => Some("delay"),
+        (260, 0) => Some("signal"),
+        (260, 1) => Some("size"),
+        (260, 2) => Some("damping"),
+        (260, 3) => Some("predelay"),
+        (260, 4) => Some("mix"),
+        (261, 0) => Some("signal"),
+        (261, 1) => Some("size"),
+        (261, 2) => Some("damping"),
+        (261, 3) => Some("predelay"),
+        (261, 4) => Some("mix"),
+        (262, 0) => Some("signal"),
+        (262, 1) => Some("mix"),
+        (270, 0) => Some("signal"),
+        (270, 1) => Some("sidechain"),
+        (270, 2) => Some("threshold"),
+        (270, 3) => Some("ratio"),
+        (270, 4) => Some("attack"),
+        (270, 5) => Some("release"),
+        (270, 6) => Some("knee"),
+        (270, 7) => Some("makeup"),
+        (271, 0) => Some("signal"),
+        (271, 1) => Some("sidechain"),
+        (271, 2) => Some("threshold"),
+        (271, 3) => Some("attack"),
+        (271, 4) => Some("release"),
+        (271, 5) => Some("knee"),
+        (271, 6) => Some("makeup"),
+        (272, 0) => Some("signal"),
+        (272, 1) => Some("sidechain"),
+        (272, 2) => Some("threshold"),
+        (272, 3) => Some("attack"),
+        (272, 4) => Some("release"),
+        (272, 5) => Some("knee"),
+        (272, 6) => Some("makeup"),
+        (273, 0) => Some("signal"),
+        (273, 1) => Some("sidechain"),
+        (273, 2) => Some("threshold"),
+        (273, 3) => Some("ratio"),
+        (273, 4) => Some("attack"),
+        (273, 5) => Some("release"),
+        (273, 6) => Some("knee"),
+        (273, 7) => Some("makeup"),
+        (300, 0) => Some("signal"),
+        (300, 1) => Some("cutoff"),
+        (301, 0) => Some("signal"),
+        (301, 1) => Some("cutoff"),
+        (302, 0) => Some("signal"),
+        (302, 1) => Some("delay"),
+        (303, 0) => Some("signal"),
+        (303, 1) => Some("cutoff"),
+        (304, 0) => Some("signal"),
+        (304, 1) => Some("cutoff"),
+        (304, 2) => Some("bandwidth"),
+        (305, 0) => Some("signal"),
+        (305, 1) => Some("cutoff"),
+        (305, 2) => Some("q"),
+        (306, 0) => Some("signal"),
+        (306, 1) => Some("center"),
+        (306, 2) => Some("q"),
+        (306, 3) => Some("morph"),
+        (307, 0) => Some("signal"),
+        (308, 0) => Some("signal"),
+        (308, 1) => Some("cutoff"),
+        (308, 2) => Some("q"),
+        (309, 0) => Some("signal"),
+        (309, 1) => Some("cutoff"),
+        (309, 2) => Some("q"),
+        (310, 0) => Some("signal"),
+        (310, 1) => Some("cutoff"),
+        (310, 2) => Some("q"),
+        (311, 0) => Some("signal"),
+        (311, 1) => Some("center"),
+        (311, 2) => Some("q"),
+        (312, 0) => Some("signal"),
+        (312, 1) => Some("center"),
+        (312, 2) => Some("q"),
+        (313, 0) => Some("signal"),
+        (313, 1) => Some("center"),
+        (313, 2) => Some("q"),
+        (314, 0) => Some("signal"),
+        (314, 1) => Some("center"),
+        (314, 2) => Some("q"),
+        (314, 3) => Some("gain"),
+        (315, 0) => Some("signal"),
+        (315, 1) => Some("center"),
+        (315, 2) => Some("q"),
+        (315, 3) => Some("gain"),
+        (316, 0) => Some("signal"),
+        (316, 1) => Some("center"),
+        (316, 2) => Some("q"),
+        (316, 3) => Some("gain"),
+        (317, 0) => Some("signal"),
+        (500, 0) => Some("repeat"),
+        (500, 1) => Some("reset"),
+        (501, 0) => Some("rate"),
+        (501, 1) => Some("start"),
+        (501, 2) => Some("end"),
+        (501, 3) => Some("crossfade"),
+        (501, 4) => Some("looped"),
+        (501, 5) => Some("samples"),
+        (501, 6) => Some("reset"),
+        (502, 0) => Some("position"),
+        (502, 1) => Some("size"),
+        (502, 2) => Some("density"),
+        (502, 3) => Some("pitch"),
+        (502, 4) => Some("spray"),
+        (502, 5) => Some("jitter"),
+        (502, 6) => Some("window"),
+        (502, 7) => Some("seed"),
         _ => None,
     }
 }
@@ -301,7 +827,15 @@ pub fn validate_parameter(processor: u64, parameter: u64, value: Value) -> Resul
         151 => crate::noise::Perlin::validate_parameter(parameter, value),
         152 => crate::noise::OpenSimplex::validate_parameter(parameter, value),
         200 => crate::env::Linear::validate_parameter(parameter, value),
+        201 => crate::env::Adsr::validate_parameter(parameter, value),
         250 => crate::delay::Bus::validate_parameter(parameter, value),
+        260 => crate::reverb::Freeverb::validate_parameter(parameter, value),
+        261 => crate::reverb::Fdn::validate_parameter(parameter, value),
+        262 => crate::reverb::Convolution::validate_parameter(parameter, value),
+        270 => crate::dynamics::Compressor::validate_parameter(parameter, value),
+        271 => crate::dynamics::Limiter::validate_parameter(parameter, value),
+        272 => crate::dynamics::Gate::validate_parameter(parameter, value),
+        273 => crate::dynamics::Expander::validate_parameter(parameter, value),
         300 => crate::filter::Butterpass::validate_parameter(parameter, value),
         301 => crate::filter::Lowpole::validate_parameter(parameter, value),
         302 => crate::filter::Allpole::validate_parameter(parameter, value),
@@ -321,6 +855,8 @@ pub fn validate_parameter(processor: u64, parameter: u64, value: Value) -> Resul
         316 => crate::filter::Highshelf::validate_parameter(parameter, value),
         317 => crate::osc::wave::Shaper::validate_parameter(parameter, value),
         500 => crate::buffer::Play::validate_parameter(parameter, value),
+        501 => crate::buffer::Sampler::validate_parameter(parameter, value),
+        502 => crate::buffer::Granular::validate_parameter(parameter, value),
         _ => unreachable!("processor ({}) param ({}) doesn't exist", processor, parameter)
     }
 }
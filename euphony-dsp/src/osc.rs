@@ -26,7 +26,7 @@ impl Phase {
             unsafe_assert!(value.is_finite(), "value: {:?}", value);
             unsafe_assert!((0.0..=1.0).contains(&value), "value: {:?}", value);
         }
-        self.0 = Rate::PERIOD.mul_add(freq, value).fract().abs();
+        self.0 = Rate::period().mul_add(freq, value).fract().abs();
         value
     }
 
@@ -194,7 +194,7 @@ impl Impulse {
             0.0
         };
 
-        let mut next = Rate::PERIOD.mul_add(freq, phase).abs();
+        let mut next = Rate::period().mul_add(freq, phase).abs();
         if next >= 1.0 {
             self.trigger = true;
             next = next.fract();
@@ -13,7 +13,7 @@ impl Phase {
     #[inline(always)]
     fn next(&mut self, freq: f64) -> u16 {
         let value = self.0;
-        let step = (Rate::PERIOD * u16::MAX as f64 * freq) as u16;
+        let step = (Rate::period() * u16::MAX as f64 * freq) as u16;
         self.0 += step.max(1);
         value.0
     }
@@ -69,7 +69,7 @@ impl Pulse {
 
         let value = self.value;
         let diff = 0.0 - value;
-        let samples = (decay * Rate::VALUE).round();
+        let samples = (decay * Rate::value()).round();
         let step = diff / samples;
 
         if value.abs() > step {
@@ -1,5 +1,3 @@
-pub use crate::sample::{
-    DefaultRate as Rate, DefaultSample as Sample, Rate as RateTrait, Sample as SampleTrait,
-};
+pub use crate::sample::{Current as Rate, DefaultSample as Sample, Sample as SampleTrait};
 pub use euphony_node::{Buffer, Input, Node};
 pub use euphony_units::zip::Zip;
@@ -0,0 +1,539 @@
+use crate::prelude::*;
+use euphony_node::Hash;
+use rustfft::{num_complex::Complex, Fft, FftPlannerScalar};
+use std::{collections::VecDeque, sync::Arc};
+
+/// The longest pre-delay, in seconds, the algorithmic reverbs support
+const MAX_PREDELAY: f64 = 1.0;
+
+/// Scales a delay length tuned for 44.1kHz to the current rate
+#[inline]
+fn scale(len: usize) -> usize {
+    ((len as f64 * Rate::value() / 44_100.0).round() as usize).max(1)
+}
+
+#[inline]
+fn mix(dry: Sample, wet: Sample, mix: Sample) -> Sample {
+    let mix = mix.clamp(0.0, 1.0);
+    dry * (1.0 - mix) + wet * mix
+}
+
+#[derive(Debug)]
+struct Line {
+    samples: Vec<Sample>,
+    position: usize,
+}
+
+impl Line {
+    fn new(len: usize) -> Self {
+        Self {
+            samples: vec![0.0; len.max(1)],
+            position: 0,
+        }
+    }
+
+    /// Returns the sample written `len` samples ago
+    #[inline]
+    fn read(&self) -> Sample {
+        self.samples[self.position]
+    }
+
+    #[inline]
+    fn write(&mut self, value: Sample) {
+        self.samples[self.position] = value;
+        self.position += 1;
+        if self.position == self.samples.len() {
+            self.position = 0;
+        }
+    }
+
+    /// Writes a sample and returns the one written `delay` samples before it
+    #[inline]
+    fn tap(&mut self, value: Sample, delay: usize) -> Sample {
+        let len = self.samples.len();
+        let delay = delay.min(len - 1);
+        self.samples[self.position] = value;
+        let idx = (self.position + len - delay) % len;
+        self.position = (self.position + 1) % len;
+        self.samples[idx]
+    }
+}
+
+#[derive(Debug)]
+struct PreDelay(Line);
+
+impl PreDelay {
+    fn new() -> Self {
+        Self(Line::new((MAX_PREDELAY * Rate::value()) as usize + 1))
+    }
+
+    #[inline]
+    fn process(&mut self, signal: Sample, predelay: Sample) -> Sample {
+        let delay = (predelay.clamp(0.0, MAX_PREDELAY) * Rate::value()).round() as usize;
+        self.0.tap(signal, delay)
+    }
+}
+
+#[derive(Debug)]
+struct Comb {
+    line: Line,
+    filter: Sample,
+}
+
+impl Comb {
+    #[inline]
+    fn process(&mut self, input: Sample, feedback: Sample, damp: Sample) -> Sample {
+        let output = self.line.read();
+        self.filter = output * (1.0 - damp) + self.filter * damp;
+        self.line.write(input + self.filter * feedback);
+        output
+    }
+}
+
+#[derive(Debug)]
+struct Allpass(Line);
+
+impl Allpass {
+    #[inline]
+    fn process(&mut self, input: Sample) -> Sample {
+        let delayed = self.0.read();
+        self.0.write(input + delayed * 0.5);
+        delayed - input
+    }
+}
+
+#[derive(Debug, Node)]
+#[node(id = 260, module = "reverb")]
+#[input(signal, default = 0.0)]
+#[input(size, default = 0.5)]
+#[input(damping, default = 0.5)]
+#[input(predelay)]
+#[input(mix, default = 0.3)]
+/// Schroeder-style algorithmic reverb using the Freeverb tuning
+///
+/// `size` and `damping` range from 0 to 1, `predelay` is in seconds, and `mix` blends the dry
+/// signal with the reverb.
+pub struct Freeverb {
+    predelay: PreDelay,
+    combs: [Comb; 8],
+    allpasses: [Allpass; 4],
+}
+
+impl Default for Freeverb {
+    fn default() -> Self {
+        let comb = |len| Comb {
+            line: Line::new(scale(len)),
+            filter: 0.0,
+        };
+        let allpass = |len| Allpass(Line::new(scale(len)));
+
+        Self {
+            predelay: PreDelay::new(),
+            combs: [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617].map(comb),
+            allpasses: [556, 441, 341, 225].map(allpass),
+        }
+    }
+}
+
+impl Freeverb {
+    #[inline]
+    pub fn render(
+        &mut self,
+        signal: Input,
+        size: Input,
+        damping: Input,
+        predelay: Input,
+        mix: Input,
+        output: &mut [Sample],
+    ) {
+        for (signal, size, damping, predelay, amount, output) in
+            (signal, size, damping, predelay, mix, output.iter_mut()).zip()
+        {
+            let feedback = size.clamp(0.0, 1.0) * 0.28 + 0.7;
+            let damp = damping.clamp(0.0, 1.0) * 0.4;
+
+            let input = self.predelay.process(signal, predelay) * 0.015;
+
+            let mut wet = 0.0;
+            for comb in self.combs.iter_mut() {
+                wet += comb.process(input, feedback, damp);
+            }
+            for allpass in self.allpasses.iter_mut() {
+                wet = allpass.process(wet);
+            }
+
+            *output = self::mix(signal, wet * 3.0, amount);
+        }
+    }
+}
+
+/// Mutually prime line lengths, tuned for 44.1kHz, so the modes don't stack up
+const FDN_LINES: [usize; 8] = [1321, 1471, 1627, 1787, 1949, 2111, 2269, 2423];
+
+#[derive(Debug, Node)]
+#[node(id = 261, module = "reverb")]
+#[input(signal, default = 0.0)]
+#[input(size, default = 0.5)]
+#[input(damping, default = 0.5)]
+#[input(predelay)]
+#[input(mix, default = 0.3)]
+/// Feedback delay network reverb with eight lines mixed by a Hadamard matrix
+///
+/// `size` ranges from 0 to 1 and sets the decay time from 0.1 to 10 seconds. `damping` ranges from
+/// 0 to 1 and darkens the tail, `predelay` is in seconds, and `mix` blends the dry signal with the
+/// reverb.
+pub struct Fdn {
+    predelay: PreDelay,
+    lines: [Line; 8],
+    filters: [Sample; 8],
+}
+
+impl Default for Fdn {
+    fn default() -> Self {
+        Self {
+            predelay: PreDelay::new(),
+            lines: FDN_LINES.map(|len| Line::new(scale(len))),
+            filters: [0.0; 8],
+        }
+    }
+}
+
+impl Fdn {
+    #[inline]
+    pub fn render(
+        &mut self,
+        signal: Input,
+        size: Input,
+        damping: Input,
+        predelay: Input,
+        mix: Input,
+        output: &mut [Sample],
+    ) {
+        let sample_rate = Rate::value();
+        let norm = (FDN_LINES.len() as f64).sqrt().recip();
+
+        for (signal, size, damping, predelay, amount, output) in
+            (signal, size, damping, predelay, mix, output.iter_mut()).zip()
+        {
+            let rt60 = 0.1 * 100f64.powf(size.clamp(0.0, 1.0));
+            let damp = damping.clamp(0.0, 1.0) * 0.7;
+
+            let input = self.predelay.process(signal, predelay) * norm;
+
+            let mut values = [0.0; 8];
+            let mut wet = 0.0;
+            for (idx, (line, filter, value)) in (
+                self.lines.iter(),
+                self.filters.iter_mut(),
+                values.iter_mut(),
+            )
+                .zip()
+                .enumerate()
+            {
+                let delayed = line.read();
+                // alternate the signs so the lines don't sum to a comb
+                wet += if idx % 2 == 0 { delayed } else { -delayed };
+
+                // lose 60dB every `rt60` seconds regardless of the line length
+                let gain = 10f64.powf(-3.0 * line.samples.len() as f64 / (rt60 * sample_rate));
+                *filter = delayed * (1.0 - damp) + *filter * damp;
+                *value = *filter * gain;
+            }
+
+            hadamard(&mut values);
+
+            for (line, value) in (self.lines.iter_mut(), values).zip() {
+                line.write(value * norm + input);
+            }
+
+            *output = self::mix(signal, wet * norm, amount);
+        }
+    }
+}
+
+/// Applies an unnormalized fast Walsh-Hadamard transform
+#[inline]
+fn hadamard(values: &mut [Sample; 8]) {
+    let mut width = 1;
+    while width < values.len() {
+        for start in (0..values.len()).step_by(width * 2) {
+            for idx in start..start + width {
+                let a = values[idx];
+                let b = values[idx + width];
+                values[idx] = a + b;
+                values[idx + width] = a - b;
+            }
+        }
+        width *= 2;
+    }
+}
+
+/// The partition size for the impulse response
+///
+/// The first partition is convolved directly so the output doesn't have any latency.
+const BLOCK: usize = 256;
+
+#[derive(Node)]
+#[node(id = 262, module = "reverb")]
+#[buffer(impulse)]
+#[input(signal, default = 0.0)]
+#[input(mix, default = 1.0)]
+/// Convolves the signal with an impulse response
+///
+/// The response is split into partitions and convolved in the frequency domain, so long rooms
+/// stay cheap. `mix` blends the dry signal with the convolved one.
+pub struct Convolution {
+    hash: Hash,
+    len: usize,
+    /// The first partition of the response, applied directly
+    head: Vec<Sample>,
+    /// The spectra of the remaining partitions
+    partitions: Vec<Vec<Complex<Sample>>>,
+    /// The spectra of the most recent input blocks, newest first
+    history: VecDeque<Vec<Complex<Sample>>>,
+    /// The previous and current input blocks
+    window: Box<[Sample; BLOCK * 2]>,
+    /// The output of the partitions for the current block
+    tail: Box<[Sample; BLOCK]>,
+    position: usize,
+    forward: Arc<dyn Fft<Sample>>,
+    inverse: Arc<dyn Fft<Sample>>,
+    buffer: Vec<Complex<Sample>>,
+    scratch: Vec<Complex<Sample>>,
+}
+
+impl Default for Convolution {
+    fn default() -> Self {
+        // the scalar planner picks the same algorithm on every machine so renders stay identical
+        let mut planner = FftPlannerScalar::new();
+        let forward = planner.plan_fft_forward(BLOCK * 2);
+        let inverse = planner.plan_fft_inverse(BLOCK * 2);
+        let scratch_len = forward
+            .get_inplace_scratch_len()
+            .max(inverse.get_inplace_scratch_len());
+
+        Self {
+            hash: [0; 32],
+            len: 0,
+            head: vec![],
+            partitions: vec![],
+            history: VecDeque::new(),
+            window: Box::new([0.0; BLOCK * 2]),
+            tail: Box::new([0.0; BLOCK]),
+            position: 0,
+            forward,
+            inverse,
+            buffer: vec![Complex::default(); BLOCK * 2],
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+}
+
+impl Convolution {
+    #[inline]
+    pub fn render(&mut self, signal: Input, mix: Input, impulse: Buffer, output: &mut [Sample]) {
+        if *impulse.hash != self.hash || impulse.samples.len() != self.len {
+            self.set_impulse(impulse);
+        }
+
+        for (signal, amount, output) in (signal, mix, output.iter_mut()).zip() {
+            let position = self.position;
+            self.window[BLOCK + position] = signal;
+
+            let mut wet = self.tail[position];
+            for (coefficient, sample) in (
+                self.head.iter(),
+                self.window[..=BLOCK + position].iter().rev(),
+            )
+                .zip()
+            {
+                wet += coefficient * sample;
+            }
+
+            *output = self::mix(signal, wet, amount);
+
+            self.position += 1;
+            if self.position == BLOCK {
+                self.process_block();
+                self.window.copy_within(BLOCK.., 0);
+                self.position = 0;
+            }
+        }
+    }
+
+    /// Splits the impulse response into partitions
+    ///
+    /// This allocates so it should only happen when the response changes.
+    fn set_impulse(&mut self, impulse: Buffer) {
+        self.hash = *impulse.hash;
+        self.len = impulse.samples.len();
+
+        let mut chunks = impulse.samples.chunks(BLOCK);
+        self.head = chunks.next().unwrap_or(&[]).to_vec();
+
+        self.partitions.clear();
+        for chunk in chunks {
+            let mut spectrum = vec![Complex::default(); BLOCK * 2];
+            for (from, to) in (chunk, spectrum.iter_mut()).zip() {
+                to.re = *from;
+            }
+            self.forward
+                .process_with_scratch(&mut spectrum, &mut self.scratch);
+            self.partitions.push(spectrum);
+        }
+
+        self.history.clear();
+        self.history
+            .resize(self.partitions.len(), vec![Complex::default(); BLOCK * 2]);
+        self.tail.fill(0.0);
+    }
+
+    /// Convolves the completed input block with the partitions to produce the next block's tail
+    fn process_block(&mut self) {
+        let Some(mut spectrum) = self.history.pop_back() else {
+            return;
+        };
+
+        for (from, to) in (self.window.iter(), spectrum.iter_mut()).zip() {
+            *to = Complex::new(*from, 0.0);
+        }
+        self.forward
+            .process_with_scratch(&mut spectrum, &mut self.scratch);
+        self.history.push_front(spectrum);
+
+        self.buffer.fill(Complex::default());
+        for (spectrum, partition) in (self.history.iter(), self.partitions.iter()).zip() {
+            for (to, (a, b)) in self
+                .buffer
+                .iter_mut()
+                .zip(spectrum.iter().zip(partition.iter()))
+            {
+                *to += a * b;
+            }
+        }
+        self.inverse
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+
+        // only the second half of the circular convolution is free of wrap-around
+        let scale = (BLOCK * 2) as f64;
+        for (from, to) in (self.buffer[BLOCK..].iter(), self.tail.iter_mut()).zip() {
+            *to = from.re / scale;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euphony_node::LEN;
+
+    fn impulse(len: usize) -> Vec<Sample> {
+        let mut signal = vec![0.0; len];
+        signal[0] = 1.0;
+        signal
+    }
+
+    fn render(
+        len: usize,
+        signal: &[Sample],
+        mut f: impl FnMut(Input, &mut [Sample]),
+    ) -> Vec<Sample> {
+        let mut output = vec![0.0; len];
+        // render in chunks that don't line up with the partitions
+        for (idx, chunk) in output.chunks_mut(LEN - 1).enumerate() {
+            let mut input = [0.0; LEN];
+            let start = (idx * (LEN - 1)).min(signal.len());
+            let end = (start + chunk.len()).min(signal.len());
+            input[..end - start].copy_from_slice(&signal[start..end]);
+            f((&input).into(), chunk);
+        }
+        output
+    }
+
+    fn energy(samples: &[Sample]) -> f64 {
+        samples.iter().map(|s| s * s).sum()
+    }
+
+    #[test]
+    fn algorithmic_test() {
+        let len = Rate::value() as usize * 2;
+        let signal = impulse(len);
+        let predelay = 0.01;
+        let delay = (predelay * Rate::value()) as usize;
+
+        let mut freeverb = Freeverb::default();
+        let mut fdn = Fdn::default();
+        let outputs = [
+            render(len, &signal, |s, o| {
+                freeverb.render(s, 0.8.into(), 0.5.into(), predelay.into(), 1.0.into(), o)
+            }),
+            render(len, &signal, |s, o| {
+                fdn.render(s, 0.5.into(), 0.5.into(), predelay.into(), 1.0.into(), o)
+            }),
+        ];
+
+        for output in outputs {
+            assert!(output.iter().all(|s| s.is_finite()));
+            // nothing comes out until the pre-delay has passed
+            assert!(output[..delay].iter().all(|s| *s == 0.0));
+
+            let quarter = len / 4;
+            let early = energy(&output[..quarter]);
+            let late = energy(&output[len - quarter..]);
+            assert!(early > 0.0);
+            assert!(late < early, "the tail should decay: {late} >= {early}");
+        }
+    }
+
+    #[test]
+    fn determinism_test() {
+        let len = LEN * 16;
+        let signal = impulse(len);
+
+        let run = || {
+            let mut fdn = Fdn::default();
+            render(len, &signal, |s, o| {
+                fdn.render(s, 0.7.into(), 0.2.into(), 0.0.into(), 0.5.into(), o)
+            })
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn convolution_test() {
+        let len = BLOCK * 12 + 17;
+        let signal: Vec<Sample> = (0..len)
+            .map(|idx| ((idx * 7919) % 101) as f64 / 50.0 - 1.0)
+            .collect();
+
+        // a unit impulse leaves the signal untouched
+        let mut convolution = Convolution::default();
+        let identity = [1.0];
+        let output = render(len, &signal, |s, o| {
+            convolution.render(s, 1.0.into(), (&identity[..]).into(), o)
+        });
+        assert_eq!(output, signal);
+
+        // a response spanning several partitions matches the direct convolution
+        let response: Vec<Sample> = (0..BLOCK * 4 + 3)
+            .map(|idx| (-(idx as f64) / 200.0).exp() * if idx % 3 == 0 { 1.0 } else { -0.5 })
+            .collect();
+        let mut convolution = Convolution::default();
+        let output = render(len, &signal, |s, o| {
+            convolution.render(s, 1.0.into(), (&response[..]).into(), o)
+        });
+
+        for (idx, actual) in output.iter().enumerate() {
+            let expected: f64 = response
+                .iter()
+                .take(idx + 1)
+                .enumerate()
+                .map(|(offset, h)| h * signal[idx - offset])
+                .sum();
+            assert!(
+                (actual - expected).abs() < 1e-9,
+                "sample {idx}: {actual} != {expected}"
+            );
+        }
+    }
+}
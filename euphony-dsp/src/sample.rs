@@ -28,3 +28,81 @@ impl Rate for Rate48000 {
     const COUNT: u64 = 48000;
     const NANOS_PER_SAMPLE: Ratio<u64> = Ratio(62500, 3);
 }
+
+/// A sample rate in hertz, chosen at runtime
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SampleRate(u32);
+
+impl SampleRate {
+    pub const DEFAULT: Self = Self(DefaultRate::COUNT as _);
+
+    #[inline]
+    pub const fn new(hz: u32) -> Option<Self> {
+        if hz == 0 {
+            None
+        } else {
+            Some(Self(hz))
+        }
+    }
+
+    #[inline]
+    pub const fn count(self) -> u64 {
+        self.0 as _
+    }
+
+    #[inline]
+    pub fn value(self) -> f64 {
+        self.0 as _
+    }
+
+    #[inline]
+    pub fn period(self) -> f64 {
+        1.0 / self.value()
+    }
+
+    #[inline]
+    pub fn nanos_per_sample(self) -> Ratio<u64> {
+        Ratio(1_000_000_000, self.count()).reduce()
+    }
+}
+
+impl Default for SampleRate {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl From<SampleRate> for u32 {
+    #[inline]
+    fn from(rate: SampleRate) -> Self {
+        rate.0
+    }
+}
+
+// nodes fall back to the default rate outside of a render
+const _: () = assert!(DefaultRate::COUNT == euphony_node::rate::DEFAULT as u64);
+
+/// The sample rate that nodes are rendered at
+///
+/// The rate comes from the render context while nodes are spawned and processed, see
+/// [`euphony_node::rate`], and is [`SampleRate::DEFAULT`] otherwise.
+pub struct Current;
+
+impl Current {
+    #[inline]
+    pub fn get() -> SampleRate {
+        SampleRate(euphony_node::rate::get())
+    }
+
+    #[inline]
+    pub fn value() -> f64 {
+        Self::get().value()
+    }
+
+    /// Returns the period without dividing so it can be called for every sample
+    #[inline]
+    pub fn period() -> f64 {
+        euphony_node::rate::period()
+    }
+}
@@ -45,6 +45,10 @@ pub trait Processor<C: Config>: 'static + Send {
     fn process(&mut self, inputs: Inputs<C>, context: &C::Context);
 
     fn fork(&self) -> Option<Box<dyn Processor<C>>>;
+
+    /// Called when the node is explicitly finished rather than dropped with the graph
+    #[inline]
+    fn finish(&mut self) {}
 }
 
 #[derive(Debug)]
@@ -9,6 +9,7 @@ pub struct Node {
     ident: Ident,
     id: syn::LitInt,
     fork: Option<Ident>,
+    validate: Option<Ident>,
     module: Option<syn::Path>,
     attrs: Vec<syn::Attribute>,
 }
@@ -94,6 +95,17 @@ impl ToTokens for Node {
             quote!(None)
         };
 
+        let validate = if let Some(validate) = self.validate.as_ref() {
+            quote!(Self::#validate(param, value))
+        } else {
+            quote!({
+                // TODO
+                let _ = param;
+                let _ = value;
+                Ok(())
+            })
+        };
+
         let test_name = Ident::new(&format!("euphony_node_test_{name_str}"), self.ident.span());
 
         let mut test_inputs = quote!();
@@ -174,10 +186,7 @@ impl ToTokens for Node {
 
                 #[inline]
                 pub fn validate_parameter(param: u64, value: ::euphony_node::ParameterValue) -> Result<(), ::euphony_node::Error> {
-                    // TODO
-                    let _ = param;
-                    let _ = value;
-                    Ok(())
+                    #validate
                 }
             }
 
@@ -0,0 +1,111 @@
+//! Encodes samples into higher order Ambisonics
+//!
+//! The channels use the AmbiX convention: ACN ordering with SN3D normalization.
+
+use crate::{frame::Frame, Mixer, SpatialSample, Writer};
+use euphony_dsp::sample::Sample;
+use euphony_units::coordinates::Cartesian;
+
+/// The highest supported order
+pub const MAX_ORDER: usize = 3;
+const MAX_CHANNELS: usize = (MAX_ORDER + 1) * (MAX_ORDER + 1);
+
+/// Returns the number of channels needed for an order
+#[inline]
+pub const fn channels(order: usize) -> usize {
+    (order + 1) * (order + 1)
+}
+
+#[derive(Debug)]
+pub struct Ambisonic<W: Writer> {
+    writer: W,
+}
+
+impl<W: Writer> Ambisonic<W> {
+    /// Creates an encoder with the order implied by the writer's channel count
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        debug_assert!(
+            (1..=MAX_ORDER).any(|order| channels(order) == W::Frame::CHANNELS),
+            "{} channels is not a supported ambisonic order",
+            W::Frame::CHANNELS
+        );
+        Self { writer }
+    }
+
+    #[inline]
+    pub fn finish(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Writer> Mixer for Ambisonic<W> {
+    type Error = W::Error;
+
+    #[inline]
+    fn skip(&mut self, frames: usize) -> Result<(), Self::Error> {
+        self.writer.skip(frames)
+    }
+
+    #[inline]
+    fn mix(&mut self, samples: &[SpatialSample]) -> Result<(), Self::Error> {
+        let mut frame = [0.0f64; MAX_CHANNELS];
+
+        for s in samples.iter() {
+            let coefficients = encode(&s.coordinate);
+            for (to, coefficient) in frame.iter_mut().zip(coefficients) {
+                *to += s.value * coefficient;
+            }
+        }
+
+        let frame = W::Frame::from_fn(|idx| frame[idx].to_sample());
+        self.writer.write(frame)?;
+
+        Ok(())
+    }
+}
+
+/// Computes the spherical harmonics up to the third order for a coordinate
+///
+/// Coordinates at the center only have an omnidirectional component.
+pub fn encode(coordinate: &Cartesian<f64>) -> [f64; MAX_CHANNELS] {
+    let mut out = [0.0; MAX_CHANNELS];
+    out[0] = 1.0;
+
+    let Cartesian { x, y, z } = *coordinate;
+    let radius = (x * x + y * y + z * z).sqrt();
+    if radius == 0.0 {
+        return out;
+    }
+
+    // euphony uses x as right and y as forward while Ambisonics uses x as forward and y as left
+    let (x, y, z) = (y / radius, -x / radius, z / radius);
+
+    let sqrt3 = 3f64.sqrt();
+    let sqrt15 = 15f64.sqrt();
+    let sqrt3_8 = (3.0f64 / 8.0).sqrt();
+    let sqrt5_8 = (5.0f64 / 8.0).sqrt();
+
+    // first order
+    out[1] = y;
+    out[2] = z;
+    out[3] = x;
+
+    // second order
+    out[4] = sqrt3 * x * y;
+    out[5] = sqrt3 * y * z;
+    out[6] = 0.5 * (3.0 * z * z - 1.0);
+    out[7] = sqrt3 * x * z;
+    out[8] = 0.5 * sqrt3 * (x * x - y * y);
+
+    // third order
+    out[9] = sqrt5_8 * y * (3.0 * x * x - y * y);
+    out[10] = sqrt15 * x * y * z;
+    out[11] = sqrt3_8 * y * (5.0 * z * z - 1.0);
+    out[12] = 0.5 * z * (5.0 * z * z - 3.0);
+    out[13] = sqrt3_8 * x * (5.0 * z * z - 1.0);
+    out[14] = 0.5 * sqrt15 * z * (x * x - y * y);
+    out[15] = sqrt5_8 * x * (x * x - 3.0 * y * y);
+
+    out
+}
@@ -1,6 +1,10 @@
 use crate::{Sample, SpatialSample};
 use euphony_units::coordinates::Cartesian;
 
+/// Decodes a sample as a virtual microphone pointed at a speaker
+///
+/// The sample is encoded into first order B-format with the W channel scaled by `1/√2` and the
+/// weights recover it with a polar pattern of `directionality + (1 - directionality) * cos θ`.
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Weights {
     w: f64,
@@ -16,7 +20,11 @@ impl Weights {
         } = coordinate;
 
         let radius = (x * x + y * y + z * z).sqrt();
-        let m = (1.0 - directionality) / radius;
+        let m = if radius > 0.0 {
+            (1.0 - directionality) / radius
+        } else {
+            0.0
+        };
         x *= m;
         y *= m;
         z *= m;
@@ -27,8 +35,33 @@ impl Weights {
         }
     }
 
+    /// Scales the output of the weights by a linear gain
+    #[inline]
+    pub fn scale(self, gain: f64) -> Self {
+        let Cartesian { x, y, z } = self.coordinate;
+        Self {
+            w: self.w * gain,
+            coordinate: Cartesian {
+                x: x * gain,
+                y: y * gain,
+                z: z * gain,
+            },
+        }
+    }
+
     #[inline]
     pub fn dot(&self, sample: &SpatialSample) -> f64 {
-        self.w * sample.value + self.coordinate.dot(&sample.coordinate)
+        let Cartesian { x, y, z } = sample.coordinate;
+        let radius = (x * x + y * y + z * z).sqrt();
+
+        let w = sample.value * core::f64::consts::FRAC_1_SQRT_2;
+        let mut value = self.w * w;
+
+        // samples at the center don't have a direction so they're only carried by W
+        if radius > 0.0 {
+            value += self.coordinate.dot(&sample.coordinate) * sample.value / radius;
+        }
+
+        value
     }
 }
@@ -35,7 +35,7 @@ impl<const CHANNELS: usize, W: Writer> Mixer for Distance<CHANNELS, W> {
 
         for s in samples.iter() {
             for (to, config) in frame.iter_mut().zip(self.config.iter()) {
-                *to = config.dot(s);
+                *to += config.dot(s);
             }
         }
 
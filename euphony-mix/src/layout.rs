@@ -0,0 +1,437 @@
+//! Output channel layouts and a mixer that can target any of them
+
+use crate::{
+    ambisonic::{self, Ambisonic},
+    bformat::Weights,
+    distance::Distance,
+    frame::Frame,
+    mono::Mono,
+    stereo::Stereo,
+    Mixer, SpatialSample, Writer,
+};
+use core::{fmt, str::FromStr};
+use euphony_units::coordinates::Cartesian;
+
+/// The polar pattern of the virtual microphones pointed at each speaker
+const DIRECTIONALITY: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Layout {
+    Mono,
+    #[default]
+    Stereo,
+    Quad,
+    Surround51,
+    Surround71,
+    Surround714,
+    Octagon,
+    /// AmbiX B-format of the given order
+    Ambisonic(u8),
+}
+
+/// The direction of a speaker in degrees, with positive azimuths to the right
+///
+/// LFE channels don't have a direction and don't receive any of the mix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Speaker {
+    Direction { azimuth: f64, elevation: f64 },
+    Lfe,
+}
+
+const fn speaker(azimuth: f64, elevation: f64) -> Speaker {
+    Speaker::Direction { azimuth, elevation }
+}
+
+// The surround layouts follow the WAVE channel order (FL, FR, FC, LFE, BL, BR, SL, SR) with the
+// height channels at the end
+const QUAD: [Speaker; 4] = [
+    speaker(-45.0, 0.0),
+    speaker(45.0, 0.0),
+    speaker(-135.0, 0.0),
+    speaker(135.0, 0.0),
+];
+const SURROUND_51: [Speaker; 6] = [
+    speaker(-30.0, 0.0),
+    speaker(30.0, 0.0),
+    speaker(0.0, 0.0),
+    Speaker::Lfe,
+    speaker(-110.0, 0.0),
+    speaker(110.0, 0.0),
+];
+const SURROUND_71: [Speaker; 8] = [
+    speaker(-30.0, 0.0),
+    speaker(30.0, 0.0),
+    speaker(0.0, 0.0),
+    Speaker::Lfe,
+    speaker(-150.0, 0.0),
+    speaker(150.0, 0.0),
+    speaker(-90.0, 0.0),
+    speaker(90.0, 0.0),
+];
+const SURROUND_714: [Speaker; 12] = [
+    speaker(-30.0, 0.0),
+    speaker(30.0, 0.0),
+    speaker(0.0, 0.0),
+    Speaker::Lfe,
+    speaker(-150.0, 0.0),
+    speaker(150.0, 0.0),
+    speaker(-90.0, 0.0),
+    speaker(90.0, 0.0),
+    speaker(-45.0, 45.0),
+    speaker(45.0, 45.0),
+    speaker(-135.0, 45.0),
+    speaker(135.0, 45.0),
+];
+const OCTAGON: [Speaker; 8] = [
+    speaker(-22.5, 0.0),
+    speaker(22.5, 0.0),
+    speaker(67.5, 0.0),
+    speaker(112.5, 0.0),
+    speaker(157.5, 0.0),
+    speaker(-157.5, 0.0),
+    speaker(-112.5, 0.0),
+    speaker(-67.5, 0.0),
+];
+
+impl Layout {
+    /// Returns the default layout for a number of channels
+    pub fn from_channels(channels: usize) -> Option<Self> {
+        Some(match channels {
+            1 => Self::Mono,
+            2 => Self::Stereo,
+            4 => Self::Quad,
+            6 => Self::Surround51,
+            8 => Self::Surround71,
+            12 => Self::Surround714,
+            16 => Self::Ambisonic(3),
+            _ => return None,
+        })
+    }
+
+    pub fn channels(self) -> usize {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+            Self::Ambisonic(order) => ambisonic::channels(order as _),
+            _ => self.speakers().len(),
+        }
+    }
+
+    /// Returns the speakers of a surround layout, in channel order
+    ///
+    /// Mono, stereo and ambisonic layouts don't have any speakers.
+    pub fn speakers(self) -> &'static [Speaker] {
+        match self {
+            Self::Quad => &QUAD,
+            Self::Surround51 => &SURROUND_51,
+            Self::Surround71 => &SURROUND_71,
+            Self::Surround714 => &SURROUND_714,
+            Self::Octagon => &OCTAGON,
+            Self::Mono | Self::Stereo | Self::Ambisonic(_) => &[],
+        }
+    }
+
+    /// Returns the BS.1770 loudness weight of each channel
+    ///
+    /// Speakers to the sides of the listener are 1.5dB louder and the LFE is left out. Ambisonic
+    /// channels don't feed speakers so they can't be weighted.
+    pub fn loudness_weights(self) -> Option<Vec<f64>> {
+        let speakers = self.speakers();
+        if speakers.is_empty() {
+            return match self {
+                Self::Ambisonic(_) => None,
+                _ => Some(vec![1.0; self.channels()]),
+            };
+        }
+
+        let weights = speakers
+            .iter()
+            .map(|speaker| match *speaker {
+                Speaker::Direction { azimuth, elevation }
+                    if elevation.abs() < 30.0 && (60.0..=120.0).contains(&azimuth.abs()) =>
+                {
+                    1.41
+                }
+                Speaker::Direction { .. } => 1.0,
+                Speaker::Lfe => 0.0,
+            })
+            .collect();
+
+        Some(weights)
+    }
+
+    /// Returns the decoding weights for each of the speakers
+    ///
+    /// The weights are scaled so a source moving around the listener keeps about the same power
+    /// as it would in mono.
+    pub fn weights<const CHANNELS: usize>(self) -> [Weights; CHANNELS] {
+        let speakers = self.speakers();
+        assert_eq!(
+            speakers.len(),
+            CHANNELS,
+            "{self} doesn't have {CHANNELS} channels"
+        );
+
+        let weights: [Weights; CHANNELS] = core::array::from_fn(|idx| match speakers[idx] {
+            Speaker::Direction { azimuth, elevation } => {
+                Weights::new(direction(azimuth, elevation), DIRECTIONALITY)
+            }
+            Speaker::Lfe => Weights::default(),
+        });
+
+        // average the power over a circle of sources around the listener
+        const STEPS: usize = 72;
+        let mut power = 0.0;
+        for step in 0..STEPS {
+            let sample = SpatialSample {
+                value: 1.0,
+                coordinate: direction(step as f64 * 360.0 / STEPS as f64, 0.0),
+            };
+            for weights in &weights {
+                power += weights.dot(&sample).powi(2);
+            }
+        }
+        let gain = (power / STEPS as f64).sqrt().recip();
+
+        weights.map(|weights| weights.scale(gain))
+    }
+}
+
+fn direction(azimuth: f64, elevation: f64) -> Cartesian<f64> {
+    let azimuth = azimuth.to_radians();
+    let elevation = elevation.to_radians();
+    Cartesian {
+        x: azimuth.sin() * elevation.cos(),
+        y: azimuth.cos() * elevation.cos(),
+        z: elevation.sin(),
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Mono => "mono".fmt(f),
+            Self::Stereo => "stereo".fmt(f),
+            Self::Quad => "quad".fmt(f),
+            Self::Surround51 => "5.1".fmt(f),
+            Self::Surround71 => "7.1".fmt(f),
+            Self::Surround714 => "7.1.4".fmt(f),
+            Self::Octagon => "octagon".fmt(f),
+            Self::Ambisonic(order) => write!(f, "ambix{order}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseLayoutError(String);
+
+impl fmt::Display for ParseLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid layout {:?}; expected a channel count or one of mono, stereo, quad, 5.1, \
+             7.1, 7.1.4, octagon, ambix1, ambix2 or ambix3",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseLayoutError {}
+
+/// Parses layout names like `5.1` or `ambix3`, or a channel count
+impl FromStr for Layout {
+    type Err = ParseLayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let layout = match s.trim().to_lowercase().as_str() {
+            "mono" => Self::Mono,
+            "stereo" => Self::Stereo,
+            "quad" => Self::Quad,
+            "5.1" => Self::Surround51,
+            "7.1" => Self::Surround71,
+            "7.1.4" => Self::Surround714,
+            "octagon" => Self::Octagon,
+            "ambix1" | "foa" => Self::Ambisonic(1),
+            "ambix2" => Self::Ambisonic(2),
+            "ambix3" | "hoa3" => Self::Ambisonic(3),
+            other => other
+                .parse()
+                .ok()
+                .and_then(Self::from_channels)
+                .ok_or_else(|| ParseLayoutError(s.to_string()))?,
+        };
+        Ok(layout)
+    }
+}
+
+/// Mixes into any layout with the writer's number of channels
+#[derive(Debug)]
+pub enum Spatial<const CHANNELS: usize, W: Writer> {
+    Mono(Mono<W>),
+    Stereo(Stereo<W>),
+    Speakers(Distance<CHANNELS, W>),
+    Ambisonic(Ambisonic<W>),
+}
+
+impl<const CHANNELS: usize, W: Writer> Spatial<CHANNELS, W> {
+    pub fn new(writer: W, layout: Layout) -> Self {
+        assert_eq!(layout.channels(), CHANNELS);
+        assert_eq!(W::Frame::CHANNELS, CHANNELS);
+
+        match layout {
+            Layout::Mono => Self::Mono(Mono::new(writer)),
+            Layout::Stereo => Self::Stereo(Stereo::new(writer)),
+            Layout::Ambisonic(_) => Self::Ambisonic(Ambisonic::new(writer)),
+            _ => Self::Speakers(Distance::new(writer, layout.weights())),
+        }
+    }
+
+    #[inline]
+    pub fn finish(self) -> W {
+        match self {
+            Self::Mono(mixer) => mixer.finish(),
+            Self::Stereo(mixer) => mixer.finish(),
+            Self::Speakers(mixer) => mixer.finish(),
+            Self::Ambisonic(mixer) => mixer.finish(),
+        }
+    }
+}
+
+impl<const CHANNELS: usize, W: Writer> Mixer for Spatial<CHANNELS, W> {
+    type Error = W::Error;
+
+    #[inline]
+    fn skip(&mut self, frames: usize) -> Result<(), Self::Error> {
+        match self {
+            Self::Mono(mixer) => mixer.skip(frames),
+            Self::Stereo(mixer) => mixer.skip(frames),
+            Self::Speakers(mixer) => mixer.skip(frames),
+            Self::Ambisonic(mixer) => mixer.skip(frames),
+        }
+    }
+
+    #[inline]
+    fn mix(&mut self, samples: &[SpatialSample]) -> Result<(), Self::Error> {
+        match self {
+            Self::Mono(mixer) => mixer.mix(samples),
+            Self::Stereo(mixer) => mixer.mix(samples),
+            Self::Speakers(mixer) => mixer.mix(samples),
+            Self::Ambisonic(mixer) => mixer.mix(samples),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Buffer<const CHANNELS: usize>(Vec<[f64; CHANNELS]>);
+
+    impl<const CHANNELS: usize> Writer for Buffer<CHANNELS>
+    where
+        [f64; CHANNELS]: Frame<Sample = f64>,
+    {
+        type Error = ();
+        type Sample = f64;
+        type Frame = [f64; CHANNELS];
+
+        fn skip(&mut self, _frames: usize) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn write(&mut self, frame: [f64; CHANNELS]) -> Result<(), ()> {
+            self.0.push(frame);
+            Ok(())
+        }
+    }
+
+    fn mix<const CHANNELS: usize>(layout: Layout, coordinate: Cartesian<f64>) -> [f64; CHANNELS]
+    where
+        [f64; CHANNELS]: Frame<Sample = f64>,
+    {
+        let mut mixer = Spatial::<CHANNELS, _>::new(Buffer::<CHANNELS>::default(), layout);
+        mixer
+            .mix(&[SpatialSample {
+                value: 1.0,
+                coordinate,
+            }])
+            .unwrap();
+        mixer.finish().0[0]
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("5.1".parse(), Ok(Layout::Surround51));
+        assert_eq!("7.1.4".parse(), Ok(Layout::Surround714));
+        assert_eq!("ambix3".parse(), Ok(Layout::Ambisonic(3)));
+        assert_eq!("2".parse(), Ok(Layout::Stereo));
+        assert!("3".parse::<Layout>().is_err());
+
+        for layout in [
+            Layout::Mono,
+            Layout::Stereo,
+            Layout::Quad,
+            Layout::Surround51,
+            Layout::Surround71,
+            Layout::Surround714,
+            Layout::Octagon,
+            Layout::Ambisonic(1),
+            Layout::Ambisonic(2),
+            Layout::Ambisonic(3),
+        ] {
+            assert_eq!(layout.to_string().parse(), Ok(layout));
+        }
+    }
+
+    #[test]
+    fn surround() {
+        let front = mix::<6>(Layout::Surround51, direction(0.0, 0.0));
+        // the center is loudest and the lfe doesn't get anything
+        assert!(front[2] > front[0] && front[0] > front[4]);
+        assert!((front[0] - front[1]).abs() < 1e-9);
+        assert_eq!(front[3], 0.0);
+
+        let right = mix::<8>(Layout::Octagon, direction(90.0, 0.0));
+        assert!(right[2] > right[0] && right[2] > right[6]);
+        assert!((right[2] - right[3]).abs() < 1e-9);
+
+        // sources at the center are spread evenly
+        let center = mix::<4>(Layout::Quad, Cartesian::default());
+        assert!(center
+            .iter()
+            .all(|v| (v - center[0]).abs() < 1e-9 && *v > 0.0));
+    }
+
+    #[test]
+    fn loudness_weights() {
+        assert_eq!(Layout::Stereo.loudness_weights(), Some(vec![1.0; 2]));
+        assert_eq!(
+            Layout::Surround51.loudness_weights(),
+            Some(vec![1.0, 1.0, 1.0, 0.0, 1.41, 1.41])
+        );
+        // the back channels of 7.1 and the heights aren't weighted
+        assert_eq!(
+            Layout::Surround714.loudness_weights(),
+            Some(vec![
+                1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.41, 1.41, 1.0, 1.0, 1.0, 1.0
+            ])
+        );
+        assert_eq!(Layout::Ambisonic(1).loudness_weights(), None);
+    }
+
+    #[test]
+    fn ambisonic() {
+        // ACN order is W, Y, Z, X with Y pointing left
+        let front = mix::<4>(Layout::Ambisonic(1), direction(0.0, 0.0));
+        assert_eq!(front.map(|v| (v * 1e9).round() / 1e9), [1.0, 0.0, 0.0, 1.0]);
+
+        let left = mix::<4>(Layout::Ambisonic(1), direction(-90.0, 0.0));
+        assert_eq!(left.map(|v| (v * 1e9).round() / 1e9), [1.0, 1.0, 0.0, 0.0]);
+
+        let up = mix::<16>(Layout::Ambisonic(3), direction(0.0, 90.0));
+        assert!((up[2] - 1.0).abs() < 1e-9);
+        assert!((up[6] - 1.0).abs() < 1e-9);
+        assert!((up[12] - 1.0).abs() < 1e-9);
+    }
+}
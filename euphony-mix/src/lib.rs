@@ -2,8 +2,12 @@ pub use dasp_frame as frame;
 use euphony_dsp::sample::{self, DefaultSample as Sample, FromSample};
 use euphony_units::coordinates::Cartesian;
 
-mod bformat;
+pub mod ambisonic;
+pub mod bformat;
 pub mod distance;
+pub mod layout;
+pub mod limiter;
+pub mod loudness;
 pub mod mono;
 pub mod stereo;
 
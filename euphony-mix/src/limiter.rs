@@ -0,0 +1,217 @@
+use crate::{frame::Frame, loudness::TruePeak, Writer};
+use euphony_dsp::sample::{FromSample, Sample};
+use std::collections::VecDeque;
+
+/// Applies a gain to the frames while keeping their true peak under a ceiling
+///
+/// Gain reduction is held for the lookahead and then smoothed over the same window so it's in
+/// place before each peak reaches the output. The output is delayed internally but each frame is
+/// written at its original position.
+#[derive(Debug)]
+pub struct Limiter<W: Writer> {
+    writer: W,
+    gain: f64,
+    ceiling: f64,
+    lookahead: usize,
+    peaks: Vec<TruePeak>,
+    /// The delayed frames, interleaved
+    delay: VecDeque<f64>,
+    /// The minimum required gains over the hold, as `(frame, gain)`
+    holds: VecDeque<(u64, f64)>,
+    /// The held gains that are being averaged
+    window: VecDeque<f64>,
+    window_sum: f64,
+    frame: u64,
+    /// The lowest gain that was applied
+    reduction: f64,
+}
+
+impl<W: Writer> Limiter<W>
+where
+    W::Sample: FromSample<f64>,
+{
+    /// Creates a limiter with a linear `gain` and `ceiling`
+    pub fn new(writer: W, sample_rate: u32, gain: f64, ceiling: f64) -> Self {
+        // 5ms is long enough to catch transients without audibly pumping
+        let lookahead = (sample_rate as usize / 200).max(1);
+        let channels = W::Frame::CHANNELS;
+
+        Self {
+            writer,
+            gain,
+            ceiling,
+            lookahead,
+            peaks: vec![TruePeak::default(); channels],
+            delay: VecDeque::with_capacity((lookahead + TruePeak::DELAY) * channels),
+            holds: VecDeque::new(),
+            window: VecDeque::with_capacity(lookahead),
+            window_sum: 0.0,
+            frame: 0,
+            reduction: 1.0,
+        }
+    }
+
+    /// Flushes the delayed frames, returning the writer and the lowest gain reduction applied
+    pub fn finish(mut self) -> Result<(W, f64), W::Error> {
+        for _ in 0..self.delay_len() {
+            self.process(core::iter::repeat(0.0).take(W::Frame::CHANNELS), false)?;
+        }
+
+        Ok((self.writer, self.reduction))
+    }
+
+    /// Peaks between samples are detected after the samples themselves so the gain is held long
+    /// enough to cover both
+    #[inline]
+    fn hold_len(&self) -> usize {
+        self.lookahead + TruePeak::DELAY
+    }
+
+    #[inline]
+    fn delay_len(&self) -> usize {
+        self.lookahead - 1 + TruePeak::DELAY
+    }
+
+    fn process(
+        &mut self,
+        samples: impl Iterator<Item = f64>,
+        is_input: bool,
+    ) -> Result<(), W::Error> {
+        let mut peak = 0.0f64;
+        for (sample, detector) in samples.zip(&mut self.peaks) {
+            let sample = sample * self.gain;
+            peak = peak.max(detector.apply(sample));
+            if is_input {
+                self.delay.push_back(sample);
+            }
+        }
+
+        let required = if peak > self.ceiling {
+            self.ceiling / peak
+        } else {
+            1.0
+        };
+
+        // keep a monotonic queue so the minimum over the lookahead is always at the front
+        while self.holds.back().is_some_and(|(_, gain)| *gain >= required) {
+            self.holds.pop_back();
+        }
+        self.holds.push_back((self.frame, required));
+        while self
+            .holds
+            .front()
+            .is_some_and(|(frame, _)| *frame + self.hold_len() as u64 <= self.frame)
+        {
+            self.holds.pop_front();
+        }
+        let held = self.holds.front().map_or(1.0, |(_, gain)| *gain);
+
+        self.window.push_back(held);
+        self.window_sum += held;
+        if self.window.len() > self.lookahead {
+            self.window_sum -= self.window.pop_front().unwrap_or(1.0);
+        }
+        let gain = (self.window_sum / self.lookahead as f64).min(1.0);
+
+        self.frame += 1;
+
+        if self.frame as usize <= self.delay_len() {
+            return Ok(());
+        }
+
+        self.reduction = self.reduction.min(gain);
+
+        let delay = &mut self.delay;
+        let ceiling = self.ceiling;
+        let frame = W::Frame::from_fn(|_| {
+            let sample = delay.pop_front().unwrap_or(0.0) * gain;
+            // the running average can drift by a rounding error so clip anything left over
+            sample.clamp(-ceiling, ceiling).to_sample()
+        });
+        self.writer.write(frame)
+    }
+}
+
+impl<W: Writer> Writer for Limiter<W>
+where
+    W::Sample: FromSample<f64>,
+    W::Frame: Frame<Sample = W::Sample>,
+{
+    type Error = W::Error;
+    type Sample = W::Sample;
+    type Frame = W::Frame;
+
+    #[inline]
+    fn skip(&mut self, frames: usize) -> Result<(), Self::Error> {
+        // silence still needs to flow through the delay
+        for _ in 0..frames {
+            self.process(core::iter::repeat(0.0).take(W::Frame::CHANNELS), true)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn write(&mut self, frame: Self::Frame) -> Result<(), Self::Error> {
+        self.process(
+            frame
+                .channels()
+                .map(|sample| sample.to_float_sample().to_sample::<f64>()),
+            true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Buffer(Vec<[f64; 1]>);
+
+    impl Writer for Buffer {
+        type Error = ();
+        type Sample = f64;
+        type Frame = [f64; 1];
+
+        fn skip(&mut self, frames: usize) -> Result<(), ()> {
+            for _ in 0..frames {
+                self.0.push([0.0]);
+            }
+            Ok(())
+        }
+
+        fn write(&mut self, frame: [f64; 1]) -> Result<(), ()> {
+            self.0.push(frame);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn ceiling() {
+        let mut limiter = Limiter::new(Buffer::default(), 48000, 2.0, 0.5);
+
+        limiter.skip(100).unwrap();
+        for idx in 0..48000 {
+            let value = if idx % 1000 == 0 { 0.9 } else { 0.1 };
+            limiter.write([value]).unwrap();
+        }
+
+        let (buffer, reduction) = limiter.finish().unwrap();
+        let frames = buffer.0;
+
+        // the output lines up with the input
+        assert_eq!(frames.len(), 48100);
+        assert!(frames[..100].iter().all(|frame| frame[0] == 0.0));
+
+        let peak = frames
+            .iter()
+            .map(|frame| frame[0].abs())
+            .fold(0.0, f64::max);
+        assert!(peak <= 0.5, "{peak}");
+        assert!(reduction < 0.3, "{reduction}");
+
+        // the gain is applied away from the peaks
+        assert!((frames[100 + 500][0] - 0.2).abs() < 1e-9);
+    }
+}
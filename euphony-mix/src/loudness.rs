@@ -0,0 +1,450 @@
+//! Loudness metering as described by ITU-R BS.1770 and EBU R128
+
+use crate::{frame::Frame, Writer};
+use euphony_dsp::sample::Sample;
+use euphony_units::dynamics::loudness::Loudness;
+
+/// The readings of a [`Meter`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Measurement {
+    /// The gated loudness of the whole signal
+    pub integrated: Loudness,
+    /// The loudest 3 second window
+    pub short_term: Loudness,
+    /// The largest absolute value of the signal oversampled by 4x
+    pub true_peak: f64,
+}
+
+impl Measurement {
+    /// The true peak in dBTP
+    #[inline]
+    pub fn true_peak_db(&self) -> f64 {
+        20.0 * self.true_peak.log10()
+    }
+}
+
+/// Measures the frames passed through to the inner writer
+#[derive(Debug)]
+pub struct Meter<W: Writer> {
+    writer: W,
+    filters: Vec<KWeighting>,
+    peaks: Vec<TruePeak>,
+    /// The gain applied to the power of each channel
+    weights: Vec<f64>,
+    /// The number of frames in each 100ms step
+    step_len: usize,
+    step_pos: usize,
+    /// The K-weighted power of the current step
+    step_power: f64,
+    /// The power of the last 30 steps
+    steps: Vec<f64>,
+    step_count: usize,
+    /// The power of each 400ms gating block
+    blocks: Vec<f64>,
+    short_term: Loudness,
+    true_peak: f64,
+}
+
+impl<W: Writer> Meter<W> {
+    pub fn new(writer: W, sample_rate: u32) -> Self {
+        Self::with_weights(writer, sample_rate, vec![1.0; W::Frame::CHANNELS])
+    }
+
+    /// Creates a meter that scales the power of each channel by its weight
+    ///
+    /// BS.1770 weights the surround channels by 1.41 (+1.5dB) and leaves out the LFE.
+    pub fn with_weights(writer: W, sample_rate: u32, weights: Vec<f64>) -> Self {
+        let channels = W::Frame::CHANNELS;
+        debug_assert_eq!(weights.len(), channels);
+        Self {
+            writer,
+            filters: vec![KWeighting::new(sample_rate as f64); channels],
+            peaks: vec![TruePeak::default(); channels],
+            weights,
+            step_len: (sample_rate as usize / 10).max(1),
+            step_pos: 0,
+            step_power: 0.0,
+            steps: vec![0.0; SHORT_TERM_STEPS],
+            step_count: 0,
+            blocks: vec![],
+            short_term: Loudness::SILENCE,
+            true_peak: 0.0,
+        }
+    }
+
+    pub fn finish(self) -> (W, Measurement) {
+        let mut short_term = self.short_term;
+
+        // signals shorter than the window are measured over their whole length
+        if self.step_count < SHORT_TERM_STEPS && self.step_count > 0 {
+            let power = self.steps[..self.step_count].iter().sum::<f64>() / self.step_count as f64;
+            short_term = gate(Loudness::from_power(power));
+        }
+
+        let measurement = Measurement {
+            integrated: integrated(&self.blocks),
+            short_term,
+            true_peak: self.true_peak,
+        };
+
+        (self.writer, measurement)
+    }
+
+    #[inline]
+    fn measure(&mut self, samples: impl Iterator<Item = f64>) {
+        for (((sample, filter), peak), weight) in samples
+            .zip(&mut self.filters)
+            .zip(&mut self.peaks)
+            .zip(&self.weights)
+        {
+            let weighted = filter.apply(sample);
+            self.step_power += weight * weighted * weighted;
+            self.true_peak = self.true_peak.max(peak.apply(sample));
+        }
+
+        self.step_pos += 1;
+        if self.step_pos == self.step_len {
+            self.finish_step();
+        }
+    }
+
+    fn finish_step(&mut self) {
+        let power = self.step_power / self.step_len as f64;
+        self.steps[self.step_count % SHORT_TERM_STEPS] = power;
+        self.step_count += 1;
+        self.step_pos = 0;
+        self.step_power = 0.0;
+
+        // gating blocks are 400ms long and overlap by 75%
+        if self.step_count >= BLOCK_STEPS {
+            let power = (0..BLOCK_STEPS)
+                .map(|offset| self.steps[(self.step_count - 1 - offset) % SHORT_TERM_STEPS])
+                .sum::<f64>()
+                / BLOCK_STEPS as f64;
+            self.blocks.push(power);
+        }
+
+        if self.step_count >= SHORT_TERM_STEPS {
+            let power = self.steps.iter().sum::<f64>() / SHORT_TERM_STEPS as f64;
+            let loudness = gate(Loudness::from_power(power));
+            if loudness > self.short_term || self.short_term.is_silent() {
+                self.short_term = loudness;
+            }
+        }
+    }
+}
+
+impl<W: Writer> Writer for Meter<W>
+where
+    W::Frame: Frame<Sample = W::Sample>,
+{
+    type Error = W::Error;
+    type Sample = W::Sample;
+    type Frame = W::Frame;
+
+    #[inline]
+    fn skip(&mut self, frames: usize) -> Result<(), Self::Error> {
+        // the filters and windows still need to see the silence
+        for _ in 0..frames {
+            self.measure(core::iter::repeat(0.0).take(W::Frame::CHANNELS));
+        }
+
+        self.writer.skip(frames)
+    }
+
+    #[inline]
+    fn write(&mut self, frame: Self::Frame) -> Result<(), Self::Error> {
+        self.measure(
+            frame
+                .channels()
+                .map(|sample| sample.to_float_sample().to_sample::<f64>()),
+        );
+
+        self.writer.write(frame)
+    }
+}
+
+const BLOCK_STEPS: usize = 4;
+const SHORT_TERM_STEPS: usize = 30;
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Treats anything below the absolute gate as silence
+fn gate(loudness: Loudness) -> Loudness {
+    if loudness.0 > ABSOLUTE_GATE {
+        loudness
+    } else {
+        Loudness::SILENCE
+    }
+}
+
+fn integrated(blocks: &[f64]) -> Loudness {
+    let mean = |threshold: f64| {
+        let mut sum = 0.0;
+        let mut count = 0;
+        for power in blocks {
+            if Loudness::from_power(*power).0 > threshold {
+                sum += power;
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            None
+        } else {
+            Some(sum / count as f64)
+        }
+    };
+
+    let Some(power) = mean(ABSOLUTE_GATE) else {
+        return Loudness::SILENCE;
+    };
+
+    let threshold = Loudness::from_power(power).0 + RELATIVE_GATE;
+    let power = mean(threshold).unwrap_or(power);
+    Loudness::from_power(power)
+}
+
+/// The pre-filter and RLB high-pass filter from BS.1770, designed for any sample rate
+#[derive(Clone, Debug)]
+struct KWeighting {
+    shelf: Biquad,
+    high_pass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: f64) -> Self {
+        use core::f64::consts::PI;
+
+        let f0 = 1681.974450955533;
+        let gain = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (PI * f0 / sample_rate).tan();
+        let vh = 10f64.powf(gain / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad::new(
+            [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Biquad::new(
+            [1.0, -2.0, 1.0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        Self { shelf, high_pass }
+    }
+
+    #[inline]
+    fn apply(&mut self, sample: f64) -> f64 {
+        self.high_pass.apply(self.shelf.apply(sample))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    state: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            state: [0.0; 2],
+        }
+    }
+
+    #[inline]
+    fn apply(&mut self, x: f64) -> f64 {
+        // transposed direct form II
+        let y = self.b[0] * x + self.state[0];
+        self.state[0] = self.b[1] * x - self.a[0] * y + self.state[1];
+        self.state[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+const OVERSAMPLE: usize = 4;
+const PHASE_TAPS: usize = 12;
+
+/// Estimates the peak between samples by oversampling with a windowed sinc interpolator
+#[derive(Clone, Debug)]
+pub(crate) struct TruePeak {
+    history: [f64; PHASE_TAPS],
+    pos: usize,
+}
+
+impl Default for TruePeak {
+    fn default() -> Self {
+        Self {
+            history: [0.0; PHASE_TAPS],
+            pos: 0,
+        }
+    }
+}
+
+impl TruePeak {
+    /// The number of samples the interpolated peaks lag behind the input
+    pub(crate) const DELAY: usize = PHASE_TAPS / 2;
+
+    /// Returns the largest absolute value between the previous sample and this one
+    #[inline]
+    pub(crate) fn apply(&mut self, sample: f64) -> f64 {
+        self.pos = (self.pos + 1) % PHASE_TAPS;
+        self.history[self.pos] = sample;
+
+        let coefficients = interpolator();
+        let mut peak = sample.abs();
+
+        for phase in coefficients {
+            let mut value = 0.0;
+            for (idx, coefficient) in phase.iter().enumerate() {
+                let pos = (self.pos + PHASE_TAPS - idx) % PHASE_TAPS;
+                value += coefficient * self.history[pos];
+            }
+            peak = peak.max(value.abs());
+        }
+
+        peak
+    }
+}
+
+type Phases = [[f64; PHASE_TAPS]; OVERSAMPLE];
+
+fn interpolator() -> &'static Phases {
+    static COEFFICIENTS: std::sync::OnceLock<Phases> = std::sync::OnceLock::new();
+
+    COEFFICIENTS.get_or_init(|| {
+        use core::f64::consts::PI;
+
+        let taps = OVERSAMPLE * PHASE_TAPS;
+        let center = (taps - 1) as f64 / 2.0;
+        let mut phases = [[0.0; PHASE_TAPS]; OVERSAMPLE];
+
+        for tap in 0..taps {
+            let m = tap as f64 - center;
+            let x = m / OVERSAMPLE as f64;
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (PI * x).sin() / (PI * x)
+            };
+            let window = 0.5 * (1.0 - (2.0 * PI * (tap as f64 + 0.5) / taps as f64).cos());
+            phases[tap % OVERSAMPLE][tap / OVERSAMPLE] = sinc * window;
+        }
+
+        phases
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Null;
+
+    impl Writer for Null {
+        type Error = ();
+        type Sample = f64;
+        type Frame = [f64; 2];
+
+        fn skip(&mut self, _frames: usize) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn write(&mut self, _frame: [f64; 2]) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    fn sine(frequency: f64, amplitude: f64, secs: f64) -> Measurement {
+        let rate = 48000;
+        let mut meter = Meter::new(Null, rate);
+        let frames = (rate as f64 * secs) as usize;
+        for idx in 0..frames {
+            let phase = idx as f64 * frequency / rate as f64;
+            let value = (phase * core::f64::consts::TAU).sin() * amplitude;
+            meter.write([value, value]).unwrap();
+        }
+        meter.finish().1
+    }
+
+    #[test]
+    fn reference_tone() {
+        // EBU Tech 3341 case 1: a -23 dBFS 1kHz stereo sine measures -23 LUFS
+        let amplitude = 10f64.powf(-23.0 / 20.0);
+        let measurement = sine(1000.0, amplitude, 20.0);
+        assert!(
+            (measurement.integrated.0 + 23.0).abs() < 0.1,
+            "{measurement:?}"
+        );
+        assert!(
+            (measurement.short_term.0 + 23.0).abs() < 0.1,
+            "{measurement:?}"
+        );
+    }
+
+    #[test]
+    fn true_peak() {
+        // samples of a quarter-rate sine offset by 45 degrees never land on the peak
+        let rate = 48000;
+        let mut meter = Meter::new(Null, rate);
+        for idx in 0..rate {
+            let phase = idx as f64 / 4.0 + 0.125;
+            let value = (phase * core::f64::consts::TAU).sin() * 0.5;
+            meter.write([value, value]).unwrap();
+        }
+        let measurement = meter.finish().1;
+        assert!(measurement.true_peak > 0.49, "{measurement:?}");
+        assert!(measurement.true_peak < 0.52, "{measurement:?}");
+    }
+
+    #[test]
+    fn channel_weights() {
+        let rate = 48000;
+        let amplitude = 10f64.powf(-23.0 / 20.0);
+        let measure = |weights: Vec<f64>| {
+            let mut meter = Meter::with_weights(Null, rate, weights);
+            for idx in 0..rate * 5 {
+                let phase = idx as f64 * 1000.0 / rate as f64;
+                let value = (phase * core::f64::consts::TAU).sin() * amplitude;
+                meter.write([0.0, value]).unwrap();
+            }
+            meter.finish().1
+        };
+
+        // a surround channel is 1.5dB louder than the same signal in a front channel
+        let front = measure(vec![1.0, 1.0]);
+        let surround = measure(vec![1.0, 1.41]);
+        let difference = surround.integrated.0 - front.integrated.0;
+        assert!((difference - 1.5).abs() < 0.05, "{front:?} {surround:?}");
+
+        // the LFE doesn't contribute to the loudness but still has a peak
+        let lfe = measure(vec![1.0, 0.0]);
+        assert!(lfe.integrated.is_silent(), "{lfe:?}");
+        assert!(lfe.true_peak > 0.0, "{lfe:?}");
+    }
+
+    #[test]
+    fn silence() {
+        let mut meter = Meter::new(Null, 48000);
+        meter.skip(48000).unwrap();
+        let measurement = meter.finish().1;
+        assert!(measurement.integrated.is_silent());
+        assert!(measurement.short_term.is_silent());
+        assert_eq!(measurement.true_peak, 0.0);
+    }
+}
@@ -1,9 +1,6 @@
 #![allow(dead_code)]
 
-use crate::{
-    frame::{Frame, N2},
-    Mixer, Writer,
-};
+use crate::{frame::Frame, Mixer, Writer};
 use euphony_dsp::sample::Sample;
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -18,6 +15,7 @@ pub struct Stereo<W: Writer> {
 impl<W: Writer> Stereo<W> {
     #[inline]
     pub fn new(writer: W) -> Self {
+        debug_assert_eq!(W::Frame::CHANNELS, 2);
         Self {
             writer,
             config: Default::default(),
@@ -35,10 +33,7 @@ impl<W: Writer> Stereo<W> {
     }
 }
 
-impl<W: Writer> Mixer for Stereo<W>
-where
-    W::Frame: Frame<NumChannels = N2>,
-{
+impl<W: Writer> Mixer for Stereo<W> {
     type Error = W::Error;
 
     #[inline]
@@ -48,6 +43,8 @@ where
 
     #[inline]
     fn mix(&mut self, samples: &[crate::SpatialSample]) -> Result<(), Self::Error> {
+        debug_assert_eq!(W::Frame::CHANNELS, 2);
+
         let mut frame = [0.0f64, 0.0f64];
         for s in samples.iter() {
             let (left, right) = s.coordinate.stereo_weights();
@@ -55,7 +52,7 @@ where
             frame[1] += s.value * right;
         }
 
-        let frame = W::Frame::from_fn(|idx| unsafe { frame.get_unchecked(idx).to_sample() });
+        let frame = W::Frame::from_fn(|idx| frame[idx].to_sample());
         self.writer.write(frame)?;
 
         Ok(())
@@ -10,6 +10,7 @@ pub type Error = String;
 
 pub type BoxProcessor = Box<dyn graph::Processor<Config>>;
 
+pub mod rate;
 mod sink;
 pub use sink::{SampleType, Sink};
 
@@ -43,6 +44,8 @@ type BufferKey = (u64, u64);
 pub struct Context {
     pub buffers: Box<dyn BufferMap>,
     pub partial: Option<usize>,
+    /// The rate nodes are processed at, in hertz
+    pub sample_rate: u32,
 }
 
 impl Default for Context {
@@ -50,6 +53,7 @@ impl Default for Context {
         Self {
             buffers: Box::new(()),
             partial: None,
+            sample_rate: rate::DEFAULT,
         }
     }
 }
@@ -279,21 +283,28 @@ impl<const I: usize, const B: usize, P: Node<I, B>> graph::Processor<Config>
             keys: &self.buffers,
         };
 
-        if let Some(partial) = context.partial {
-            let output = unsafe {
-                debug_assert!(partial <= LEN);
-                self.output.get_unchecked_mut(..partial)
-            };
-            self.processor.process(inputs, buffers, output);
-        } else {
-            self.processor
-                .process_full(inputs, buffers, &mut self.output);
-        }
+        rate::scope(context.sample_rate, || {
+            if let Some(partial) = context.partial {
+                let output = unsafe {
+                    debug_assert!(partial <= LEN);
+                    self.output.get_unchecked_mut(..partial)
+                };
+                self.processor.process(inputs, buffers, output);
+            } else {
+                self.processor
+                    .process_full(inputs, buffers, &mut self.output);
+            }
+        })
     }
 
     fn fork(&self) -> Option<BoxProcessor> {
         self.processor.fork()
     }
+
+    #[inline(never)]
+    fn finish(&mut self) {
+        self.processor.finish()
+    }
 }
 
 pub trait Node<const INPUTS: usize, const BUFFERS: usize>: 'static + Send {
@@ -303,6 +314,10 @@ pub trait Node<const INPUTS: usize, const BUFFERS: usize>: 'static + Send {
         None
     }
 
+    /// Called when the node is explicitly finished rather than dropped with the graph
+    #[inline]
+    fn finish(&mut self) {}
+
     #[inline]
     fn trigger(&mut self, param: Parameter, value: f64) -> bool {
         // no op
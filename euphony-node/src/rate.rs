@@ -0,0 +1,64 @@
+//! The sample rate of the render that's spawning or processing nodes on the current thread
+//!
+//! Nodes are constructed and processed without any arguments for the rate so it's set from the
+//! [`Context`](crate::Context) for the duration of each call instead. This keeps renders at
+//! different rates on separate threads from interfering with each other.
+
+use core::cell::Cell;
+
+/// The rate outside of a render
+pub const DEFAULT: u32 = 48_000;
+
+#[derive(Clone, Copy)]
+struct Rate {
+    count: u32,
+    period: f64,
+}
+
+impl Rate {
+    #[inline]
+    fn new(count: u32) -> Self {
+        Self {
+            count,
+            period: 1.0 / count as f64,
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Rate> = const {
+        Cell::new(Rate {
+            count: DEFAULT,
+            period: 1.0 / DEFAULT as f64,
+        })
+    };
+}
+
+/// Calls `f` with `hz` as the current rate
+#[inline]
+pub fn scope<R, F: FnOnce() -> R>(hz: u32, f: F) -> R {
+    struct Restore(Rate);
+
+    impl Drop for Restore {
+        #[inline]
+        fn drop(&mut self) {
+            CURRENT.with(|current| current.set(self.0));
+        }
+    }
+
+    let prev = CURRENT.with(|current| current.replace(Rate::new(hz)));
+    let _restore = Restore(prev);
+    f()
+}
+
+/// Returns the current rate in hertz
+#[inline]
+pub fn get() -> u32 {
+    CURRENT.with(|current| current.get().count)
+}
+
+/// Returns the duration of a sample at the current rate, in seconds
+#[inline]
+pub fn period() -> f64 {
+    CURRENT.with(|current| current.get().period)
+}
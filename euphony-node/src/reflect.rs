@@ -93,6 +93,7 @@ fn generate_api_impl<O: io::Write>(o: &mut O, mut nodes: Vec<Node>) -> io::Resul
     for (name, docs, inputs) in ext {
         let lower = name.to_snake_case();
         w!("#[inline]");
+        w!("#[track_caller]");
         w!("#[doc = {:?}]", docs);
         match inputs.len() {
             1 => w!("fn {lower}(&self) -> crate::processors::unary::{name} {{"),
@@ -143,6 +144,7 @@ fn generate_api_impl<O: io::Write>(o: &mut O, mut nodes: Vec<Node>) -> io::Resul
     for (name, docs) in filters {
         let lower = name.to_snake_case();
         w!("#[inline]");
+        w!("#[track_caller]");
         w!("#[doc = {:?}]", docs);
         w!("fn {lower}(&self) -> crate::processors::filter::{name} {{");
         w!("    crate::processors::filter::{lower}().with_signal(self)");
@@ -243,6 +245,49 @@ fn nodes_file<O: io::Write>(o: &mut O, nodes: &[Node]) -> io::Result<()> {
     w!("}}");
     w!();
 
+    w!("#[rustfmt::skip]");
+    w!("#[inline]");
+    w!("pub fn path(processor: u64) -> Option<&'static str> {{");
+    w!("    match processor {{");
+    for node in nodes {
+        w!("        {} => Some({:?}),", node.id, node.qualified_name());
+    }
+    w!("        _ => None,");
+    w!("    }}");
+    w!("}}");
+    w!();
+
+    w!("#[rustfmt::skip]");
+    w!("#[inline]");
+    w!("pub fn id(path: &str) -> Option<u64> {{");
+    w!("    match path {{");
+    for node in nodes {
+        w!("        {:?} => Some({}),", node.qualified_name(), node.id);
+    }
+    w!("        _ => None,");
+    w!("    }}");
+    w!("}}");
+    w!();
+
+    w!("#[rustfmt::skip]");
+    w!("#[inline]");
+    w!("pub fn parameter_name(processor: u64, parameter: u64) -> Option<&'static str> {{");
+    w!("    match (processor, parameter) {{");
+    for node in nodes {
+        for input in &node.inputs {
+            w!(
+                "        ({}, {}) => Some({:?}),",
+                node.id,
+                input.id,
+                input.name
+            );
+        }
+    }
+    w!("        _ => None,");
+    w!("    }}");
+    w!("}}");
+    w!();
+
     w!("#[rustfmt::skip]");
     w!("#[inline]");
     w!("pub fn validate_parameter(processor: u64, parameter: u64, value: Value) -> Result<(), Error> {{");
@@ -279,6 +324,16 @@ impl Node {
         format!("crate::{}::{}", impl_path, self.name)
     }
 
+    /// Returns the name of the node qualified by its module, e.g. `osc::nes::Pulse`
+    fn qualified_name(&self) -> String {
+        let mut name = self.module.join("::");
+        if !name.is_empty() {
+            name += "::";
+        }
+        name += &self.name;
+        name
+    }
+
     pub fn test(&self, manifest_dir: &str) {
         assert_ne!(self.id, 0, "processor id 0 is reserved for Sink");
 
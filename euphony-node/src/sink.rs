@@ -9,6 +9,12 @@ pub trait Sink: 'static + Send + Sized {
     }
 
     fn write<S: Iterator<Item = (f64, Polar<f64>)>>(&mut self, samples: S);
+
+    /// Called once all of the samples have been written
+    ///
+    /// Sinks that are dropped without being finished were cancelled and shouldn't be kept.
+    #[inline]
+    fn finish(&mut self) {}
 }
 
 #[repr(u8)]
@@ -41,6 +47,11 @@ impl<Inner: Sink> Wrapper<Inner> {
 impl<Inner: Sink> Node<4, 0> for Wrapper<Inner> {
     const DEFAULTS: [f64; 4] = [0.0, 0.0, 0.0, 0.0];
 
+    #[inline]
+    fn finish(&mut self) {
+        self.inner.finish()
+    }
+
     #[inline]
     fn process(&mut self, inputs: Inputs<4>, _buffer: Buffers<0>, samples: &mut [f64]) {
         let pcm = inputs.get(0);
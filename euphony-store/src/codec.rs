@@ -1,5 +1,33 @@
 mod reader;
 pub use reader::*;
 
+mod samples;
+
 mod writer;
 pub use writer::*;
+
+/// The encoding of the samples in a sink
+///
+/// The format is stored as a single byte after the sample and coordinate hashes. Sinks written
+/// before the format existed don't have the byte and are read as [`Format::Raw`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Each sample is stored as a native-endian `f64`
+    #[default]
+    Raw = 0,
+    /// Repeated samples are run-length encoded and the rest are stored as the XOR of their bit
+    /// pattern with the previous sample's, with any leading and trailing zero bytes omitted
+    Compressed = 1,
+}
+
+impl Format {
+    #[inline]
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Raw),
+            1 => Some(Self::Compressed),
+            _ => None,
+        }
+    }
+}
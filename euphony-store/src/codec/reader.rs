@@ -1,3 +1,4 @@
+use super::{samples::Decoder, Format};
 use crate::{ext::*, storage::Storage};
 use euphony_compiler::Hash;
 use euphony_mix::SpatialSample;
@@ -5,6 +6,8 @@ use euphony_units::coordinates::Cartesian;
 use std::io;
 
 pub struct Reader<R: io::Read> {
+    format: Format,
+    decoder: Decoder,
     sample: R,
     coordinate: R,
     coord_buffer: Option<(Cartesian<f64>, u32)>,
@@ -26,7 +29,20 @@ impl<R: io::Read> Reader<R> {
         let sample = open!();
         let coordinate = open!();
 
+        let mut format = [0u8];
+        let format = match group.read(&mut format)? {
+            0 => Format::Raw,
+            _ => Format::from_u8(format[0]).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown sink format: {}", format[0]),
+                )
+            })?,
+        };
+
         Ok(Self {
+            format,
+            decoder: Default::default(),
             sample,
             coordinate,
             coord_buffer: None,
@@ -35,7 +51,10 @@ impl<R: io::Read> Reader<R> {
 
     #[inline]
     fn read_sample(&mut self) -> io::Result<SpatialSample> {
-        let value = self.sample.read_f64()?;
+        let value = match self.format {
+            Format::Raw => self.sample.read_f64()?,
+            Format::Compressed => self.decoder.read(&mut self.sample)?,
+        };
         let coordinate = self.read_coordinate()?;
 
         Ok(SpatialSample { value, coordinate })
@@ -0,0 +1,178 @@
+use crate::storage;
+use std::io;
+
+/// Marks a run of samples that are equal to the previous sample
+///
+/// Literal headers store the number of omitted leading and trailing bytes in each nibble, which
+/// add up to at most 7, so they can never be equal to this.
+const RUN: u8 = 0xff;
+
+/// Compresses samples for [`Format::Compressed`](super::Format::Compressed)
+///
+/// The stream starts with a previous sample of `0.0` so leading silence is a single run.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    prev: u64,
+    run: u64,
+}
+
+impl Encoder {
+    #[inline]
+    pub fn write<O: storage::Output>(&mut self, sample: f64, out: &mut O) {
+        let bits = sample.to_bits();
+        let diff = bits ^ self.prev;
+
+        if diff == 0 {
+            self.run += 1;
+            return;
+        }
+
+        self.flush(out);
+        self.prev = bits;
+
+        let lead = diff.leading_zeros() as usize / 8;
+        let trail = diff.trailing_zeros() as usize / 8;
+        let bytes = diff.to_be_bytes();
+
+        let mut literal = [0u8; 9];
+        literal[0] = ((lead << 4) | trail) as u8;
+        let len = 8 - lead - trail;
+        literal[1..=len].copy_from_slice(&bytes[lead..8 - trail]);
+        out.write(&literal[..=len]);
+    }
+
+    #[inline]
+    pub fn flush<O: storage::Output>(&mut self, out: &mut O) {
+        if self.run == 0 {
+            return;
+        }
+
+        let mut run = [0u8; 11];
+        run[0] = RUN;
+        let len = write_varint(&mut run[1..], self.run);
+        out.write(&run[..=len]);
+        self.run = 0;
+    }
+}
+
+/// Decompresses samples written by an [`Encoder`]
+#[derive(Debug, Default)]
+pub struct Decoder {
+    prev: u64,
+    run: u64,
+}
+
+impl Decoder {
+    #[inline]
+    pub fn read<R: io::Read>(&mut self, input: &mut R) -> io::Result<f64> {
+        if let Some(run) = self.run.checked_sub(1) {
+            self.run = run;
+            return Ok(f64::from_bits(self.prev));
+        }
+
+        let mut header = [0u8];
+        input.read_exact(&mut header)?;
+        let header = header[0];
+
+        if header == RUN {
+            let run = read_varint(input)?;
+            self.run = run
+                .checked_sub(1)
+                .ok_or_else(|| invalid("empty sample run"))?;
+            return Ok(f64::from_bits(self.prev));
+        }
+
+        let lead = (header >> 4) as usize;
+        let trail = (header & 0xf) as usize;
+        if lead + trail > 7 {
+            return Err(invalid("invalid sample header"));
+        }
+
+        let mut bytes = [0u8; 8];
+        input.read_exact(&mut bytes[lead..8 - trail])?;
+        self.prev ^= u64::from_be_bytes(bytes);
+
+        Ok(f64::from_bits(self.prev))
+    }
+}
+
+/// Writes a LEB128 encoded value, returning the number of bytes written
+#[inline]
+fn write_varint(out: &mut [u8], mut value: u64) -> usize {
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            out[len] = byte;
+            return len + 1;
+        }
+
+        out[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+#[inline]
+fn read_varint<R: io::Read>(input: &mut R) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        input.read_exact(&mut byte)?;
+        let byte = byte[0];
+
+        value |= ((byte & 0x7f) as u64) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(invalid("sample run is too long"))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Buffer;
+
+    #[test]
+    fn round_trip() {
+        let mut samples = vec![0.0; 1000];
+        samples.extend((0..1000).map(|i| (i as f64 / 10.0).sin()));
+        samples.extend([1.0; 300]);
+        samples.extend([
+            -0.0,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::NAN,
+            f64::INFINITY,
+            0.0,
+        ]);
+        samples.extend([0.25; 200]);
+
+        let mut encoder = Encoder::default();
+        let mut out = Buffer::default();
+        for sample in &samples {
+            encoder.write(*sample, &mut out);
+        }
+        encoder.flush(&mut out);
+
+        assert!(out.0.len() < samples.len() * 8 / 2);
+
+        let mut decoder = Decoder::default();
+        let mut input = io::Cursor::new(out.0);
+        for (idx, sample) in samples.iter().enumerate() {
+            let actual = decoder.read(&mut input).unwrap();
+            assert_eq!(actual.to_bits(), sample.to_bits(), "sample {idx}");
+        }
+
+        let err = decoder.read(&mut input).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
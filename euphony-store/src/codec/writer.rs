@@ -1,3 +1,4 @@
+use super::{samples::Encoder, Format};
 use crate::{
     dc::LeakDc,
     storage::{self, Storage},
@@ -8,6 +9,8 @@ use euphony_units::coordinates::{Cartesian, Polar};
 
 pub struct Writer<O: storage::Output> {
     leak_dc: LeakDc,
+    format: Format,
+    encoder: Encoder,
     samples: O,
     coordinates: O,
     coord_buffer: Option<(Cartesian<f64>, u32)>,
@@ -21,9 +24,11 @@ impl<W: storage::Output> fmt::Debug for Writer<W> {
 }
 
 impl<O: storage::Output> Writer<O> {
-    pub fn new<S: Storage<Output = O>>(storage: &mut S, sink: O) -> Self {
+    pub fn new<S: Storage<Output = O>>(storage: &mut S, sink: O, format: Format) -> Self {
         Self {
             leak_dc: Default::default(),
+            format,
+            encoder: Default::default(),
             samples: storage.create(),
             coordinates: storage.create(),
             coord_buffer: None,
@@ -66,17 +71,23 @@ impl<W: storage::Output> Sink for Writer<W> {
     fn write<S: Iterator<Item = (f64, Polar<f64>)>>(&mut self, samples: S) {
         for (sample, coord) in samples {
             let sample = self.leak_dc.apply(sample);
-            let sample = sample.to_ne_bytes();
-            self.samples.write(&sample);
+
+            match self.format {
+                Format::Raw => self.samples.write(&sample.to_ne_bytes()),
+                Format::Compressed => self.encoder.write(sample, &mut self.samples),
+            }
 
             self.write_coord(coord.into());
         }
     }
-}
 
-impl<W: storage::Output> Drop for Writer<W> {
+    /// Writes the index to the sink
+    ///
+    /// If the writer is dropped before this, the outputs are discarded so a cancelled render
+    /// isn't mistaken for a finished one.
     #[inline]
-    fn drop(&mut self) {
+    fn finish(&mut self) {
+        self.encoder.flush(&mut self.samples);
         let a = self.samples.finish();
         self.sink.write(&a);
 
@@ -86,5 +97,12 @@ impl<W: storage::Output> Drop for Writer<W> {
 
         let b = self.coordinates.finish();
         self.sink.write(&b);
+
+        // raw sinks are written without the format so they match sinks from older versions
+        if self.format != Format::Raw {
+            self.sink.write(&[self.format as u8]);
+        }
+
+        self.sink.finish();
     }
 }
@@ -0,0 +1,89 @@
+use crate::{ext::*, storage::Storage, timeline::Timeline};
+use euphony_compiler::Hash;
+use std::{collections::HashSet, io};
+
+/// The set of stored hashes that are referenced by at least one timeline
+#[derive(Clone, Debug, Default)]
+pub struct Reachable {
+    hashes: HashSet<Hash>,
+}
+
+impl Reachable {
+    /// Marks every group, sink, midi, overview and buffer referenced by the timeline
+    ///
+    /// Missing entries are skipped so partially collected stores can still be marked.
+    pub fn mark<S: Storage>(&mut self, storage: &S, timeline: &Timeline) -> io::Result<()> {
+        for group in &timeline.groups {
+            if let Some(midi) = group.midi.as_ref() {
+                self.hashes.insert(**midi);
+            }
+
+            if let Some(overview) = group.overview.as_ref() {
+                self.hashes.insert(**overview);
+            }
+
+            if !self.hashes.insert(*group.entries) {
+                continue;
+            }
+
+            let entries = match storage.open_group(&group.entries) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+
+            for entry in entries {
+                let entry = entry?;
+                self.mark_list(storage, &entry.hash)?;
+            }
+        }
+
+        for path in &timeline.buffers {
+            let hash = crate::storage::fs::buffer_hash(path, timeline.sample_rate as _);
+            self.mark_list(storage, &hash)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Marks an entry that contains a list of hashes, along with each of the hashes
+    ///
+    /// Sinks list their samples and coordinates, followed by an optional format byte, and buffers
+    /// list each of their channels.
+    fn mark_list<S: Storage>(&mut self, storage: &S, hash: &Hash) -> io::Result<()> {
+        if !self.hashes.insert(*hash) {
+            return Ok(());
+        }
+
+        let mut list = match storage.open_raw(hash) {
+            Ok(list) => list,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        loop {
+            match list.read_hash() {
+                Ok(hash) => {
+                    self.hashes.insert(hash);
+                }
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
@@ -1,14 +1,20 @@
 use euphony_compiler::{midi, Hash, Writer};
 use euphony_mix::Mixer;
 use std::{io, path::PathBuf};
+use storage::Output;
+use timeline::{Group, Timeline};
 
 mod codec;
 mod dc;
 mod ext;
+pub mod gc;
 mod mix;
+pub mod overview;
 pub mod storage;
 pub mod timeline;
 
+pub use codec::Format;
+
 pub type DefaultStorage = storage::fs::Directory;
 pub type DefaultTimeline = timeline::Timeline;
 
@@ -37,6 +43,54 @@ impl<S: storage::Storage + Writer, T: Writer> Store<S, T> {
     ) -> io::Result<()> {
         mix::mix(&self.storage, group, mixer)
     }
+
+    /// Mixes several groups together without rendering each of them separately
+    #[inline]
+    pub fn mix_groups<M: Mixer<Error = E>, E: Into<io::Error>>(
+        &self,
+        groups: &[Hash],
+        mixer: &mut M,
+    ) -> io::Result<()> {
+        mix::mix_all(&self.storage, groups, mixer)
+    }
+
+    /// Reads the overview written when the group was measured
+    pub fn read_overview(&self, hash: &Hash) -> io::Result<overview::Overview> {
+        let reader = self.storage.open_raw(hash)?;
+        overview::Overview::read(io::BufReader::new(reader))
+    }
+}
+
+impl<S: storage::Storage + Writer> Store<S, Timeline> {
+    /// Measures the extent and peak level of each group in the timeline and writes its overview
+    ///
+    /// Groups with the same contents as one in `previous` reuse its measurements rather than
+    /// reading all of the samples again.
+    pub fn measure_groups(&mut self, previous: &[Group]) -> io::Result<()> {
+        for group in &mut self.timeline.groups {
+            if let Some(prev) = previous.iter().find(|prev| *prev.entries == *group.entries) {
+                group.start = prev.start;
+                group.end = prev.end;
+                group.peak = prev.peak;
+                group.overview.clone_from(&prev.overview);
+                continue;
+            }
+
+            let mut levels = mix::Levels::default();
+            mix::mix(&self.storage, &group.entries, &mut levels)?;
+
+            group.start = levels.start.unwrap_or(levels.end);
+            group.end = levels.end;
+            group.peak = levels.peak;
+
+            let overview = levels.overview.finish(self.timeline.sample_rate);
+            let mut output = self.storage.create();
+            overview.write(&mut output);
+            group.overview = Some(output.finish().into());
+        }
+
+        Ok(())
+    }
 }
 
 impl<S: Writer, T: Writer> Writer for Store<S, T> {
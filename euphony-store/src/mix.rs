@@ -1,5 +1,6 @@
-use crate::{storage::Storage, Hash};
+use crate::{overview, storage::Storage, Hash};
 use core::mem::size_of;
+use euphony_compiler::Entry;
 use euphony_mix::{Mixer, SpatialSample};
 use std::{collections::VecDeque, io};
 
@@ -10,8 +11,42 @@ pub fn mix<S: Storage, M: Mixer<Error = E>, E: Into<io::Error>>(
     group: &Hash,
     mixer: &mut M,
 ) -> io::Result<()> {
-    let mut sample_offset = 0;
     let group = storage.open_group(group)?;
+    mix_entries(storage, group, mixer)
+}
+
+/// Mixes several groups in lockstep, as if they were a single group
+///
+/// Only the sinks that are currently playing are held open so memory use doesn't depend on the
+/// length of the groups.
+pub fn mix_all<S: Storage, M: Mixer<Error = E>, E: Into<io::Error>>(
+    storage: &S,
+    groups: &[Hash],
+    mixer: &mut M,
+) -> io::Result<()> {
+    let mut merged = Merge { groups: vec![] };
+
+    for group in groups {
+        let mut group = storage.open_group(group)?;
+        if let Some(next) = group.next().transpose()? {
+            merged.groups.push((next, group));
+        }
+    }
+
+    mix_entries(storage, merged, mixer)
+}
+
+fn mix_entries<
+    S: Storage,
+    G: Iterator<Item = io::Result<Entry>>,
+    M: Mixer<Error = E>,
+    E: Into<io::Error>,
+>(
+    storage: &S,
+    group: G,
+    mixer: &mut M,
+) -> io::Result<()> {
+    let mut sample_offset = 0;
 
     let mut exporter: Exporter<S, M, E> = Exporter {
         samples: Vec::with_capacity(SAMPLE_CAPACITY),
@@ -37,6 +72,65 @@ pub fn mix<S: Storage, M: Mixer<Error = E>, E: Into<io::Error>>(
     Ok(())
 }
 
+/// Measures the extent and peak level of a group, along with its overview
+#[derive(Clone, Debug, Default)]
+pub struct Levels {
+    pub start: Option<u64>,
+    pub end: u64,
+    pub peak: f64,
+    pub overview: overview::Builder,
+}
+
+impl Mixer for Levels {
+    type Error = io::Error;
+
+    #[inline]
+    fn skip(&mut self, samples: usize) -> io::Result<()> {
+        self.end += samples as u64;
+        self.overview.skip(samples as u64);
+        Ok(())
+    }
+
+    #[inline]
+    fn mix(&mut self, samples: &[SpatialSample]) -> io::Result<()> {
+        self.start.get_or_insert(self.end);
+        self.end += 1;
+
+        let value: f64 = samples.iter().map(|sample| sample.value).sum();
+        self.peak = self.peak.max(value.abs());
+        self.overview.push(value);
+
+        Ok(())
+    }
+}
+
+/// Merges the entries of several groups in order of their offsets
+struct Merge<G> {
+    /// The next entry of each group, along with the rest of its entries
+    groups: Vec<(Entry, G)>,
+}
+
+impl<G: Iterator<Item = io::Result<Entry>>> Iterator for Merge<G> {
+    type Item = io::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (idx, _) = self
+            .groups
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (entry, _))| entry.sample_offset)?;
+
+        let next = match self.groups[idx].1.next() {
+            Some(Ok(next)) => next,
+            Some(Err(err)) => return Some(Err(err)),
+            None => return Some(Ok(self.groups.swap_remove(idx).0)),
+        };
+
+        let entry = core::mem::replace(&mut self.groups[idx].0, next);
+        Some(Ok(entry))
+    }
+}
+
 struct Exporter<'a, S: Storage, M: Mixer<Error = E>, E> {
     samples: Vec<SpatialSample>,
     entries: VecDeque<S::Sink>,
@@ -120,3 +214,36 @@ impl<'a, S: Storage, W: Mixer<Error = E>, E: Into<io::Error>> Exporter<'a, S, W,
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(offsets: &[u64]) -> impl Iterator<Item = io::Result<Entry>> {
+        offsets
+            .iter()
+            .map(|&sample_offset| {
+                Ok(Entry {
+                    sample_offset,
+                    hash: [sample_offset as u8; 32],
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn merge() {
+        let mut merged = Merge { groups: vec![] };
+
+        for offsets in [&[0, 10, 10, 30][..], &[5, 10, 40], &[]] {
+            let mut group = group(offsets);
+            if let Some(next) = group.next() {
+                merged.groups.push((next.unwrap(), group));
+            }
+        }
+
+        let offsets: Vec<_> = merged.map(|entry| entry.unwrap().sample_offset).collect();
+        assert_eq!(offsets, [0, 5, 10, 10, 10, 30, 40]);
+    }
+}
@@ -0,0 +1,293 @@
+//! A multi-resolution summary of the levels in a group
+//!
+//! Overviews are written to the contents directory when a group is measured so waveforms can
+//! be drawn without mixing the whole group again. The blob is little-endian and laid out as:
+//!
+//! * `b"EUOV"`
+//! * `u32` sample rate
+//! * `u32` samples summarized by each bin in the first level
+//! * `u32` bins merged into each bin of the following level
+//! * `u32` level count
+//! * for each level, a `u64` bin count followed by each bin's `f32` peak and `f32` RMS
+//!
+//! Bins start at the beginning of the timeline rather than the start of the group.
+
+use crate::storage::Output;
+use std::io;
+
+const MAGIC: [u8; 4] = *b"EUOV";
+
+/// The number of samples summarized by each bin in the first level
+pub const BLOCK: u32 = 256;
+
+/// The number of bins merged into each bin of the following level
+pub const FACTOR: u32 = 4;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overview {
+    pub sample_rate: u32,
+    pub block: u32,
+    pub factor: u32,
+    /// Each level, from the finest to the coarsest
+    pub levels: Vec<Level>,
+}
+
+impl Overview {
+    pub fn read<R: io::Read>(mut r: R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid overview header",
+            ));
+        }
+
+        let sample_rate = read_u32(&mut r)?;
+        let block = read_u32(&mut r)?;
+        let factor = read_u32(&mut r)?;
+        let count = read_u32(&mut r)?;
+
+        let mut levels = vec![];
+        let mut len = block as u64;
+
+        for _ in 0..count {
+            let mut bytes = [0; 8];
+            r.read_exact(&mut bytes)?;
+            let bins = u64::from_le_bytes(bytes);
+
+            let mut level = Level {
+                block: len,
+                bins: Vec::with_capacity(bins.min(1 << 20) as usize),
+            };
+
+            for _ in 0..bins {
+                let peak = read_f32(&mut r)?;
+                let rms = read_f32(&mut r)?;
+                level.bins.push(Bin { peak, rms });
+            }
+
+            levels.push(level);
+            len *= factor as u64;
+        }
+
+        Ok(Self {
+            sample_rate,
+            block,
+            factor,
+            levels,
+        })
+    }
+
+    pub fn write<O: Output>(&self, out: &mut O) {
+        out.write(&MAGIC);
+        out.write(&self.sample_rate.to_le_bytes());
+        out.write(&self.block.to_le_bytes());
+        out.write(&self.factor.to_le_bytes());
+        out.write(&(self.levels.len() as u32).to_le_bytes());
+
+        for level in &self.levels {
+            out.write(&(level.bins.len() as u64).to_le_bytes());
+            for bin in &level.bins {
+                out.write(&bin.peak.to_le_bytes());
+                out.write(&bin.rms.to_le_bytes());
+            }
+        }
+    }
+
+    /// Returns the coarsest level that still has at least one bin for every `samples`
+    pub fn level(&self, samples: u64) -> Option<&Level> {
+        self.levels
+            .iter()
+            .rev()
+            .find(|level| level.block <= samples)
+            .or_else(|| self.levels.first())
+    }
+
+    /// Summarizes the samples in `start..end` using the most appropriate level
+    pub fn range(&self, start: u64, end: u64) -> Bin {
+        let len = end.saturating_sub(start).max(1);
+        self.level(len)
+            .map(|level| level.range(start, end))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Level {
+    /// The number of samples summarized by each bin
+    pub block: u64,
+    pub bins: Vec<Bin>,
+}
+
+impl Level {
+    /// Summarizes the bins that overlap with the samples in `start..end`
+    pub fn range(&self, start: u64, end: u64) -> Bin {
+        let first = (start / self.block) as usize;
+        let last = end.div_ceil(self.block).max(first as u64 + 1) as usize;
+        let last = last.min(self.bins.len());
+
+        if first >= last {
+            return Bin::default();
+        }
+
+        Bin::merge(&self.bins[first..last])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bin {
+    /// The largest absolute value in the bin
+    pub peak: f32,
+    /// The root mean square of the values in the bin
+    pub rms: f32,
+}
+
+impl Bin {
+    fn merge(bins: &[Bin]) -> Self {
+        let mut peak = 0.0f32;
+        let mut squares = 0.0f64;
+
+        for bin in bins {
+            peak = peak.max(bin.peak);
+            squares += (bin.rms as f64).powi(2);
+        }
+
+        let rms = (squares / bins.len().max(1) as f64).sqrt() as f32;
+
+        Self { peak, rms }
+    }
+}
+
+/// Summarizes samples into the first level of an overview as they're mixed
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Builder {
+    bins: Vec<Bin>,
+    peak: f64,
+    squares: f64,
+    len: u32,
+}
+
+impl Builder {
+    #[inline]
+    pub fn push(&mut self, value: f64) {
+        self.peak = self.peak.max(value.abs());
+        self.squares += value * value;
+        self.len += 1;
+
+        if self.len == BLOCK {
+            self.flush();
+        }
+    }
+
+    pub fn skip(&mut self, mut samples: u64) {
+        // fill the current bin before pushing any silent ones
+        if self.len > 0 {
+            let remaining = ((BLOCK - self.len) as u64).min(samples);
+            self.len += remaining as u32;
+            samples -= remaining;
+
+            if self.len < BLOCK {
+                return;
+            }
+
+            self.flush();
+        }
+
+        let silent = samples / BLOCK as u64;
+        self.bins
+            .resize(self.bins.len() + silent as usize, Bin::default());
+        self.len = (samples % BLOCK as u64) as u32;
+    }
+
+    pub fn finish(mut self, sample_rate: u32) -> Overview {
+        if self.len > 0 {
+            self.flush();
+        }
+
+        let mut levels = vec![Level {
+            block: BLOCK as u64,
+            bins: self.bins,
+        }];
+
+        while let Some(prev) = levels.last().filter(|level| level.bins.len() > 1) {
+            let level = Level {
+                block: prev.block * FACTOR as u64,
+                bins: prev.bins.chunks(FACTOR as usize).map(Bin::merge).collect(),
+            };
+            levels.push(level);
+        }
+
+        Overview {
+            sample_rate,
+            block: BLOCK,
+            factor: FACTOR,
+            levels,
+        }
+    }
+
+    fn flush(&mut self) {
+        let rms = (self.squares / self.len as f64).sqrt();
+        self.bins.push(Bin {
+            peak: self.peak as f32,
+            rms: rms as f32,
+        });
+        self.peak = 0.0;
+        self.squares = 0.0;
+        self.len = 0;
+    }
+}
+
+fn read_u32<R: io::Read>(r: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32<R: io::Read>(r: &mut R) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Buffer;
+
+    #[test]
+    fn levels() {
+        let mut builder = Builder::default();
+        builder.skip(BLOCK as u64 * 3 + 10);
+        for _ in 0..BLOCK * 2 {
+            builder.push(-0.5);
+        }
+
+        let overview = builder.finish(48000);
+        let first = &overview.levels[0];
+        assert_eq!(first.bins.len(), 6);
+        assert_eq!(first.bins[2], Bin::default());
+        assert_eq!(first.bins[3].peak, 0.5);
+        assert_eq!(
+            first.bins[4],
+            Bin {
+                peak: 0.5,
+                rms: 0.5
+            }
+        );
+
+        // each level merges `FACTOR` bins until a single bin covers the group
+        assert_eq!(overview.levels.len(), 3);
+        assert_eq!(overview.levels[2].bins.len(), 1);
+        assert_eq!(overview.levels[2].bins[0].peak, 0.5);
+
+        let silence = overview.range(0, BLOCK as u64 * 3);
+        assert_eq!(silence, Bin::default());
+        assert_eq!(overview.range(0, BLOCK as u64 * 8).peak, 0.5);
+
+        let mut out = Buffer::default();
+        overview.write(&mut out);
+        let decoded = Overview::read(&out.0[..]).unwrap();
+        assert_eq!(decoded, overview);
+    }
+}
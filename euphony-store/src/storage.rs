@@ -2,7 +2,14 @@ use euphony_compiler::{Entry, Hash};
 use euphony_mix::SpatialSample;
 use std::io;
 
+#[cfg(feature = "remote")]
+pub mod backend;
 pub mod fs;
+#[cfg(feature = "remote")]
+pub mod remote;
+
+#[cfg(feature = "remote")]
+pub use backend::Backend;
 
 pub trait Storage {
     type Output: Output;
@@ -23,3 +30,19 @@ pub trait Output: 'static + Send + Sync {
     fn write(&mut self, bytes: &[u8]);
     fn finish(&mut self) -> Hash;
 }
+
+/// Collects the written bytes in memory
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct Buffer(pub Vec<u8>);
+
+#[cfg(test)]
+impl Output for Buffer {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn finish(&mut self) -> Hash {
+        Hash::default()
+    }
+}
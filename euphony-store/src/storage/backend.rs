@@ -0,0 +1,180 @@
+//! Selects between the local and shared stores at runtime
+
+use crate::{
+    codec,
+    storage::{
+        self,
+        fs::{self as local, Directory, GroupReader},
+        remote::{self, Remote},
+        Storage,
+    },
+    Format,
+};
+use euphony_compiler::{midi, Entry, Hash, Writer};
+use euphony_node::BoxProcessor;
+use std::{fs, io, path::Path};
+
+// both variants hold a `Directory` so they're about the same size
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum Backend {
+    Local(Directory),
+    Remote(Remote),
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::Local(Directory::default())
+    }
+}
+
+impl Backend {
+    /// Caches the entries of the shared store at `url` in the local directory
+    ///
+    /// The format of new sinks is kept.
+    pub fn share(&mut self, url: &str) {
+        let local = self.local();
+        let format = local.format();
+        let mut remote = Remote::new(local.path().to_owned(), url);
+        remote.set_format(format);
+        *self = Self::Remote(remote);
+    }
+
+    /// The directory the entries are written to
+    pub fn local(&self) -> &Directory {
+        match self {
+            Self::Local(local) => local,
+            Self::Remote(remote) => remote.local(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.local().path()
+    }
+
+    pub fn format(&self) -> Format {
+        self.local().format()
+    }
+
+    pub fn set_format(&mut self, format: Format) {
+        match self {
+            Self::Local(local) => local.set_format(format),
+            Self::Remote(remote) => remote.set_format(format),
+        }
+    }
+}
+
+impl From<Directory> for Backend {
+    fn from(local: Directory) -> Self {
+        Self::Local(local)
+    }
+}
+
+impl From<Remote> for Backend {
+    fn from(remote: Remote) -> Self {
+        Self::Remote(remote)
+    }
+}
+
+impl Storage for Backend {
+    type Output = Output;
+    type Reader = io::BufReader<fs::File>;
+    type Group = GroupReader;
+    type Sink = codec::Reader<Self::Reader>;
+
+    fn create(&self) -> Self::Output {
+        match self {
+            Self::Local(local) => Output::Local(local.create()),
+            Self::Remote(remote) => Output::Remote(remote.create()),
+        }
+    }
+
+    fn open_raw(&self, hash: &Hash) -> io::Result<Self::Reader> {
+        match self {
+            Self::Local(local) => local.open_raw(hash),
+            Self::Remote(remote) => remote.open_raw(hash),
+        }
+    }
+
+    fn open_group(&self, hash: &Hash) -> io::Result<Self::Group> {
+        match self {
+            Self::Local(local) => local.open_group(hash),
+            Self::Remote(remote) => remote.open_group(hash),
+        }
+    }
+
+    fn open_sink(&self, hash: &Hash) -> io::Result<Self::Sink> {
+        match self {
+            Self::Local(local) => local.open_sink(hash),
+            Self::Remote(remote) => remote.open_sink(hash),
+        }
+    }
+}
+
+impl Writer for Backend {
+    fn is_cached(&self, hash: &Hash) -> bool {
+        match self {
+            Self::Local(local) => local.is_cached(hash),
+            Self::Remote(remote) => remote.is_cached(hash),
+        }
+    }
+
+    fn sink(&mut self, hash: &Hash) -> BoxProcessor {
+        match self {
+            Self::Local(local) => local.sink(hash),
+            Self::Remote(remote) => remote.sink(hash),
+        }
+    }
+
+    fn group<I: Iterator<Item = Entry>>(
+        &mut self,
+        name: &str,
+        hash: &Hash,
+        entries: I,
+        midi: &midi::Writer,
+    ) {
+        match self {
+            Self::Local(local) => local.group(name, hash, entries, midi),
+            Self::Remote(remote) => remote.group(name, hash, entries, midi),
+        }
+    }
+
+    fn buffer<
+        F: FnOnce(
+            Box<dyn euphony_compiler::BufferReader>,
+        ) -> euphony_compiler::Result<Vec<euphony_compiler::ConvertedBuffer>, E>,
+        E,
+    >(
+        &self,
+        path: &str,
+        sample_rate: u64,
+        init: F,
+    ) -> euphony_compiler::Result<Vec<euphony_compiler::CachedBuffer>, E> {
+        self.local().buffer(path, sample_rate, init)
+    }
+}
+
+// both variants hold a local output so they're about the same size
+#[allow(clippy::large_enum_variant)]
+pub enum Output {
+    Local(local::Output),
+    Remote(remote::Output),
+}
+
+impl storage::Output for Output {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Local(output) => output.write(bytes),
+            Self::Remote(output) => output.write(bytes),
+        }
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Hash {
+        match self {
+            Self::Local(output) => output.finish(),
+            Self::Remote(output) => output.finish(),
+        }
+    }
+}
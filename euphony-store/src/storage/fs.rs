@@ -1,5 +1,5 @@
 use crate::{
-    codec,
+    codec::{self, Format},
     ext::*,
     storage::{self, Output as _, Storage},
 };
@@ -21,6 +21,7 @@ pub type File = io::BufWriter<fs::File>;
 #[derive(Clone, Debug)]
 pub struct Directory {
     state: State,
+    format: Format,
 }
 
 #[derive(Clone, Debug)]
@@ -46,6 +47,8 @@ enum OState {
     PreHashed {
         file: File,
         hash: Hash,
+        path: Option<TempPath>,
+        target: PathBuf,
     },
     Incremental {
         file: fs::File,
@@ -120,7 +123,17 @@ impl storage::Output for Output {
     #[inline]
     fn finish(&mut self) -> Hash {
         let result = match &mut self.0 {
-            OState::PreHashed { file, hash } => file.flush().map(|_| *hash),
+            OState::PreHashed {
+                file,
+                hash,
+                path,
+                target,
+            } => {
+                let tmp_path = path.take().expect("cannot finalize twice");
+
+                file.flush()
+                    .and_then(|_| tmp_path.persist(target).map(|_| *hash).map_err(|e| e.error))
+            }
             OState::Incremental {
                 file,
                 buffer,
@@ -160,19 +173,49 @@ impl Directory {
                 path: Arc::new(path),
                 hasher: Hasher::new(),
             },
+            format: Format::default(),
         }
     }
 
+    /// The format that new sinks are written in
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
     pub fn path(&self) -> &Path {
         &self.state.path
     }
 
-    fn hash_path(&self, hash: &Hash) -> PathBuf {
-        let mut out = [b'A'; 64];
-        let len = BASE64_URL_SAFE_NO_PAD.encode_slice(hash, &mut out).unwrap();
-        let out = unsafe { core::str::from_utf8_unchecked_mut(&mut out) };
-        let out = &out[..len];
-        self.state.path.join(out)
+    pub fn hash_path(&self, hash: &Hash) -> PathBuf {
+        self.state.hash_path(hash)
+    }
+
+    /// Lists all of the files in the directory that are named by their hash
+    pub fn list(&self) -> io::Result<Vec<(Hash, fs::DirEntry)>> {
+        let mut entries = vec![];
+
+        for entry in fs::read_dir(self.path())? {
+            let entry = entry?;
+
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+
+            // the decoder requires room for its estimate of the length, which is rounded up
+            let mut out = [0u8; 48];
+            if let Ok(len) = BASE64_URL_SAFE_NO_PAD.decode_slice(name, &mut out) {
+                if let Ok(hash) = out[..len].try_into() {
+                    entries.push((hash, entry));
+                }
+            }
+        }
+
+        Ok(entries)
     }
 
     fn open(&self, hash: &Hash) -> io::Result<Option<File>> {
@@ -192,6 +235,26 @@ impl Directory {
             })
     }
 
+    /// Creates an output stored under `hash` rather than the hash of its contents
+    ///
+    /// Returns `None` if the entry already exists. The entry is only created once the output is
+    /// finished so dropping it beforehand leaves nothing behind.
+    pub(crate) fn keyed_output(&self, hash: &Hash) -> io::Result<Option<Output>> {
+        let target = self.hash_path(hash);
+        if target.exists() {
+            return Ok(None);
+        }
+
+        let (file, path) = NamedTempFile::new_in(self.path())?.into_parts();
+        let file = io::BufWriter::new(file);
+        Ok(Some(Output(OState::PreHashed {
+            file,
+            hash: *hash,
+            path: Some(path),
+            target,
+        })))
+    }
+
     fn write_group<I: Iterator<Item = Entry>>(file: Option<File>, entries: I) -> io::Result<()> {
         if let Some(mut file) = file {
             for entry in entries {
@@ -257,9 +320,9 @@ impl Writer for Directory {
     }
 
     fn sink(&mut self, hash: &Hash) -> BoxProcessor {
-        if let Some(file) = self.open(hash).unwrap() {
-            let output = Output(OState::PreHashed { file, hash: *hash });
-            codec::Writer::new(self, output).spawn()
+        if let Some(output) = self.keyed_output(hash).unwrap() {
+            let format = self.format;
+            codec::Writer::new(self, output, format).spawn()
         } else {
             NoopSink.spawn()
         }
@@ -295,10 +358,7 @@ impl Writer for Directory {
         sample_rate: u64,
         init: F,
     ) -> euphony_compiler::Result<Vec<euphony_compiler::CachedBuffer>, E> {
-        let mut hasher = Hasher::new();
-        hasher.update(&sample_rate.to_le_bytes());
-        hasher.update(path.as_bytes());
-        let path_hash = *hasher.finalize().as_bytes();
+        let path_hash = buffer_hash(path, sample_rate);
         let contents = std::fs::File::open(path).unwrap();
 
         let mut buffers = vec![];
@@ -346,6 +406,14 @@ impl Writer for Directory {
     }
 }
 
+/// Returns the hash of the file listing the converted channels of the buffer at `path`
+pub fn buffer_hash(path: &str, sample_rate: u64) -> Hash {
+    let mut hasher = Hasher::new();
+    hasher.update(&sample_rate.to_le_bytes());
+    hasher.update(path.as_bytes());
+    *hasher.finalize().as_bytes()
+}
+
 fn read_result<T>(result: io::Result<T>) -> io::Result<Option<T>> {
     match result {
         Ok(value) => Ok(Some(value)),
@@ -354,7 +422,7 @@ fn read_result<T>(result: io::Result<T>) -> io::Result<Option<T>> {
     }
 }
 
-struct NoopSink;
+pub(crate) struct NoopSink;
 
 impl Sink for NoopSink {
     #[inline]
@@ -378,3 +446,111 @@ impl Iterator for GroupReader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use euphony_compiler::{BufferReader, CachedBuffer, Compiler, ConvertedBuffer, Progress};
+    use std::io::Cursor;
+
+    /// Cancels the compilation once `cancel_at` sinks have been spawned
+    struct Cancel {
+        dir: Directory,
+        progress: Progress,
+        cancel_at: usize,
+        rendered: usize,
+    }
+
+    impl Writer for Cancel {
+        fn is_cached(&self, hash: &Hash) -> bool {
+            self.dir.is_cached(hash)
+        }
+
+        fn sink(&mut self, hash: &Hash) -> BoxProcessor {
+            self.rendered += 1;
+            if self.rendered == self.cancel_at {
+                self.progress.cancel();
+            }
+            self.dir.sink(hash)
+        }
+
+        fn group<I: Iterator<Item = Entry>>(
+            &mut self,
+            name: &str,
+            hash: &Hash,
+            entries: I,
+            midi: &midi::Writer,
+        ) {
+            self.dir.group(name, hash, entries, midi)
+        }
+
+        fn buffer<F: FnOnce(Box<dyn BufferReader>) -> Result<Vec<ConvertedBuffer>, E>, E>(
+            &self,
+            path: &str,
+            sample_rate: u64,
+            init: F,
+        ) -> Result<Vec<CachedBuffer>, E> {
+            self.dir.buffer(path, sample_rate, init)
+        }
+    }
+
+    #[test]
+    fn cancelled_sinks() {
+        let input = euphony_command::asm::assemble(
+            r#"
+            GROUP id = 1, name = "a"
+            SPAWN id = 0, processor = sink, group = 1
+            SPAWN id = 1, processor = osc::Sine
+            SET node = 1, param = 0, value = 440
+            PIPE node = 0, param = 0, source = 1
+            ADVANCE ticks = 64
+            SPAWN id = 2, processor = sink, group = 1
+            PIPE node = 2, param = 0, source = 1
+            ADVANCE ticks = 64
+            FIN node = 1
+            FIN node = 0
+            FIN node = 2
+            "#,
+            &euphony_compiler::Processors,
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut compiler = Compiler::default();
+        let progress = compiler.progress();
+        let mut output = Cancel {
+            dir: Directory::new(dir.path().to_owned()),
+            progress: progress.clone(),
+            cancel_at: 2,
+            rendered: 0,
+        };
+
+        // the first sink has written samples by the time the second one is spawned
+        let err = compiler
+            .compile(&mut Cursor::new(&input), &mut output)
+            .unwrap_err();
+        assert!(matches!(err, euphony_compiler::Error::Cancelled));
+        assert_eq!(output.rendered, 2);
+
+        // nothing was left behind for the cancelled sinks
+        assert!(output.dir.list().unwrap().is_empty());
+
+        // so the next compilation renders them again
+        progress.reset();
+        output.cancel_at = 0;
+        output.rendered = 0;
+        compiler
+            .compile(&mut Cursor::new(&input), &mut output)
+            .unwrap();
+        assert_eq!(output.rendered, 2);
+        assert_eq!(progress.snapshot().sinks_rendered, 2);
+        assert!(!output.dir.list().unwrap().is_empty());
+
+        // and the finished sinks are cached afterwards
+        output.rendered = 0;
+        compiler
+            .compile(&mut Cursor::new(&input), &mut output)
+            .unwrap();
+        assert_eq!(output.rendered, 0);
+    }
+}
@@ -0,0 +1,546 @@
+//! A local cache layered over a shared, content-addressed store
+//!
+//! The shared store is reached over HTTP, with each entry addressed by its base64 encoded hash:
+//!
+//! * `GET {url}/{hash}` returns the entry or `404` if it doesn't exist
+//! * `HEAD {url}/{hash}` checks if the entry exists
+//! * `PUT {url}/{hash}` stores the request body as the entry
+//!
+//! Entries are downloaded into the local cache before being read so they're only fetched once.
+//! Sinks and groups are only uploaded once everything they reference has been, so other machines
+//! never find an index they can't read.
+
+use crate::{
+    codec::{self, Format},
+    storage::{
+        self,
+        fs::{self as local, Directory, GroupReader, NoopSink},
+        Storage,
+    },
+};
+use base64::prelude::*;
+use blake3::Hasher;
+use euphony_compiler::{midi, Entry, Hash, Writer};
+use euphony_node::{BoxProcessor, Sink};
+use reqwest::{blocking::Client, StatusCode};
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tempfile::NamedTempFile;
+
+#[derive(Clone, Debug)]
+pub struct Remote {
+    local: Directory,
+    client: Client,
+    url: Arc<str>,
+    /// The entries that couldn't be uploaded, which keep any indexes that reference them local
+    failed: Arc<Mutex<HashSet<Hash>>>,
+}
+
+/// The kind of entry being downloaded, which determines how it's verified
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    /// The entry is addressed by the hash of its contents
+    Content,
+    /// The entry is stored under a key and contains a list of fixed size records
+    Index { record_len: u64 },
+    /// The entry is stored under a key and contains the hashes of the samples and coordinates,
+    /// optionally followed by the format
+    Sink,
+}
+
+/// Groups list the sample offset and hash of each sink
+const GROUP: Kind = Kind::Index { record_len: 40 };
+
+impl Remote {
+    /// Creates a store that caches the entries at `url` in the `path` directory
+    pub fn new(path: PathBuf, url: &str) -> Self {
+        Self {
+            local: Directory::new(path),
+            client: Client::new(),
+            url: url.trim_end_matches('/').into(),
+            failed: Default::default(),
+        }
+    }
+
+    pub fn local(&self) -> &Directory {
+        &self.local
+    }
+
+    /// Sets the format that new sinks are written in
+    pub fn set_format(&mut self, format: Format) {
+        self.local.set_format(format);
+    }
+
+    fn url(&self, hash: &Hash) -> String {
+        format!("{}/{}", self.url, BASE64_URL_SAFE_NO_PAD.encode(hash))
+    }
+
+    /// Returns `true` if the shared store contains the entry
+    fn contains(&self, hash: &Hash) -> io::Result<bool> {
+        let res = self
+            .client
+            .head(self.url(hash))
+            .send()
+            .map_err(io::Error::other)?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+
+        res.error_for_status().map_err(io::Error::other)?;
+        Ok(true)
+    }
+
+    /// Downloads the entry into the local cache, if it isn't there already
+    fn fetch(&self, hash: &Hash, kind: Kind) -> io::Result<()> {
+        let path = self.local.hash_path(hash);
+        if path.exists() {
+            return Ok(());
+        }
+
+        let mut res = self
+            .client
+            .get(self.url(hash))
+            .send()
+            .map_err(io::Error::other)?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} was not found", self.url(hash)),
+            ));
+        }
+
+        res = res.error_for_status().map_err(io::Error::other)?;
+
+        // download next to the final path so it can be moved into place once verified
+        let mut out = HashWriter {
+            file: NamedTempFile::new_in(self.local.path())?,
+            hasher: Hasher::new(),
+            len: 0,
+        };
+        res.copy_to(&mut out).map_err(io::Error::other)?;
+
+        let is_valid = match kind {
+            Kind::Content => out.hasher.finalize().as_bytes() == hash,
+            Kind::Index { record_len } => out.len % record_len == 0,
+            Kind::Sink => matches!(out.len, 64 | 65),
+        };
+
+        if !is_valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} returned corrupted contents", self.url(hash)),
+            ));
+        }
+
+        out.file.persist(path).map_err(|err| err.error)?;
+
+        Ok(())
+    }
+
+    /// Downloads an index along with all of the entries it references
+    fn fetch_sink(&self, hash: &Hash) -> io::Result<()> {
+        self.fetch(hash, Kind::Sink)?;
+
+        let index = fs::read(self.local.hash_path(hash))?;
+        for hash in index.chunks_exact(32) {
+            self.fetch(hash.try_into().unwrap(), Kind::Content)?;
+        }
+
+        Ok(())
+    }
+
+    /// Uploads an entry from the local cache to the shared store
+    fn upload(&self, hash: &Hash) -> io::Result<()> {
+        let contents = fs::read(self.local.hash_path(hash))?;
+        self.client
+            .put(self.url(hash))
+            .body(contents)
+            .send()
+            .and_then(|res| res.error_for_status())
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    /// Uploads an entry, logging any errors since the shared store is only a cache
+    fn share(&self, hash: &Hash) {
+        let result = self.upload(hash);
+        let mut failed = self.failed.lock().unwrap();
+        if let Err(err) = result {
+            log::warn!("could not upload {}: {}", self.url(hash), err);
+            failed.insert(*hash);
+        } else {
+            failed.remove(hash);
+        }
+    }
+
+    /// Uploads an index once all of the entries it references have been uploaded
+    fn share_index<I: IntoIterator<Item = Hash>>(&self, hash: &Hash, references: I) {
+        let is_missing = {
+            let failed = self.failed.lock().unwrap();
+            references.into_iter().any(|hash| failed.contains(&hash))
+        };
+
+        if is_missing {
+            log::warn!(
+                "not uploading {} since its entries couldn't be uploaded",
+                self.url(hash)
+            );
+            self.failed.lock().unwrap().insert(*hash);
+            return;
+        }
+
+        self.share(hash);
+    }
+
+    /// Uploads a sink index along with the samples and coordinates it lists
+    fn share_sink(&self, hash: &Hash) {
+        match fs::read(self.local.hash_path(hash)) {
+            Ok(index) => {
+                let references = index.chunks_exact(32).map(|hash| hash.try_into().unwrap());
+                self.share_index(hash, references)
+            }
+            Err(err) => log::warn!("could not read sink {}: {}", self.url(hash), err),
+        }
+    }
+
+    /// Uploads a group along with the sinks it lists
+    fn share_group(&self, hash: &Hash) {
+        match fs::read(self.local.hash_path(hash)) {
+            Ok(group) => {
+                let references = group
+                    .chunks_exact(40)
+                    .map(|record| record[8..].try_into().unwrap());
+                self.share_index(hash, references)
+            }
+            Err(err) => log::warn!("could not read group {}: {}", self.url(hash), err),
+        }
+    }
+}
+
+impl Storage for Remote {
+    type Output = Output;
+    type Reader = io::BufReader<fs::File>;
+    type Group = GroupReader;
+    type Sink = codec::Reader<Self::Reader>;
+
+    fn create(&self) -> Self::Output {
+        Output {
+            inner: self.local.create(),
+            remote: self.clone(),
+            key: None,
+        }
+    }
+
+    fn open_raw(&self, hash: &Hash) -> io::Result<Self::Reader> {
+        self.fetch(hash, Kind::Content)?;
+        self.local.open_raw(hash)
+    }
+
+    fn open_group(&self, hash: &Hash) -> io::Result<Self::Group> {
+        self.fetch(hash, GROUP)?;
+        self.local.open_group(hash)
+    }
+
+    fn open_sink(&self, hash: &Hash) -> io::Result<Self::Sink> {
+        self.fetch_sink(hash)?;
+        self.local.open_sink(hash)
+    }
+}
+
+impl Writer for Remote {
+    fn is_cached(&self, hash: &Hash) -> bool {
+        if self.local.is_cached(hash) {
+            return true;
+        }
+
+        // another machine may have already rendered the sink
+        match self.contains(hash) {
+            Ok(is_cached) => is_cached,
+            Err(err) => {
+                log::warn!("could not reach {}: {}", self.url, err);
+                false
+            }
+        }
+    }
+
+    fn sink(&mut self, hash: &Hash) -> BoxProcessor {
+        let output = self.local.keyed_output(hash).unwrap().map(|inner| Output {
+            inner,
+            remote: self.clone(),
+            key: Some(*hash),
+        });
+
+        if let Some(output) = output {
+            let format = self.local.format();
+            codec::Writer::new(self, output, format).spawn()
+        } else {
+            NoopSink.spawn()
+        }
+    }
+
+    fn group<I: Iterator<Item = Entry>>(
+        &mut self,
+        name: &str,
+        hash: &Hash,
+        entries: I,
+        midi: &midi::Writer,
+    ) {
+        self.local.group(name, hash, entries, midi);
+
+        self.share_group(hash);
+        if !midi.is_empty() {
+            self.share(midi.hash());
+        }
+    }
+
+    fn buffer<
+        F: FnOnce(
+            Box<dyn euphony_compiler::BufferReader>,
+        ) -> euphony_compiler::Result<Vec<euphony_compiler::ConvertedBuffer>, E>,
+        E,
+    >(
+        &self,
+        path: &str,
+        sample_rate: u64,
+        init: F,
+    ) -> euphony_compiler::Result<Vec<euphony_compiler::CachedBuffer>, E> {
+        // buffers are converted from local files so there's nothing to share
+        self.local.buffer(path, sample_rate, init)
+    }
+}
+
+/// Writes to the local cache and uploads the entry once it's finished
+pub struct Output {
+    inner: local::Output,
+    remote: Remote,
+    /// The key of a sink index, which is only uploaded once the entries it lists have been
+    key: Option<Hash>,
+}
+
+impl storage::Output for Output {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.write(bytes);
+    }
+
+    #[inline]
+    fn finish(&mut self) -> Hash {
+        let hash = self.inner.finish();
+        if self.key.is_some() {
+            self.remote.share_sink(&hash);
+        } else {
+            self.remote.share(&hash);
+        }
+        hash
+    }
+}
+
+struct HashWriter {
+    file: NamedTempFile,
+    hasher: Hasher,
+    len: u64,
+}
+
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.file.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        self.len += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Output as _;
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader, Read},
+        net::TcpListener,
+        sync::Mutex,
+        thread,
+    };
+
+    type Entries = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Serves the shared store protocol from memory
+    fn serve() -> (String, Entries) {
+        serve_with(|_| true)
+    }
+
+    /// Serves the shared store protocol from memory, only accepting uploads that pass `accept`
+    fn serve_with(accept: fn(&[u8]) -> bool) -> (String, Entries) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/contents", listener.local_addr().unwrap());
+        let entries = Entries::default();
+
+        let state = entries.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                let mut request = request.split_whitespace();
+                let method = request.next().unwrap().to_string();
+                let path = request.next().unwrap().to_string();
+
+                let mut len = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim().to_ascii_lowercase();
+                    if header.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.strip_prefix("content-length:") {
+                        len = value.trim().parse().unwrap();
+                    }
+                }
+
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut entries = state.lock().unwrap();
+                let (status, body) = match (method.as_str(), entries.get(&path)) {
+                    ("PUT", _) if !accept(&body) => ("500 Internal Server Error", vec![]),
+                    ("PUT", _) => {
+                        entries.insert(path, body);
+                        ("200 OK", vec![])
+                    }
+                    ("GET", Some(body)) => ("200 OK", body.clone()),
+                    ("HEAD", Some(_)) => ("200 OK", vec![]),
+                    _ => ("404 Not Found", vec![]),
+                };
+
+                let len = if method == "HEAD" { 0 } else { body.len() };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\ncontent-length: {len}\r\nconnection: close\r\n\r\n"
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+
+        (url, entries)
+    }
+
+    #[test]
+    fn shared_entries() {
+        let (url, entries) = serve();
+        let a_dir = tempfile::tempdir().unwrap();
+        let b_dir = tempfile::tempdir().unwrap();
+        let a = Remote::new(a_dir.path().to_owned(), &url);
+        let b = Remote::new(b_dir.path().to_owned(), &url);
+
+        let mut out = a.create();
+        out.write(b"hello");
+        let hash = out.finish();
+        assert_eq!(&hash, blake3::hash(b"hello").as_bytes());
+        assert_eq!(entries.lock().unwrap().len(), 1);
+
+        // the second machine downloads the entry from the shared store
+        assert!(!b.local().hash_path(&hash).exists());
+        let mut contents = vec![];
+        b.open_raw(&hash)
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        assert_eq!(contents, b"hello");
+        assert!(b.local().hash_path(&hash).exists());
+
+        let missing = [1; 32];
+        let err = b.open_raw(&missing).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn shared_sinks() {
+        let (url, _entries) = serve();
+        let a_dir = tempfile::tempdir().unwrap();
+        let b_dir = tempfile::tempdir().unwrap();
+        let mut a = Remote::new(a_dir.path().to_owned(), &url);
+        let b = Remote::new(b_dir.path().to_owned(), &url);
+
+        let hash = [2; 32];
+        assert!(!b.is_cached(&hash));
+
+        a.sink(&hash).finish();
+
+        // the sink was rendered by the first machine
+        assert!(b.is_cached(&hash));
+        assert!(b.open_sink(&hash).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn failed_uploads() {
+        // nothing is written to the sink so its empty samples and coordinates fail to upload
+        let (url, entries) = serve_with(|body| !body.is_empty());
+        let dir = tempfile::tempdir().unwrap();
+        let mut remote = Remote::new(dir.path().to_owned(), &url);
+
+        let hash = [4; 32];
+        remote.sink(&hash).finish();
+        assert!(remote.local().is_cached(&hash));
+        assert!(entries.lock().unwrap().is_empty());
+
+        // groups that list the sink aren't shared either
+        let group = [5; 32];
+        let entry = Entry {
+            sample_offset: 0,
+            hash,
+        };
+        let midi = midi::Writer::default();
+        remote.group("a", &group, [entry].into_iter(), &midi);
+        assert!(remote.local().hash_path(&group).exists());
+        assert!(entries.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn backend() {
+        let (url, entries) = serve();
+        let dir = tempfile::tempdir().unwrap();
+        let mut backend = storage::Backend::from(Directory::new(dir.path().to_owned()));
+        backend.set_format(Format::Compressed);
+
+        backend.share(&url);
+        assert!(matches!(backend, storage::Backend::Remote(_)));
+        assert_eq!(backend.format(), Format::Compressed);
+        assert_eq!(backend.path(), dir.path());
+
+        let mut out = backend.create();
+        out.write(b"hello");
+        let hash = out.finish();
+        assert!(backend.local().hash_path(&hash).exists());
+        assert_eq!(entries.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn corrupted_entries() {
+        let (url, entries) = serve();
+        let dir = tempfile::tempdir().unwrap();
+        let remote = Remote::new(dir.path().to_owned(), &url);
+
+        let hash = *blake3::hash(b"hello").as_bytes();
+        let path = format!("/contents/{}", BASE64_URL_SAFE_NO_PAD.encode(hash));
+        entries.lock().unwrap().insert(path, b"goodbye".to_vec());
+
+        let err = remote.open_raw(&hash).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(!remote.local().hash_path(&hash).exists());
+    }
+}
@@ -1,28 +1,56 @@
-use euphony_compiler::{
-    midi,
-    sample::{DefaultRate, Rate as _},
-    Hash, Writer,
-};
+use euphony_compiler::{midi, sample::SampleRate, Annotations, Hash, Writer};
 use serde::{Deserialize, Serialize};
 use std::{io, ops};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Timeline {
+    /// The version of the timeline format
+    ///
+    /// Timelines written before the version was introduced are read as version `1`.
+    #[serde(default = "legacy_version")]
+    pub version: u32,
     pub sample_rate: u32,
+    /// The tempo at the start of the composition, in beats per minute
+    #[serde(default = "default_tempo")]
+    pub tempo: f64,
+    /// Each change in tempo, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tempo_map: Vec<TempoChange>,
+    /// Each change in time signature, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_signatures: Vec<TimeSignature>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<Marker>,
     pub groups: Vec<Group>,
+    /// The paths of the buffers loaded by the composition
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buffers: Vec<String>,
+    /// The loudness of the last render
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loudness: Option<Loudness>,
 }
 
 impl Default for Timeline {
     #[inline]
     fn default() -> Self {
         Self {
-            sample_rate: DefaultRate::COUNT as _,
+            version: Self::VERSION,
+            sample_rate: SampleRate::DEFAULT.into(),
+            tempo: default_tempo(),
+            tempo_map: Default::default(),
+            time_signatures: Default::default(),
+            markers: Default::default(),
             groups: Default::default(),
+            buffers: Default::default(),
+            loudness: Default::default(),
         }
     }
 }
 
 impl Timeline {
+    /// The version of the timeline format written by this crate
+    pub const VERSION: u32 = 2;
+
     #[inline]
     pub fn to_json<W: io::Write>(&self, w: W) -> io::Result<()> {
         serde_json::to_writer(w, self)?;
@@ -31,9 +59,121 @@ impl Timeline {
 
     #[inline]
     pub fn reset(&mut self) {
-        self.sample_rate = DefaultRate::COUNT as _;
+        self.version = Self::VERSION;
+        self.sample_rate = SampleRate::DEFAULT.into();
+        self.tempo = default_tempo();
+        self.tempo_map.clear();
+        self.time_signatures.clear();
+        self.markers.clear();
         self.groups.clear();
+        self.buffers.clear();
+        self.loudness = None;
     }
+
+    /// Returns the tempo map, starting with the initial tempo if it wasn't recorded
+    pub fn tempo_changes(&self) -> Vec<TempoChange> {
+        if self
+            .tempo_map
+            .first()
+            .is_some_and(|change| change.sample == 0)
+        {
+            return self.tempo_map.clone();
+        }
+
+        let start = TempoChange {
+            sample: 0,
+            beat: 0.0,
+            bpm: self.tempo,
+            ramp: false,
+        };
+        core::iter::once(start)
+            .chain(self.tempo_map.iter().cloned())
+            .collect()
+    }
+
+    /// Copies the tempo map, time signatures and markers from a compilation
+    pub fn set_annotations(&mut self, annotations: &Annotations) {
+        self.tempo_map = annotations
+            .tempo_map
+            .iter()
+            .map(|change| TempoChange {
+                sample: change.sample,
+                beat: change.beat,
+                bpm: change.bpm,
+                ramp: change.ramp,
+            })
+            .collect();
+
+        self.time_signatures = annotations
+            .time_signatures
+            .iter()
+            .map(|change| TimeSignature {
+                sample: change.sample,
+                beat: change.beat,
+                beats: change.beats,
+                unit: change.unit,
+            })
+            .collect();
+
+        self.markers = annotations
+            .markers
+            .iter()
+            .map(|marker| Marker {
+                sample: marker.sample,
+                beat: marker.beat,
+                name: marker.name.clone(),
+            })
+            .collect();
+    }
+}
+
+fn legacy_version() -> u32 {
+    1
+}
+
+fn default_tempo() -> f64 {
+    120.0
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TempoChange {
+    pub sample: u64,
+    pub beat: f64,
+    /// The tempo in beats per minute
+    pub bpm: f64,
+    /// The tempo changes linearly until the next change, otherwise it's held
+    #[serde(default)]
+    pub ramp: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimeSignature {
+    pub sample: u64,
+    pub beat: f64,
+    /// The number of beats in each bar
+    pub beats: u64,
+    /// The note value of each beat, e.g. `4` for quarter notes
+    pub unit: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub sample: u64,
+    pub beat: f64,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Loudness {
+    /// The integrated loudness in LUFS
+    pub integrated: f64,
+    /// The loudest 3 second window in LUFS
+    pub short_term: f64,
+    /// The true peak in dBTP
+    pub true_peak: f64,
+    /// The gain applied to normalize the render in dB
+    #[serde(default)]
+    pub gain: f64,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -42,6 +182,20 @@ pub struct Group {
     pub entries: HashDisplay,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub midi: Option<HashDisplay>,
+    /// The first sample rendered by the group
+    #[serde(default)]
+    pub start: u64,
+    /// The sample after the last one rendered by the group
+    #[serde(default)]
+    pub end: u64,
+    /// The largest absolute value of the group's sinks summed into a single channel
+    #[serde(default)]
+    pub peak: f64,
+    /// The summary of the group's levels at multiple resolutions
+    ///
+    /// See [`crate::overview`] for the format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overview: Option<HashDisplay>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -55,6 +209,12 @@ impl ops::Deref for HashDisplay {
     }
 }
 
+impl From<Hash> for HashDisplay {
+    fn from(hash: Hash) -> Self {
+        Self(hash)
+    }
+}
+
 mod base64 {
     use ::base64::prelude::*;
     use euphony_compiler::Hash;
@@ -83,16 +243,17 @@ mod base64 {
     {
         if deserializer.is_human_readable() {
             let s = <&str>::deserialize(deserializer)?;
-            let mut out = Hash::default();
+            // the decoder requires room for its estimate of the length, which is rounded up
+            let mut out = [0u8; 48];
             let len = BASE64_URL_SAFE_NO_PAD
                 .decode_slice(s, &mut out)
                 .map_err(serde::de::Error::custom)?;
 
-            if len != out.len() {
-                return Err(serde::de::Error::custom("invalid hash length"));
-            }
+            let hash = out[..len]
+                .try_into()
+                .map_err(|_| serde::de::Error::custom("invalid hash length"))?;
 
-            Ok(out)
+            Ok(hash)
         } else {
             Hash::deserialize(deserializer)
         }
@@ -126,6 +287,8 @@ impl Writer for Timeline {
             } else {
                 Some(HashDisplay(*midi.hash()))
             },
+            // the levels are measured once the sinks are rendered
+            ..Default::default()
         });
     }
 
@@ -143,3 +306,33 @@ impl Writer for Timeline {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_json() {
+        let json = r#"{
+            "sample_rate": 48000,
+            "groups": [{
+                "name": "lead",
+                "entries": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"
+            }]
+        }"#;
+
+        let timeline: Timeline = serde_json::from_str(json).unwrap();
+        assert_eq!(timeline.version, 1);
+        assert_eq!(timeline.tempo, 120.0);
+        assert!(timeline.tempo_map.is_empty());
+        assert!(timeline.markers.is_empty());
+        assert_eq!(timeline.groups[0].name, "lead");
+        assert_eq!(timeline.groups[0].end, 0);
+
+        // new timelines are written with the current version
+        let mut out = vec![];
+        Timeline::default().to_json(&mut out).unwrap();
+        let timeline: Timeline = serde_json::from_slice(&out).unwrap();
+        assert_eq!(timeline.version, Timeline::VERSION);
+    }
+}
@@ -1,4 +1,5 @@
 use core::future::Future;
+use euphony_compiler::sample::SampleRate;
 use std::{
     io,
     path::Path,
@@ -22,6 +23,14 @@ impl io::Write for List {
 use euphony::prelude::*;
 
 pub fn start<F>(name: &str, f: F)
+where
+    F: 'static + Future<Output = ()> + Send,
+{
+    start_at(name, SampleRate::DEFAULT, f)
+}
+
+/// Renders the composition at `sample_rate` rather than the default
+pub fn start_at<F>(name: &str, sample_rate: SampleRate, f: F)
 where
     F: 'static + Future<Output = ()> + Send,
 {
@@ -40,6 +49,7 @@ where
     std::fs::create_dir_all(&contents).unwrap();
     let timeline = target.join("main.json");
     let mut compiler = euphony_cli::compiler::Compiler::new(contents, timeline);
+    compiler.set_sample_rate(sample_rate);
 
     let mut result = io::Cursor::new(result);
     compiler.render(&mut result).unwrap();
@@ -210,3 +220,131 @@ fn simple_noise_test() {
         .await;
     })
 }
+
+#[test]
+fn envgen_test() {
+    start("envgen", async {
+        set_tempo(Tempo(60, 1));
+        let env = euphony::envgen!([1, 0.5, 0.25], [1 / 4, 1 / 4]);
+        // sinks leak DC so the envelope rides on a carrier that alternates 1, 0, -1, 0
+        let carrier = osc::sine().with_frequency(12000.0).with_phase(0.25);
+        let sink = (carrier * &env).sink();
+        env.await;
+        sink.fin();
+    });
+
+    let wav = hound::WavReader::open("target/euphony/envgen/main.wav").unwrap();
+    let channels = wav.spec().channels as usize;
+    let samples: Vec<f32> = wav.into_samples().map(Result::unwrap).collect();
+    let left: Vec<f32> = samples.iter().step_by(channels).copied().collect();
+
+    // the attack starts at the first value instead of ramping up from zero
+    let first = left[0];
+    assert!(first > 0.5, "{first}");
+
+    // each segment ends on its value, a quarter beat (12000 samples) apart
+    let level = |idx: usize| (left[idx] / first).abs();
+    assert!((level(6000) - 0.75).abs() < 1e-2, "{}", level(6000));
+    assert!((level(12000) - 0.5).abs() < 1e-2, "{}", level(12000));
+    assert!((level(23998) - 0.25).abs() < 1e-2, "{}", level(23998));
+}
+
+#[test]
+fn looped_sampler_test() {
+    // a tenth of a second of a 12khz carrier so it isn't filtered out by the sinks
+    std::fs::create_dir_all("target/euphony/tmp").unwrap();
+    let path = Path::new("target/euphony/tmp/loop.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    for idx in 0..4800 {
+        writer
+            .write_sample([1.0f32, 0.0, -1.0, 0.0][idx % 4])
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+    let path = path.canonicalize().unwrap().to_str().unwrap().to_owned();
+
+    start("looped_sampler", async move {
+        let buffer = Buffer::new(path);
+        buffer.sampler().passes(3).spawn_primary();
+        // endless loops don't keep the composition from finishing
+        buffer
+            .sampler()
+            .looped(core::time::Duration::from_millis(10))
+            .spawn_primary();
+    });
+
+    let wav = hound::WavReader::open("target/euphony/looped_sampler/main.wav").unwrap();
+    let channels = wav.spec().channels as usize;
+    let samples: Vec<f32> = wav.into_samples().map(Result::unwrap).collect();
+    let left: Vec<f32> = samples.iter().step_by(channels).copied().collect();
+
+    // three passes, quantized to the nearest 1/64th of a beat
+    let len = left.len();
+    assert!((14000..=15000).contains(&len), "{len}");
+
+    // the loop keeps playing after the first pass
+    let peak = left[6000..len - 100]
+        .iter()
+        .fold(0.0f32, |peak, v| peak.max(v.abs()));
+    assert!(peak > 0.5, "{peak}");
+}
+
+#[test]
+fn sample_rate_test() {
+    use euphony_store::{storage::Storage, timeline::Timeline};
+
+    async fn tone() {
+        set_tempo(Tempo(60, 1));
+        let sink = osc::sine().with_frequency(1000.0).sink();
+        Beat(1, 1).delay().await;
+        sink.fin();
+    }
+
+    let mut sinks = vec![];
+    for hz in [44_100, 96_000] {
+        let name = format!("rate_{hz}");
+        start_at(&name, SampleRate::new(hz).unwrap(), tone());
+
+        let target = Path::new("target/euphony").join(&name);
+        let wav = hound::WavReader::open(target.join("main.wav")).unwrap();
+        assert_eq!(wav.spec().sample_rate, hz);
+        let channels = wav.spec().channels as usize;
+        let samples: Vec<f32> = wav.into_samples().map(Result::unwrap).collect();
+        let left: Vec<f32> = samples.iter().step_by(channels).copied().collect();
+
+        // a beat at 60bpm lasts a second at either rate, give or take the rounding of the end
+        assert!(
+            left.len().abs_diff(hz as usize) <= 1,
+            "{hz}: {}",
+            left.len()
+        );
+
+        // and the oscillator runs at the same frequency, crossing zero twice per cycle
+        let crossings = left
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count();
+        assert!((1990..=2010).contains(&crossings), "{hz}: {crossings}");
+
+        let timeline = std::fs::read(target.join("main.json")).unwrap();
+        let timeline: Timeline = serde_json::from_slice(&timeline).unwrap();
+        assert_eq!(timeline.sample_rate, hz);
+
+        let storage = euphony_store::DefaultStorage::new(target.join("contents"));
+        for group in &timeline.groups {
+            for entry in storage.open_group(&group.entries).unwrap() {
+                sinks.push(entry.unwrap().hash);
+            }
+        }
+    }
+
+    // sinks rendered at different rates are never cached as each other
+    assert_eq!(sinks.len(), 2);
+    assert_ne!(sinks[0], sinks[1]);
+}
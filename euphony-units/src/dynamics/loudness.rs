@@ -1,5 +1,102 @@
-// https://en.wikipedia.org/wiki/Loudnesss
+// https://en.wikipedia.org/wiki/LKFS
 
-new_ratio!(Loudness, i64);
+use core::{fmt, str::FromStr};
 
-// TODO what unit should we use?
+/// Perceived loudness in LUFS, as defined by ITU-R BS.1770
+///
+/// Silence is represented as negative infinity.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Loudness(pub f64);
+
+impl Loudness {
+    pub const SILENCE: Self = Self(f64::NEG_INFINITY);
+
+    /// Converts the mean square of K-weighted samples into loudness
+    #[inline]
+    pub fn from_power(power: f64) -> Self {
+        Self(-0.691 + 10.0 * power.log10())
+    }
+
+    /// Converts loudness back into the mean square of K-weighted samples
+    #[inline]
+    pub fn as_power(self) -> f64 {
+        10f64.powf((self.0 + 0.691) / 10.0)
+    }
+
+    #[inline]
+    pub fn is_silent(self) -> bool {
+        !self.0.is_finite()
+    }
+
+    /// Returns the linear gain that would need to be applied to reach `target`
+    #[inline]
+    pub fn gain_to(self, target: Self) -> f64 {
+        if self.is_silent() {
+            return 1.0;
+        }
+
+        10f64.powf((target.0 - self.0) / 20.0)
+    }
+}
+
+impl From<f64> for Loudness {
+    #[inline]
+    fn from(lufs: f64) -> Self {
+        Self(lufs)
+    }
+}
+
+impl From<Loudness> for f64 {
+    #[inline]
+    fn from(loudness: Loudness) -> Self {
+        loudness.0
+    }
+}
+
+impl fmt::Display for Loudness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_silent() {
+            return write!(f, "-inf LUFS");
+        }
+
+        write!(f, "{:.1} LUFS", self.0)
+    }
+}
+
+/// Parses values like `-14`, `-14LUFS` or `-23 LUFS`
+impl FromStr for Loudness {
+    type Err = core::num::ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s
+            .strip_suffix("LUFS")
+            .or_else(|| s.strip_suffix("lufs"))
+            .unwrap_or(s);
+        let value = s.trim_end().parse()?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("-14LUFS".parse(), Ok(Loudness(-14.0)));
+        assert_eq!("-23 LUFS".parse(), Ok(Loudness(-23.0)));
+        assert_eq!("-16".parse(), Ok(Loudness(-16.0)));
+        assert!("loud".parse::<Loudness>().is_err());
+    }
+
+    #[test]
+    fn gain() {
+        let gain = Loudness(-20.0).gain_to(Loudness(-14.0));
+        assert!((gain - 2.0).abs() < 0.01);
+        assert_eq!(Loudness::SILENCE.gain_to(Loudness(-14.0)), 1.0);
+
+        let power = Loudness(-23.0).as_power();
+        assert!((Loudness::from_power(power).0 + 23.0).abs() < 1e-9);
+    }
+}
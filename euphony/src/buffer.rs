@@ -1,6 +1,6 @@
 use crate::{prelude::*, value::Parameter};
 use bach::executor::JoinHandle;
-use core::{future::Future, time::Duration};
+use core::{future::Future, ops::Range, time::Duration};
 use euphony_buffer::AsChannel;
 
 pub use crate::processors::buffer::*;
@@ -8,6 +8,7 @@ pub use euphony_buffer::Buffer;
 
 pub trait BufferExt {
     fn play(&self) -> PlayBuf;
+    fn sampler(&self) -> SamplerBuf;
 }
 
 impl<T> BufferExt for T
@@ -17,6 +18,10 @@ where
     fn play(&self) -> PlayBuf {
         PlayBuf::new(self)
     }
+
+    fn sampler(&self) -> SamplerBuf {
+        SamplerBuf::new(self)
+    }
 }
 
 pub struct PlayBuf {
@@ -59,6 +64,7 @@ impl DelayExt for &PlayBuf {
 }
 
 impl Processor for PlayBuf {
+    #[track_caller]
     fn sink(&self) -> crate::sink::Sink {
         self.play.sink()
     }
@@ -91,3 +97,155 @@ impl SpawnExt for PlayBuf {
         self.fut().spawn_primary()
     }
 }
+
+/// Plays a slice of a buffer at a variable rate
+///
+/// The inputs of the underlying [`Sampler`] can be modulated through `Deref`, e.g.
+/// `buffer.sampler().set_rate(lfo)`.
+pub struct SamplerBuf {
+    sampler: Sampler,
+    /// The length of the slice at the original speed
+    len: Duration,
+    speed: f64,
+    /// The number of passes over the slice, or `None` if it loops until it's cancelled
+    passes: Option<u32>,
+}
+
+impl SamplerBuf {
+    pub fn new<T>(t: &T) -> Self
+    where
+        for<'a> &'a T: AsChannel,
+    {
+        let len = t.duration();
+        let sampler = sampler().with_buffer(t);
+        Self {
+            sampler,
+            len,
+            speed: 1.0,
+            passes: Some(1),
+        }
+    }
+
+    /// Plays the buffer at a constant rate, where negative rates play it in reverse
+    pub fn rate(mut self, rate: f64) -> Self {
+        self.speed = rate.abs();
+        self.sampler = self.sampler.with_rate(rate);
+        self
+    }
+
+    /// Only plays the part of the buffer between `range.start` and `range.end`
+    pub fn slice(mut self, range: Range<Duration>) -> Self {
+        self.len = range.end.saturating_sub(range.start);
+        self.sampler = self.sampler.with_start(range.start).with_end(range.end);
+        self
+    }
+
+    /// Only plays the samples between `range.start` and `range.end`
+    ///
+    /// The samples are counted at the rate the composition is rendered at, which isn't known
+    /// while composing, so the [`duration`](Self::duration) is left unchanged.
+    pub fn slice_samples(mut self, range: Range<u64>) -> Self {
+        self.sampler = self
+            .sampler
+            .with_samples(1.0)
+            .with_start(range.start as f64)
+            .with_end(range.end as f64);
+        self
+    }
+
+    /// Repeats the slice, fading its end into the other side of the loop over `crossfade`
+    ///
+    /// A spawned looped sampler keeps playing until its task is cancelled or the composition ends,
+    /// unless the number of [`passes`](Self::passes) is set. Since it never finishes on its own,
+    /// `spawn_primary` spawns it like any other task rather than holding the composition open.
+    pub fn looped(mut self, crossfade: Duration) -> Self {
+        self.passes = None;
+        self.sampler = self.sampler.with_looped(1.0).with_crossfade(crossfade);
+        self
+    }
+
+    /// Loops the slice `passes` times before finishing
+    pub fn passes(mut self, passes: u32) -> Self {
+        self.passes = Some(passes);
+        self.sampler = self.sampler.with_looped(1.0);
+        self
+    }
+
+    /// Returns how long a single pass over the slice takes
+    pub fn duration(&self) -> Duration {
+        if self.speed == 0.0 {
+            return self.len;
+        }
+        self.len.div_f64(self.speed)
+    }
+
+    #[inline]
+    fn fut(&self) -> impl 'static + Future<Output = ()> + Send {
+        let delay = self.delay();
+        let sink = self.sampler.sink();
+        let forever = self.passes.is_none();
+        async move {
+            if forever {
+                // hold on to the sink so the loop isn't cut off after a single pass
+                core::future::pending::<()>().await;
+            }
+            delay.await;
+            sink.fin()
+        }
+    }
+}
+
+impl core::ops::Deref for SamplerBuf {
+    type Target = Sampler;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sampler
+    }
+}
+
+impl DelayExt for &SamplerBuf {
+    fn delay(self) -> crate::time::Timer {
+        let beat = self.duration() * self.passes.unwrap_or(1) / tempo();
+        let beat = beat.quantize(Beat(1, 64));
+        beat.delay()
+    }
+}
+
+impl Processor for SamplerBuf {
+    #[track_caller]
+    fn sink(&self) -> crate::sink::Sink {
+        self.sampler.sink()
+    }
+
+    fn node(&self) -> crate::node::Node {
+        self.sampler.node()
+    }
+}
+
+impl From<SamplerBuf> for Parameter {
+    fn from(sampler: SamplerBuf) -> Self {
+        Parameter::from(sampler.sampler)
+    }
+}
+
+impl From<&SamplerBuf> for Parameter {
+    fn from(sampler: &SamplerBuf) -> Self {
+        Parameter::from(&sampler.sampler)
+    }
+}
+
+impl SpawnExt for SamplerBuf {
+    type Output = ();
+
+    fn spawn(self) -> JoinHandle<Self::Output> {
+        self.fut().spawn()
+    }
+
+    fn spawn_primary(self) -> JoinHandle<Self::Output> {
+        // endless loops would keep the composition from ever finishing
+        if self.passes.is_none() {
+            return self.fut().spawn();
+        }
+        self.fut().spawn_primary()
+    }
+}
@@ -3,7 +3,10 @@ use futures::{ready, FutureExt};
 use crate::{
     ext::DelayExt,
     node::Node,
-    prelude::{env, Beat, DurationInput, TargetInput, ValueInput},
+    prelude::{
+        env, AttackCurveInput, AttackInput, Beat, DecayCurveInput, DecayInput, EndInput, GateInput,
+        PeakInput, ReleaseCurveInput, ReleaseInput, StartInput, SustainInput,
+    },
     processor::Processor,
     sink::Sink,
     time::Timer,
@@ -54,38 +57,73 @@ fn macro_test() {
         drop(envgen!([0, 1, 0], [1, 2]));
         drop(envgen!([0, 1, 0], [1 / 2, 1 / 4]));
         drop(envgen!([0, 1, 0], [Beat(1, 2), Beat(3, 4)]));
+        // degenerate envelopes hold their value rather than panicking
+        drop(EnvGen::new(vec![1.into()], vec![], vec![]));
+        drop(EnvGen::new(vec![], vec![], vec![]));
     });
 }
 
+/// Ramps through a list of values over the durations between them
+///
+/// The first two segments run on an [`env::Adsr`] as its attack and decay so most envelopes only
+/// need a single command. Any segments after that are played by releasing towards each value in
+/// turn. A single value is held and missing durations are played instantly.
 #[must_use = "nodes do nothing unless routed to a Sink"]
 pub struct EnvGen {
     values: Vec<Trigger>,
     durations: Vec<Beat>,
     curves: Vec<Parameter>,
+    /// The segment that is currently playing
     position: usize,
     timer: Timer,
-    env: env::Linear,
+    env: env::Adsr,
 }
 
 impl EnvGen {
-    pub fn new(values: Vec<Trigger>, durations: Vec<Beat>, curves: Vec<Parameter>) -> Self {
-        let env = env::linear();
+    pub fn new(mut values: Vec<Trigger>, mut durations: Vec<Beat>, curves: Vec<Parameter>) -> Self {
+        while values.len() < 2 {
+            let hold = values.last().cloned().unwrap_or_else(|| 0.0.into());
+            values.push(hold);
+        }
+        if durations.is_empty() {
+            durations.push(Beat(0, 1));
+        }
 
-        let delay = durations.first().unwrap();
-        env.set_duration(*delay);
+        let duration = |idx: usize| durations[idx % durations.len()];
+        let curve = |idx: usize| -> Parameter {
+            if curves.is_empty() {
+                0.0.into()
+            } else {
+                curves[idx % curves.len()].clone()
+            }
+        };
+
+        let env = env::adsr()
+            .with_start(&values[0])
+            .with_attack(duration(0))
+            .with_peak(&values[1])
+            .with_attack_curve(curve(0));
+
+        // the decay holds the peak when there's only a single segment
+        let (env, delay, position) = if let Some(sustain) = values.get(2) {
+            let env = env
+                .with_decay(duration(1))
+                .with_sustain(sustain)
+                .with_decay_curve(curve(1));
+            (env, duration(0) + duration(1), 1)
+        } else {
+            let env = env.with_decay(0.0).with_sustain(&values[1]);
+            (env, duration(0), 0)
+        };
+
+        let env = env.with_gate(1.0);
         let timer = delay.delay();
 
-        let value = &values[0];
-        env.set_value(value.clone());
-
-        let target = &values[1];
-        env.set_target(target.clone());
-
         Self {
             values,
             durations,
             curves,
-            position: 0,
+            position,
             timer,
             env,
         }
@@ -101,15 +139,17 @@ impl EnvGen {
 
     fn update(&mut self) {
         let delay = self.durations[self.position % self.durations.len()];
-        self.env.set_duration(delay);
+        self.env.set_release(delay);
         self.timer = delay.delay();
 
-        // TODO set on env
-        let curve = &self.curves[self.position % self.curves.len()];
-        let _ = curve;
+        if !self.curves.is_empty() {
+            let curve = &self.curves[self.position % self.curves.len()];
+            self.env.set_release_curve(curve.clone());
+        }
 
         let value = &self.values[self.position + 1];
-        self.env.set_target(value.clone());
+        self.env.set_end(value);
+        self.env.set_gate(0.0);
     }
 }
 
@@ -122,6 +162,7 @@ impl ops::Deref for EnvGen {
 }
 
 impl Processor for EnvGen {
+    #[track_caller]
     fn sink(&self) -> Sink {
         self.env.sink()
     }
@@ -6,13 +6,26 @@ use crate::{
 };
 use euphony_buffer::AsChannel;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
+    panic::Location,
     sync::{Arc, Mutex},
 };
 
 thread_local! {
     static NODE_ID: Counter = Counter::new();
     static BUFFER_ID: Counter = Counter::new();
+    static LOCATION: Cell<Option<&'static Location<'static>>> = const { Cell::new(None) };
+}
+
+/// Emits the location of the composition code calling into the node, if it changed
+#[track_caller]
+fn locate() {
+    let location = Location::caller();
+    LOCATION.with(|prev| {
+        if prev.replace(Some(location)) != Some(location) {
+            output::source_location(location);
+        }
+    });
 }
 
 struct Counter(RefCell<u64>);
@@ -40,6 +53,8 @@ struct OwnedNode {
 impl Drop for OwnedNode {
     fn drop(&mut self) {
         output::finish_node(self.id);
+        // the compiler forgets the location after a node is finished
+        LOCATION.with(|prev| prev.set(None));
     }
 }
 
@@ -52,9 +67,11 @@ impl Node {
         self.0.id
     }
 
+    #[track_caller]
     pub(crate) fn new(definition: &Definition, group: Option<u64>) -> Self {
         let id = NODE_ID.with(|v| v.next());
 
+        locate();
         output::spawn_node(id, definition.id, group);
 
         let node = OwnedNode {
@@ -69,9 +86,11 @@ impl Node {
         Node(Arc::new(node))
     }
 
+    #[track_caller]
     pub(crate) fn fork(&self) -> Self {
         let id = NODE_ID.with(|v| v.next());
 
+        locate();
         output::fork_node(self.id(), id);
 
         let parameters = self.0.parameters.lock().unwrap().len();
@@ -85,13 +104,17 @@ impl Node {
         Node(Arc::new(node))
     }
 
+    #[track_caller]
     pub(crate) fn set<V: Into<Parameter>>(&self, index: u64, value: V) {
+        locate();
         let value = value.into();
         value.set(self.id(), index);
         self.0.parameters.lock().unwrap()[index as usize] = value;
     }
 
+    #[track_caller]
     pub(crate) fn set_buffer<C: AsChannel>(&self, index: u64, channel: C) {
+        locate();
         let buffer = channel.buffer(|path, ext| {
             let id = BUFFER_ID.with(|v| v.next());
             // load the buffer if needed
@@ -106,12 +129,14 @@ impl Node {
         assert!(self.0.buffers > index);
     }
 
+    #[track_caller]
     pub fn sink(&self) -> Sink {
         Sink::default().with(self)
     }
 }
 
 impl crate::processor::Processor for Node {
+    #[track_caller]
     fn sink(&self) -> Sink {
         Sink::default().with(self)
     }
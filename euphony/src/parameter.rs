@@ -8,6 +8,7 @@ pub struct Parameter {
 }
 
 impl Parameter {
+    #[track_caller]
     pub fn set<V: Into<value::Parameter>>(&self, value: V) {
         self.node.set(self.index, value)
     }
@@ -20,6 +21,7 @@ pub struct Trigger {
 }
 
 impl Trigger {
+    #[track_caller]
     pub fn set<V: Into<value::Trigger>>(&self, value: V) {
         self.node.set(self.index, value.into())
     }
@@ -32,6 +34,7 @@ pub struct Buffer {
 }
 
 impl Buffer {
+    #[track_caller]
     pub fn set<C: AsChannel>(&self, channel: C) {
         self.node.set_buffer(self.index, channel)
     }
@@ -17,12 +17,12 @@ pub use crate::{
     },
     rand,
     runtime::{primary, spawn},
-    section::section,
+    section::{marker, section},
     sink::Sink,
-    time::{now, set_tempo, tempo},
+    time::{now, ramp_tempo, set_tempo, set_time_signature, tempo, time_signature},
     units::{
         pitch::{frequency::*, Interval},
-        time::{Beat, Tempo},
+        time::{Beat, Tempo, TimeSignature},
         zip::Zip as ZipExt,
     },
     value,
@@ -24,6 +24,7 @@ pub(crate) struct Definition {
 
 impl Definition {
     #[inline]
+    #[track_caller]
     pub fn spawn(&self) -> Node {
         Node::new(self, None)
     }
@@ -35,6 +36,7 @@ macro_rules! define_processor_binary_op {
             type Output = crate::processors::binary::$op;
 
             #[inline]
+            #[track_caller]
             fn $lower(self, rhs: $name) -> Self::Output {
                 use crate::processors::input::*;
                 crate::processors::binary::$lower()
@@ -47,6 +49,7 @@ macro_rules! define_processor_binary_op {
             type Output = crate::processors::binary::$op;
 
             #[inline]
+            #[track_caller]
             fn $lower(self, rhs: &$name) -> Self::Output {
                 use crate::processors::input::*;
                 crate::processors::binary::$lower()
@@ -59,6 +62,7 @@ macro_rules! define_processor_binary_op {
             type Output = crate::processors::binary::$op;
 
             #[inline]
+            #[track_caller]
             fn $lower(self, rhs: Rhs) -> Self::Output {
                 use crate::processors::input::*;
                 crate::processors::binary::$lower()
@@ -71,6 +75,7 @@ macro_rules! define_processor_binary_op {
             type Output = crate::processors::binary::$op;
 
             #[inline]
+            #[track_caller]
             fn $lower(self, rhs: Rhs) -> Self::Output {
                 use crate::processors::input::*;
                 crate::processors::binary::$lower()
@@ -93,6 +98,7 @@ macro_rules! define_processor_ops {
             type Output = crate::processors::unary::Neg;
 
             #[inline]
+            #[track_caller]
             fn neg(self) -> Self::Output {
                 use crate::processors::input::*;
                 crate::processors::unary::neg().with_input(self)
@@ -103,6 +109,7 @@ macro_rules! define_processor_ops {
             type Output = crate::processors::unary::Neg;
 
             #[inline]
+            #[track_caller]
             fn neg(self) -> Self::Output {
                 use crate::processors::input::*;
                 crate::processors::unary::neg().with_input(self)
@@ -139,6 +146,7 @@ macro_rules! define_processor {
         pub struct $name(crate::node::Node);
 
         #[inline]
+        #[track_caller]
         $(#[doc = $doc])?
         pub fn $lower() -> $name {
             $name::default()
@@ -146,6 +154,7 @@ macro_rules! define_processor {
 
         $(
             #[inline]
+            #[track_caller]
             pub fn $fork() -> ($name, crate::node::Node) {
                 let a = $lower();
                 let b = a.fork();
@@ -161,6 +170,7 @@ macro_rules! define_processor {
 
             impl Default for $name {
                 #[inline]
+                #[track_caller]
                 fn default() -> Self {
                     use crate::processor::Definition;
                     static DEF: Definition = Definition {
@@ -200,6 +210,7 @@ macro_rules! define_processor {
 
             impl crate::processor::Processor for $name {
                 #[inline]
+                #[track_caller]
                 fn sink(&self) -> crate::sink::Sink {
                     self.0.sink()
                 }
@@ -213,12 +224,14 @@ macro_rules! define_processor {
             $(
                 impl<V: euphony_buffer::AsChannel> crate::processors::input::$trait_buffer_name<V> for $name {
                     #[inline]
+                    #[track_caller]
                     fn $with_buffer(self, value: V) -> Self {
                         self.0.set_buffer($buffer_id, value);
                         self
                     }
 
                     #[inline]
+                    #[track_caller]
                     fn $set_buffer(&self, value: V) -> &Self {
                         self.0.set_buffer($buffer_id, value);
                         self
@@ -229,12 +242,14 @@ macro_rules! define_processor {
             $(
                 impl<V: Into<$input_ty>> crate::processors::input::$trait_name<V> for $name {
                     #[inline]
+                    #[track_caller]
                     fn $with(self, value: V) -> Self {
                         self.0.set($input_id, value.into());
                         self
                     }
 
                     #[inline]
+                    #[track_caller]
                     fn $set(&self, value: V) -> &Self {
                         self.0.set($input_id, value.into());
                         self
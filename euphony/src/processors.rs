@@ -7,21 +7,25 @@ pub mod ext {
         for<'a> &'a Self: Into<Parameter>,
     {
         #[inline]
+        #[track_caller]
         #[doc = " Computes the absolute value of `input`. Returns `NAN` if the number is `NAN`.\n"]
         fn abs(&self) -> crate::processors::unary::Abs {
             crate::processors::unary::abs().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Computes the arccosine of a number. Return value is in radians in the\n range [0, pi] or NaN if the number is outside the range [-1, 1].\n"]
         fn acos(&self) -> crate::processors::unary::Acos {
             crate::processors::unary::acos().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Inverse hyperbolic cosine function.\n"]
         fn acosh(&self) -> crate::processors::unary::Acosh {
             crate::processors::unary::acosh().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Adds two signals together\n"]
         fn add<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Add
         where
@@ -30,6 +34,7 @@ pub mod ext {
             crate::processors::binary::add().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Fused multiply-add. Computes `(input + add) * mul` with only one rounding\n error, yielding a more accurate result than an unfused add-multiply.\n"]
         fn add_mul<Add, Mul>(&self, add: Add, mul: Mul) -> crate::processors::tertiary::AddMul
         where
@@ -39,21 +44,25 @@ pub mod ext {
             crate::processors::tertiary::add_mul().with_input(self).with_add(add).with_mul(mul)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Computes the arcsine of a number. Return value is in radians in the range [-pi/2, pi/2] or NaN if the number is outside the range [-1, 1].\n"]
         fn asin(&self) -> crate::processors::unary::Asin {
             crate::processors::unary::asin().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Inverse hyperbolic sine function.\n"]
         fn asinh(&self) -> crate::processors::unary::Asinh {
             crate::processors::unary::asinh().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Computes the arctangent of a number. Return value is in radians in the range [-pi/2, pi/2];\n"]
         fn atan(&self) -> crate::processors::unary::Atan {
             crate::processors::unary::atan().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Computes the four quadrant arctangent of `lhs` (`y`) and `rhs` (`x`) in radians.\n\n * `x = 0`, `y = 0`: `0`\n * `x >= 0`: `arctan(y/x)` -> `[-pi/2, pi/2]`\n * `y >= 0`: `arctan(y/x) + pi` -> `(pi/2, pi]`\n * `y < 0`: `arctan(y/x) - pi` -> `(-pi, -pi/2)`\n"]
         fn atan2<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Atan2
         where
@@ -62,21 +71,25 @@ pub mod ext {
             crate::processors::binary::atan2().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Inverse hyperbolic tangent function.\n"]
         fn atanh(&self) -> crate::processors::unary::Atanh {
             crate::processors::unary::atanh().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the cube root of a number.\n"]
         fn cbrt(&self) -> crate::processors::unary::Cbrt {
             crate::processors::unary::cbrt().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the smallest integer greater than or equal to a number.\n"]
         fn ceil(&self) -> crate::processors::unary::Ceil {
             crate::processors::unary::ceil().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Restrict a value to a certain interval unless it is NaN.\n\n Returns `max` if `input` is greater than `max`, and `min` if `input` is\n less than `min`. Otherwise this returns `input`.\n\n Note that this function returns NaN if the initial value was NaN as\n well or `min > max`\n"]
         fn clamp<Min, Max>(&self, min: Min, max: Max) -> crate::processors::tertiary::Clamp
         where
@@ -86,6 +99,7 @@ pub mod ext {
             crate::processors::tertiary::clamp().with_input(self).with_min(min).with_max(max)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns a number composed of the magnitude of `lhs` and the sign of `rhs`.\n\n Equal to `lhs` if the sign of `lhs` and `rhs` are the same, otherwise equal\n to `-lhs`. If `lhs` is a `NAN`, then a `NAN` with the sign of `rhs` is returned.\n"]
         fn copysign<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Copysign
         where
@@ -94,16 +108,19 @@ pub mod ext {
             crate::processors::binary::copysign().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Computes the cosine of a number (in radians).\n"]
         fn cos(&self) -> crate::processors::unary::Cos {
             crate::processors::unary::cos().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Hyperbolic cosine function.\n"]
         fn cosh(&self) -> crate::processors::unary::Cosh {
             crate::processors::unary::cosh().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Divides the left hand signal by the right\n"]
         fn div<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Div
         where
@@ -112,6 +129,7 @@ pub mod ext {
             crate::processors::binary::div().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Calculates Euclidean division, the matching method for `rem_euclid`.\n\n This computes the integer `n` such that `lhs = n * rhs + self.rem_euclid(rhs)`.\n In other words, the result is `lhs / rhs` rounded to the integer n such that `lhs >= n * rhs`.\n"]
         fn div_euclid<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::DivEuclid
         where
@@ -120,6 +138,7 @@ pub mod ext {
             crate::processors::binary::div_euclid().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Compares `rhs` to `lhs`. If `rhs == lhs`, the output is `1.0`. Otherwise the\n output is `0.0`.\n"]
         fn eq<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Eq
         where
@@ -128,31 +147,37 @@ pub mod ext {
             crate::processors::binary::eq().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns `e^(self)`, (the exponential function).\n"]
         fn exp(&self) -> crate::processors::unary::Exp {
             crate::processors::unary::exp().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns `2^(self)`\n"]
         fn exp2(&self) -> crate::processors::unary::Exp2 {
             crate::processors::unary::exp2().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns `e^(self) - 1` in a way that is accurate even if the number is close to zero.\n"]
         fn exp_m1(&self) -> crate::processors::unary::ExpM1 {
             crate::processors::unary::exp_m1().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the largest integer less than or equal to a number.\n"]
         fn floor(&self) -> crate::processors::unary::Floor {
             crate::processors::unary::floor().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the fractional part of a number.\n"]
         fn fract(&self) -> crate::processors::unary::Fract {
             crate::processors::unary::fract().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Compares `rhs` to `lhs`. If `rhs > lhs`, the output is `1.0`. Otherwise the\n output is `0.0`.\n"]
         fn gt<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Gt
         where
@@ -161,6 +186,7 @@ pub mod ext {
             crate::processors::binary::gt().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Compares `rhs` to `lhs`. If `rhs >= lhs`, the output is `1.0`. Otherwise the\n output is `0.0`.\n"]
         fn gte<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Gte
         where
@@ -169,6 +195,7 @@ pub mod ext {
             crate::processors::binary::gte().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Calculates the length of the hypotenuse of a right-angle triangle given legs of length `x` and `y`.\n"]
         fn hypot<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Hypot
         where
@@ -177,16 +204,19 @@ pub mod ext {
             crate::processors::binary::hypot().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the natural logarithm of the number.\n"]
         fn ln(&self) -> crate::processors::unary::Ln {
             crate::processors::unary::ln().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns ln(1+n) (natural logarithm) more accurately than if the operations\n were performed separately.\n"]
         fn ln1p(&self) -> crate::processors::unary::Ln1p {
             crate::processors::unary::ln1p().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the logarithm of the number with respect to an arbitrary base.\n\n The result might not be correctly rounded owing to implementation details;\n `self.log2()` can produce more accurate results for base 2, and `self.log10()` can produce\n more accurate results for base 10.\n"]
         fn log<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Log
         where
@@ -195,16 +225,19 @@ pub mod ext {
             crate::processors::binary::log().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the base 10 logarithm of the number.\n"]
         fn log10(&self) -> crate::processors::unary::Log10 {
             crate::processors::unary::log10().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the base 2 logarithm of the number.\n"]
         fn log2(&self) -> crate::processors::unary::Log2 {
             crate::processors::unary::log2().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Compares `rhs` to `lhs`. If `rhs < lhs`, the output is `1.0`. Otherwise the\n output is `0.0`.\n"]
         fn lt<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Lt
         where
@@ -213,6 +246,7 @@ pub mod ext {
             crate::processors::binary::lt().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Compares `rhs` to `lhs`. If `rhs <= lhs`, the output is `1.0`. Otherwise the\n output is `0.0`.\n"]
         fn lte<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Lte
         where
@@ -221,6 +255,7 @@ pub mod ext {
             crate::processors::binary::lte().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the maximum of the two numbers.\n\n Follows the IEEE-754 2008 semantics for maxNum, except for handling of signaling `NAN`s. This\n matches the behavior of libm’s fmax.\n"]
         fn max<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Max
         where
@@ -229,6 +264,7 @@ pub mod ext {
             crate::processors::binary::max().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the minimum of the two numbers.\n\n Follows the IEEE-754 2008 semantics for minNum, except for handling of signaling `NAN`s. This\n matches the behavior of libm’s fmax.\n"]
         fn min<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Min
         where
@@ -237,6 +273,7 @@ pub mod ext {
             crate::processors::binary::min().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Multiplies two signals together\n"]
         fn mul<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Mul
         where
@@ -245,6 +282,7 @@ pub mod ext {
             crate::processors::binary::mul().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Fused multiply-add. Computes `(input * mul) + add` with only one rounding\n error, yielding a more accurate result than an unfused multiply-add.\n"]
         fn mul_add<Mul, Add>(&self, mul: Mul, add: Add) -> crate::processors::tertiary::MulAdd
         where
@@ -254,6 +292,7 @@ pub mod ext {
             crate::processors::tertiary::mul_add().with_input(self).with_mul(mul).with_add(add)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Compares `rhs` to `lhs`. If `rhs != lhs`, the output is `1.0`. Otherwise the\n output is `0.0`.\n"]
         fn ne<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Ne
         where
@@ -262,21 +301,25 @@ pub mod ext {
             crate::processors::binary::ne().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " The unary negation operator `-`.\n"]
         fn neg(&self) -> crate::processors::unary::Neg {
             crate::processors::unary::neg().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Normalizes a number.\n\n * `-0.0` will be converted into `0.0`\n * `NAN` will be converted into `0.0`\n * `INFINITY` will be converted into `MAX`\n * `NEG_INFINITY` will be converted into `MIN`\n"]
         fn norm(&self) -> crate::processors::unary::Norm {
             crate::processors::unary::norm().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Passes the input signal to the output signal\n"]
         fn pass(&self) -> crate::processors::unary::Pass {
             crate::processors::unary::pass().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Raises a number to a floating point power.\n"]
         fn powf<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Powf
         where
@@ -285,6 +328,7 @@ pub mod ext {
             crate::processors::binary::powf().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Raises a number to an integer power.\n\n Using this function is generally faster than using `powf`\n"]
         fn powi<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Powi
         where
@@ -293,11 +337,13 @@ pub mod ext {
             crate::processors::binary::powi().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Takes the reciprocal (inverse) of a number, 1/x.\n"]
         fn recip(&self) -> crate::processors::unary::Recip {
             crate::processors::unary::recip().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the remainder of the left hand signal by the right\n"]
         fn rem<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Rem
         where
@@ -306,6 +352,7 @@ pub mod ext {
             crate::processors::binary::rem().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Calculates the least nonnegative remainder of `lhs (mod rhs)`.\n\n In particular, the return value `r` satisfies `0.0 <= r < rhs.abs()` in\n most cases. However, due to a floating point round-off error it can\n result in `r == rhs.abs()`, violating the mathematical definition, if\n `lhs` is much smaller than `rhs.abs()` in magnitude and `lhs < 0.0`.\n This result is not an element of the function's codomain, but it is the\n closest floating point number in the real numbers and thus fulfills the\n property `lhs == self.div_euclid(rhs) * rhs + lhs.rem_euclid(rhs)`\n approximatively.\n"]
         fn rem_euclid<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::RemEuclid
         where
@@ -314,11 +361,13 @@ pub mod ext {
             crate::processors::binary::rem_euclid().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the nearest integer to a number. Round half-way cases away from `0.0`.\n"]
         fn round(&self) -> crate::processors::unary::Round {
             crate::processors::unary::round().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " If `cond` is not `0.0 | NaN | Infinity`, then `a` is returned. Otherwise `b`\n is returned.\n"]
         fn select<A, B>(&self, a: A, b: B) -> crate::processors::tertiary::Select
         where
@@ -328,26 +377,31 @@ pub mod ext {
             crate::processors::tertiary::select().with_cond(self).with_a(a).with_b(b)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns a number that represents the sign of `self`.\n\n * `1.0` if the number is positive, `+0.0` or `INFINITY`\n * `-1.0` if the number is negative, `-0.0` or `NEG_INFINITY`\n * `NAN` if the number is `NAN`\n"]
         fn signum(&self) -> crate::processors::unary::Signum {
             crate::processors::unary::signum().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Computes the sine of a number (in radians).\n"]
         fn sin(&self) -> crate::processors::unary::Sin {
             crate::processors::unary::sin().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Hyperbolic sine function.\n"]
         fn sinh(&self) -> crate::processors::unary::Sinh {
             crate::processors::unary::sinh().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the square root of a number.\n\n Returns `NaN` if `self` is a negative number other than `-0.0`.\n"]
         fn sqrt(&self) -> crate::processors::unary::Sqrt {
             crate::processors::unary::sqrt().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Subtracts `rhs` from `lhs`\n"]
         fn sub<Lhs>(&self, lhs: Lhs) -> crate::processors::binary::Sub
         where
@@ -356,116 +410,139 @@ pub mod ext {
             crate::processors::binary::sub().with_rhs(self).with_lhs(lhs)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Computes the tangent of a number (in radians).\n"]
         fn tan(&self) -> crate::processors::unary::Tan {
             crate::processors::unary::tan().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Hyperbolic tangent function.\n"]
         fn tanh(&self) -> crate::processors::unary::Tanh {
             crate::processors::unary::tanh().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Converts radians to degrees.\n"]
         fn to_degrees(&self) -> crate::processors::unary::ToDegrees {
             crate::processors::unary::to_degrees().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Converts degrees to radians.\n"]
         fn to_radians(&self) -> crate::processors::unary::ToRadians {
             crate::processors::unary::to_radians().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Returns the integer part of a number.\n"]
         fn trunc(&self) -> crate::processors::unary::Trunc {
             crate::processors::unary::trunc().with_input(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Allpass filter.\n"]
         fn allpass(&self) -> crate::processors::filter::Allpass {
             crate::processors::filter::allpass().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Allpass filter with adjustable delay (delay > 0) in samples at DC.\n"]
         fn allpole(&self) -> crate::processors::filter::Allpole {
             crate::processors::filter::allpole().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Bandpass filter.\n"]
         fn bandpass(&self) -> crate::processors::filter::Bandpass {
             crate::processors::filter::bandpass().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Bell filter.\n"]
         fn bell(&self) -> crate::processors::filter::Bell {
             crate::processors::filter::bell().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Butterworth lowpass filter (2nd order).\n"]
         fn butterpass(&self) -> crate::processors::filter::Butterpass {
             crate::processors::filter::butterpass().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Highpass filter.\n"]
         fn highpass(&self) -> crate::processors::filter::Highpass {
             crate::processors::filter::highpass().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " One-pole, one-zero highpass filter (1st order).\n"]
         fn highpole(&self) -> crate::processors::filter::Highpole {
             crate::processors::filter::highpole().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Highshelf filter.\n"]
         fn highshelf(&self) -> crate::processors::filter::Highshelf {
             crate::processors::filter::highshelf().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Lowpass filter.\n"]
         fn lowpass(&self) -> crate::processors::filter::Lowpass {
             crate::processors::filter::lowpass().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " One-pole lowpass filter (1st order).\n"]
         fn lowpole(&self) -> crate::processors::filter::Lowpole {
             crate::processors::filter::lowpole().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Lowshelf filter.\n"]
         fn lowshelf(&self) -> crate::processors::filter::Lowshelf {
             crate::processors::filter::lowshelf().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Moog resonant lowpass filter.\n"]
         fn moog(&self) -> crate::processors::filter::Moog {
             crate::processors::filter::moog().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Morphing filter that morphs between lowpass, peak and highpass modes.\n"]
         fn morph(&self) -> crate::processors::filter::Morph {
             crate::processors::filter::morph().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Notch filter.\n"]
         fn notch(&self) -> crate::processors::filter::Notch {
             crate::processors::filter::notch().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Peak filter.\n"]
         fn peak(&self) -> crate::processors::filter::Peak {
             crate::processors::filter::peak().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Pinking filter.\n"]
         fn pinkpass(&self) -> crate::processors::filter::Pinkpass {
             crate::processors::filter::pinkpass().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = " Constant-gain bandpass resonator.\n"]
         fn resonator(&self) -> crate::processors::filter::Resonator {
             crate::processors::filter::resonator().with_signal(self)
         }
         #[inline]
+        #[track_caller]
         #[doc = ""]
         fn shaper(&self) -> crate::processors::filter::Shaper {
             crate::processors::filter::shaper().with_signal(self)
@@ -486,6 +563,14 @@ pub mod input {
         fn with_add(self, value: Value) -> Self;
         fn set_add(&self, value: Value) -> &Self;
     }
+    pub trait AttackInput<Value> {
+        fn with_attack(self, value: Value) -> Self;
+        fn set_attack(&self, value: Value) -> &Self;
+    }
+    pub trait AttackCurveInput<Value> {
+        fn with_attack_curve(self, value: Value) -> Self;
+        fn set_attack_curve(&self, value: Value) -> &Self;
+    }
     pub trait AzimuthInput<Value> {
         fn with_azimuth(self, value: Value) -> Self;
         fn set_azimuth(&self, value: Value) -> &Self;
@@ -510,18 +595,34 @@ pub mod input {
         fn with_cond(self, value: Value) -> Self;
         fn set_cond(&self, value: Value) -> &Self;
     }
+    pub trait CrossfadeInput<Value> {
+        fn with_crossfade(self, value: Value) -> Self;
+        fn set_crossfade(&self, value: Value) -> &Self;
+    }
     pub trait CutoffInput<Value> {
         fn with_cutoff(self, value: Value) -> Self;
         fn set_cutoff(&self, value: Value) -> &Self;
     }
+    pub trait DampingInput<Value> {
+        fn with_damping(self, value: Value) -> Self;
+        fn set_damping(&self, value: Value) -> &Self;
+    }
     pub trait DecayInput<Value> {
         fn with_decay(self, value: Value) -> Self;
         fn set_decay(&self, value: Value) -> &Self;
     }
+    pub trait DecayCurveInput<Value> {
+        fn with_decay_curve(self, value: Value) -> Self;
+        fn set_decay_curve(&self, value: Value) -> &Self;
+    }
     pub trait DelayInput<Value> {
         fn with_delay(self, value: Value) -> Self;
         fn set_delay(&self, value: Value) -> &Self;
     }
+    pub trait DensityInput<Value> {
+        fn with_density(self, value: Value) -> Self;
+        fn set_density(&self, value: Value) -> &Self;
+    }
     pub trait DurationInput<Value> {
         fn with_duration(self, value: Value) -> Self;
         fn set_duration(&self, value: Value) -> &Self;
@@ -530,6 +631,10 @@ pub mod input {
         fn with_duty_cycle(self, value: Value) -> Self;
         fn set_duty_cycle(&self, value: Value) -> &Self;
     }
+    pub trait EndInput<Value> {
+        fn with_end(self, value: Value) -> Self;
+        fn set_end(&self, value: Value) -> &Self;
+    }
     pub trait FrequencyInput<Value> {
         fn with_frequency(self, value: Value) -> Self;
         fn set_frequency(&self, value: Value) -> &Self;
@@ -538,6 +643,14 @@ pub mod input {
         fn with_gain(self, value: Value) -> Self;
         fn set_gain(&self, value: Value) -> &Self;
     }
+    pub trait GateInput<Value> {
+        fn with_gate(self, value: Value) -> Self;
+        fn set_gate(&self, value: Value) -> &Self;
+    }
+    pub trait ImpulseInput<Value> {
+        fn with_impulse(self, value: Value) -> Self;
+        fn set_impulse(&self, value: Value) -> &Self;
+    }
     pub trait InclineInput<Value> {
         fn with_incline(self, value: Value) -> Self;
         fn set_incline(&self, value: Value) -> &Self;
@@ -546,6 +659,14 @@ pub mod input {
         fn with_input(self, value: Value) -> Self;
         fn set_input(&self, value: Value) -> &Self;
     }
+    pub trait JitterInput<Value> {
+        fn with_jitter(self, value: Value) -> Self;
+        fn set_jitter(&self, value: Value) -> &Self;
+    }
+    pub trait KneeInput<Value> {
+        fn with_knee(self, value: Value) -> Self;
+        fn set_knee(&self, value: Value) -> &Self;
+    }
     pub trait LengthInput<Value> {
         fn with_length(self, value: Value) -> Self;
         fn set_length(&self, value: Value) -> &Self;
@@ -554,6 +675,14 @@ pub mod input {
         fn with_lhs(self, value: Value) -> Self;
         fn set_lhs(&self, value: Value) -> &Self;
     }
+    pub trait LoopedInput<Value> {
+        fn with_looped(self, value: Value) -> Self;
+        fn set_looped(&self, value: Value) -> &Self;
+    }
+    pub trait MakeupInput<Value> {
+        fn with_makeup(self, value: Value) -> Self;
+        fn set_makeup(&self, value: Value) -> &Self;
+    }
     pub trait MaxInput<Value> {
         fn with_max(self, value: Value) -> Self;
         fn set_max(&self, value: Value) -> &Self;
@@ -562,6 +691,10 @@ pub mod input {
         fn with_min(self, value: Value) -> Self;
         fn set_min(&self, value: Value) -> &Self;
     }
+    pub trait MixInput<Value> {
+        fn with_mix(self, value: Value) -> Self;
+        fn set_mix(&self, value: Value) -> &Self;
+    }
     pub trait MorphInput<Value> {
         fn with_morph(self, value: Value) -> Self;
         fn set_morph(&self, value: Value) -> &Self;
@@ -570,14 +703,26 @@ pub mod input {
         fn with_mul(self, value: Value) -> Self;
         fn set_mul(&self, value: Value) -> &Self;
     }
+    pub trait PeakInput<Value> {
+        fn with_peak(self, value: Value) -> Self;
+        fn set_peak(&self, value: Value) -> &Self;
+    }
     pub trait PhaseInput<Value> {
         fn with_phase(self, value: Value) -> Self;
         fn set_phase(&self, value: Value) -> &Self;
     }
+    pub trait PitchInput<Value> {
+        fn with_pitch(self, value: Value) -> Self;
+        fn set_pitch(&self, value: Value) -> &Self;
+    }
     pub trait PositionInput<Value> {
         fn with_position(self, value: Value) -> Self;
         fn set_position(&self, value: Value) -> &Self;
     }
+    pub trait PredelayInput<Value> {
+        fn with_predelay(self, value: Value) -> Self;
+        fn set_predelay(&self, value: Value) -> &Self;
+    }
     pub trait QInput<Value> {
         fn with_q(self, value: Value) -> Self;
         fn set_q(&self, value: Value) -> &Self;
@@ -586,6 +731,22 @@ pub mod input {
         fn with_radius(self, value: Value) -> Self;
         fn set_radius(&self, value: Value) -> &Self;
     }
+    pub trait RateInput<Value> {
+        fn with_rate(self, value: Value) -> Self;
+        fn set_rate(&self, value: Value) -> &Self;
+    }
+    pub trait RatioInput<Value> {
+        fn with_ratio(self, value: Value) -> Self;
+        fn set_ratio(&self, value: Value) -> &Self;
+    }
+    pub trait ReleaseInput<Value> {
+        fn with_release(self, value: Value) -> Self;
+        fn set_release(&self, value: Value) -> &Self;
+    }
+    pub trait ReleaseCurveInput<Value> {
+        fn with_release_curve(self, value: Value) -> Self;
+        fn set_release_curve(&self, value: Value) -> &Self;
+    }
     pub trait RepeatInput<Value> {
         fn with_repeat(self, value: Value) -> Self;
         fn set_repeat(&self, value: Value) -> &Self;
@@ -594,22 +755,58 @@ pub mod input {
         fn with_reset(self, value: Value) -> Self;
         fn set_reset(&self, value: Value) -> &Self;
     }
+    pub trait RetriggerInput<Value> {
+        fn with_retrigger(self, value: Value) -> Self;
+        fn set_retrigger(&self, value: Value) -> &Self;
+    }
     pub trait RhsInput<Value> {
         fn with_rhs(self, value: Value) -> Self;
         fn set_rhs(&self, value: Value) -> &Self;
     }
+    pub trait SamplesInput<Value> {
+        fn with_samples(self, value: Value) -> Self;
+        fn set_samples(&self, value: Value) -> &Self;
+    }
     pub trait SeedInput<Value> {
         fn with_seed(self, value: Value) -> Self;
         fn set_seed(&self, value: Value) -> &Self;
     }
+    pub trait ShapeInput<Value> {
+        fn with_shape(self, value: Value) -> Self;
+        fn set_shape(&self, value: Value) -> &Self;
+    }
+    pub trait SidechainInput<Value> {
+        fn with_sidechain(self, value: Value) -> Self;
+        fn set_sidechain(&self, value: Value) -> &Self;
+    }
     pub trait SignalInput<Value> {
         fn with_signal(self, value: Value) -> Self;
         fn set_signal(&self, value: Value) -> &Self;
     }
+    pub trait SizeInput<Value> {
+        fn with_size(self, value: Value) -> Self;
+        fn set_size(&self, value: Value) -> &Self;
+    }
+    pub trait SprayInput<Value> {
+        fn with_spray(self, value: Value) -> Self;
+        fn set_spray(&self, value: Value) -> &Self;
+    }
+    pub trait StartInput<Value> {
+        fn with_start(self, value: Value) -> Self;
+        fn set_start(&self, value: Value) -> &Self;
+    }
+    pub trait SustainInput<Value> {
+        fn with_sustain(self, value: Value) -> Self;
+        fn set_sustain(&self, value: Value) -> &Self;
+    }
     pub trait TargetInput<Value> {
         fn with_target(self, value: Value) -> Self;
         fn set_target(&self, value: Value) -> &Self;
     }
+    pub trait ThresholdInput<Value> {
+        fn with_threshold(self, value: Value) -> Self;
+        fn set_threshold(&self, value: Value) -> &Self;
+    }
     pub trait ValueInput<Value> {
         fn with_value(self, value: Value) -> Self;
         fn set_value(&self, value: Value) -> &Self;
@@ -618,6 +815,10 @@ pub mod input {
         fn with_w(self, value: Value) -> Self;
         fn set_w(&self, value: Value) -> &Self;
     }
+    pub trait WindowInput<Value> {
+        fn with_window(self, value: Value) -> Self;
+        fn set_window(&self, value: Value) -> &Self;
+    }
     pub trait XInput<Value> {
         fn with_x(self, value: Value) -> Self;
         fn set_x(&self, value: Value) -> &Self;
@@ -993,6 +1194,92 @@ mod api {
                 reset: Trigger<1>,
             }
         );
+
+        define_processor!(
+            #[doc = " Plays a slice of a buffer at a variable rate\n\n `start` and `end` are in seconds, or in samples when `samples` is set. An `end` of zero plays\n to the end of the buffer. Negative rates play the slice in reverse. When `looped` is set, the\n last `crossfade` seconds before the loop point are faded into the audio leading up to the\n other side of the slice, limited to how much of that audio is in the buffer.\n"]
+            #[id = 501]
+            #[lower = sampler]
+            struct Sampler {
+                #[buffer]
+                #[trait = BufferInput]
+                #[with = with_buffer]
+                #[set = set_buffer]
+                buffer: Buffer<0>,
+                #[trait = RateInput]
+                #[with = with_rate]
+                #[set = set_rate]
+                rate: Parameter<0>,
+                #[trait = StartInput]
+                #[with = with_start]
+                #[set = set_start]
+                start: Parameter<1>,
+                #[trait = EndInput]
+                #[with = with_end]
+                #[set = set_end]
+                end: Parameter<2>,
+                #[trait = CrossfadeInput]
+                #[with = with_crossfade]
+                #[set = set_crossfade]
+                crossfade: Parameter<3>,
+                #[trait = LoopedInput]
+                #[with = with_looped]
+                #[set = set_looped]
+                looped: Trigger<4>,
+                #[trait = SamplesInput]
+                #[with = with_samples]
+                #[set = set_samples]
+                samples: Trigger<5>,
+                #[trait = ResetInput]
+                #[with = with_reset]
+                #[set = set_reset]
+                reset: Trigger<6>,
+            }
+        );
+
+        define_processor!(
+            #[doc = " Granular synthesis over a buffer\n\n Grains of `size` seconds are started `density` times per second at `position`, a fraction of\n the buffer's length, and played back at `pitch`. `spray` randomly offsets each grain's start\n by up to that many seconds and `jitter` randomizes the time between grains as a fraction of\n the interval. `window` tapers the grains, from a rectangle at 0 to a Hann window at 1.\n"]
+            #[id = 502]
+            #[lower = granular]
+            struct Granular {
+                #[buffer]
+                #[trait = BufferInput]
+                #[with = with_buffer]
+                #[set = set_buffer]
+                buffer: Buffer<0>,
+                #[trait = PositionInput]
+                #[with = with_position]
+                #[set = set_position]
+                position: Parameter<0>,
+                #[trait = SizeInput]
+                #[with = with_size]
+                #[set = set_size]
+                size: Parameter<1>,
+                #[trait = DensityInput]
+                #[with = with_density]
+                #[set = set_density]
+                density: Parameter<2>,
+                #[trait = PitchInput]
+                #[with = with_pitch]
+                #[set = set_pitch]
+                pitch: Parameter<3>,
+                #[trait = SprayInput]
+                #[with = with_spray]
+                #[set = set_spray]
+                spray: Parameter<4>,
+                #[trait = JitterInput]
+                #[with = with_jitter]
+                #[set = set_jitter]
+                jitter: Parameter<5>,
+                #[trait = WindowInput]
+                #[with = with_window]
+                #[set = set_window]
+                window: Parameter<6>,
+                #[trait = SeedInput]
+                #[with = with_seed]
+                #[set = set_seed]
+                seed: Trigger<7>,
+            }
+        );
     }
     pub mod delay {
         define_processor!(
@@ -1011,6 +1298,159 @@ mod api {
             }
         );
     }
+    pub mod dynamics {
+        define_processor!(
+            #[doc = " Reduces the level of the signal above a threshold\n\n `threshold`, `knee` and `makeup` are in dB, `ratio` is the input change for each dB of output\n change, and `attack` and `release` are in seconds. When `sidechain` is connected to another\n node, its level drives the compression instead of the signal's.\n"]
+            #[id = 270]
+            #[lower = compressor]
+            struct Compressor {
+                #[trait = SignalInput]
+                #[with = with_signal]
+                #[set = set_signal]
+                signal: Parameter<0>,
+                #[trait = SidechainInput]
+                #[with = with_sidechain]
+                #[set = set_sidechain]
+                sidechain: Parameter<1>,
+                #[trait = ThresholdInput]
+                #[with = with_threshold]
+                #[set = set_threshold]
+                threshold: Parameter<2>,
+                #[trait = RatioInput]
+                #[with = with_ratio]
+                #[set = set_ratio]
+                ratio: Parameter<3>,
+                #[trait = AttackInput]
+                #[with = with_attack]
+                #[set = set_attack]
+                attack: Parameter<4>,
+                #[trait = ReleaseInput]
+                #[with = with_release]
+                #[set = set_release]
+                release: Parameter<5>,
+                #[trait = KneeInput]
+                #[with = with_knee]
+                #[set = set_knee]
+                knee: Parameter<6>,
+                #[trait = MakeupInput]
+                #[with = with_makeup]
+                #[set = set_makeup]
+                makeup: Parameter<7>,
+            }
+        );
+
+        define_processor!(
+            #[doc = " Keeps the level of the signal under a threshold\n\n With the default `attack` of zero and no `knee`, the signal never goes over `threshold` before\n `makeup` is applied. When `sidechain` is connected to another node, its level drives the\n limiting instead of the signal's.\n"]
+            #[id = 271]
+            #[lower = limiter]
+            struct Limiter {
+                #[trait = SignalInput]
+                #[with = with_signal]
+                #[set = set_signal]
+                signal: Parameter<0>,
+                #[trait = SidechainInput]
+                #[with = with_sidechain]
+                #[set = set_sidechain]
+                sidechain: Parameter<1>,
+                #[trait = ThresholdInput]
+                #[with = with_threshold]
+                #[set = set_threshold]
+                threshold: Parameter<2>,
+                #[trait = AttackInput]
+                #[with = with_attack]
+                #[set = set_attack]
+                attack: Parameter<3>,
+                #[trait = ReleaseInput]
+                #[with = with_release]
+                #[set = set_release]
+                release: Parameter<4>,
+                #[trait = KneeInput]
+                #[with = with_knee]
+                #[set = set_knee]
+                knee: Parameter<5>,
+                #[trait = MakeupInput]
+                #[with = with_makeup]
+                #[set = set_makeup]
+                makeup: Parameter<6>,
+            }
+        );
+
+        define_processor!(
+            #[doc = " Silences the signal below a threshold\n\n `attack` is how long the gate takes to open and `release` is how long it takes to close. When\n `sidechain` is connected to another node, its level opens the gate instead of the signal's.\n"]
+            #[id = 272]
+            #[lower = gate]
+            struct Gate {
+                #[trait = SignalInput]
+                #[with = with_signal]
+                #[set = set_signal]
+                signal: Parameter<0>,
+                #[trait = SidechainInput]
+                #[with = with_sidechain]
+                #[set = set_sidechain]
+                sidechain: Parameter<1>,
+                #[trait = ThresholdInput]
+                #[with = with_threshold]
+                #[set = set_threshold]
+                threshold: Parameter<2>,
+                #[trait = AttackInput]
+                #[with = with_attack]
+                #[set = set_attack]
+                attack: Parameter<3>,
+                #[trait = ReleaseInput]
+                #[with = with_release]
+                #[set = set_release]
+                release: Parameter<4>,
+                #[trait = KneeInput]
+                #[with = with_knee]
+                #[set = set_knee]
+                knee: Parameter<5>,
+                #[trait = MakeupInput]
+                #[with = with_makeup]
+                #[set = set_makeup]
+                makeup: Parameter<6>,
+            }
+        );
+
+        define_processor!(
+            #[doc = " Reduces the level of the signal below a threshold\n\n Each dB the level falls under `threshold` lowers the output by `ratio` dB. When `sidechain` is\n connected to another node, its level drives the expansion instead of the signal's.\n"]
+            #[id = 273]
+            #[lower = expander]
+            struct Expander {
+                #[trait = SignalInput]
+                #[with = with_signal]
+                #[set = set_signal]
+                signal: Parameter<0>,
+                #[trait = SidechainInput]
+                #[with = with_sidechain]
+                #[set = set_sidechain]
+                sidechain: Parameter<1>,
+                #[trait = ThresholdInput]
+                #[with = with_threshold]
+                #[set = set_threshold]
+                threshold: Parameter<2>,
+                #[trait = RatioInput]
+                #[with = with_ratio]
+                #[set = set_ratio]
+                ratio: Parameter<3>,
+                #[trait = AttackInput]
+                #[with = with_attack]
+                #[set = set_attack]
+                attack: Parameter<4>,
+                #[trait = ReleaseInput]
+                #[with = with_release]
+                #[set = set_release]
+                release: Parameter<5>,
+                #[trait = KneeInput]
+                #[with = with_knee]
+                #[set = set_knee]
+                knee: Parameter<6>,
+                #[trait = MakeupInput]
+                #[with = with_makeup]
+                #[set = set_makeup]
+                makeup: Parameter<7>,
+            }
+        );
+    }
     pub mod env {
         define_processor!(
             #[id = 200]
@@ -1030,6 +1470,66 @@ mod api {
                 value: Trigger<2>,
             }
         );
+
+        define_processor!(
+            #[doc = " Attack, decay, sustain and release envelope\n\n Opening the `gate` ramps from `start` to `peak` over `attack` seconds, then to `sustain` over\n `decay` seconds, where it stays until the gate is closed. Closing the gate ramps to `end` over\n `release` seconds. Opening or closing the gate again ramps from the current value, while\n `retrigger` restarts the attack from `start`. Negative stage lengths are rejected.\n\n Each stage's curve bends the ramp: 0 is linear, positive values start slowly and negative\n values start quickly. `shape` selects the exponential (0) or power (1) family of curves.\n"]
+            #[id = 201]
+            #[lower = adsr]
+            struct Adsr {
+                #[trait = StartInput]
+                #[with = with_start]
+                #[set = set_start]
+                start: Parameter<0>,
+                #[trait = AttackInput]
+                #[with = with_attack]
+                #[set = set_attack]
+                attack: Parameter<1>,
+                #[trait = PeakInput]
+                #[with = with_peak]
+                #[set = set_peak]
+                peak: Parameter<2>,
+                #[trait = DecayInput]
+                #[with = with_decay]
+                #[set = set_decay]
+                decay: Parameter<3>,
+                #[trait = SustainInput]
+                #[with = with_sustain]
+                #[set = set_sustain]
+                sustain: Parameter<4>,
+                #[trait = ReleaseInput]
+                #[with = with_release]
+                #[set = set_release]
+                release: Parameter<5>,
+                #[trait = EndInput]
+                #[with = with_end]
+                #[set = set_end]
+                end: Parameter<6>,
+                #[trait = AttackCurveInput]
+                #[with = with_attack_curve]
+                #[set = set_attack_curve]
+                attack_curve: Parameter<7>,
+                #[trait = DecayCurveInput]
+                #[with = with_decay_curve]
+                #[set = set_decay_curve]
+                decay_curve: Parameter<8>,
+                #[trait = ReleaseCurveInput]
+                #[with = with_release_curve]
+                #[set = set_release_curve]
+                release_curve: Parameter<9>,
+                #[trait = ShapeInput]
+                #[with = with_shape]
+                #[set = set_shape]
+                shape: Trigger<10>,
+                #[trait = GateInput]
+                #[with = with_gate]
+                #[set = set_gate]
+                gate: Trigger<11>,
+                #[trait = RetriggerInput]
+                #[with = with_retrigger]
+                #[set = set_retrigger]
+                retrigger: Trigger<12>,
+            }
+        );
     }
     pub mod filter {
         define_processor!(
@@ -1739,6 +2239,84 @@ mod api {
             );
         }
     }
+    pub mod reverb {
+        define_processor!(
+            #[doc = " Schroeder-style algorithmic reverb using the Freeverb tuning\n\n `size` and `damping` range from 0 to 1, `predelay` is in seconds, and `mix` blends the dry\n signal with the reverb.\n"]
+            #[id = 260]
+            #[lower = freeverb]
+            struct Freeverb {
+                #[trait = SignalInput]
+                #[with = with_signal]
+                #[set = set_signal]
+                signal: Parameter<0>,
+                #[trait = SizeInput]
+                #[with = with_size]
+                #[set = set_size]
+                size: Parameter<1>,
+                #[trait = DampingInput]
+                #[with = with_damping]
+                #[set = set_damping]
+                damping: Parameter<2>,
+                #[trait = PredelayInput]
+                #[with = with_predelay]
+                #[set = set_predelay]
+                predelay: Parameter<3>,
+                #[trait = MixInput]
+                #[with = with_mix]
+                #[set = set_mix]
+                mix: Parameter<4>,
+            }
+        );
+
+        define_processor!(
+            #[doc = " Feedback delay network reverb with eight lines mixed by a Hadamard matrix\n\n `size` ranges from 0 to 1 and sets the decay time from 0.1 to 10 seconds. `damping` ranges from\n 0 to 1 and darkens the tail, `predelay` is in seconds, and `mix` blends the dry signal with the\n reverb.\n"]
+            #[id = 261]
+            #[lower = fdn]
+            struct Fdn {
+                #[trait = SignalInput]
+                #[with = with_signal]
+                #[set = set_signal]
+                signal: Parameter<0>,
+                #[trait = SizeInput]
+                #[with = with_size]
+                #[set = set_size]
+                size: Parameter<1>,
+                #[trait = DampingInput]
+                #[with = with_damping]
+                #[set = set_damping]
+                damping: Parameter<2>,
+                #[trait = PredelayInput]
+                #[with = with_predelay]
+                #[set = set_predelay]
+                predelay: Parameter<3>,
+                #[trait = MixInput]
+                #[with = with_mix]
+                #[set = set_mix]
+                mix: Parameter<4>,
+            }
+        );
+
+        define_processor!(
+            #[doc = " Convolves the signal with an impulse response\n\n The response is split into partitions and convolved in the frequency domain, so long rooms\n stay cheap. `mix` blends the dry signal with the convolved one.\n"]
+            #[id = 262]
+            #[lower = convolution]
+            struct Convolution {
+                #[buffer]
+                #[trait = ImpulseInput]
+                #[with = with_impulse]
+                #[set = set_impulse]
+                impulse: Buffer<0>,
+                #[trait = SignalInput]
+                #[with = with_signal]
+                #[set = set_signal]
+                signal: Parameter<0>,
+                #[trait = MixInput]
+                #[with = with_mix]
+                #[set = set_mix]
+                mix: Parameter<1>,
+            }
+        );
+    }
     pub mod tertiary {
         define_processor!(
             #[doc = " Fused multiply-add. Computes `(input + add) * mul` with only one rounding\n error, yielding a more accurate result than an unfused add-multiply.\n"]
@@ -13,6 +13,17 @@ pub fn section<T: DelayExt>(time: T) -> Section {
     Section::new(time.delay())
 }
 
+/// Starts a section and marks it in the timeline with `name`
+pub fn named<T: DelayExt>(name: &str, time: T) -> Section {
+    marker(name);
+    section(time)
+}
+
+/// Marks the current position in the timeline with `name`
+pub fn marker(name: &str) {
+    crate::output::marker(name);
+}
+
 pub struct Section {
     handles: Vec<JoinHandle<()>>,
     timer: Timer,
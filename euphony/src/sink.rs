@@ -10,6 +10,7 @@ static SINK: Definition = Definition {
 pub struct Sink(Node);
 
 impl Default for Sink {
+    #[track_caller]
     fn default() -> Self {
         let group = crate::group::current();
         Self(Node::new(&SINK, Some(group.as_u64())))
@@ -18,12 +19,14 @@ impl Default for Sink {
 
 impl Sink {
     #[inline]
+    #[track_caller]
     pub fn with<T: Into<Parameter>>(self, out: T) -> Self {
         self.0.set(0, out);
         self
     }
 
     #[inline]
+    #[track_caller]
     pub fn set<T: Into<Parameter>>(&self, out: T) -> &Self {
         self.0.set(0, out);
         self
@@ -37,12 +40,14 @@ impl Sink {
 
 impl<V: Into<Parameter>> input::AzimuthInput<V> for Sink {
     #[inline]
+    #[track_caller]
     fn with_azimuth(self, value: V) -> Self {
         self.0.set(1, value);
         self
     }
 
     #[inline]
+    #[track_caller]
     fn set_azimuth(&self, value: V) -> &Self {
         self.0.set(1, value);
         self
@@ -51,12 +56,14 @@ impl<V: Into<Parameter>> input::AzimuthInput<V> for Sink {
 
 impl<V: Into<Parameter>> input::InclineInput<V> for Sink {
     #[inline]
+    #[track_caller]
     fn with_incline(self, value: V) -> Self {
         self.0.set(2, value);
         self
     }
 
     #[inline]
+    #[track_caller]
     fn set_incline(&self, value: V) -> &Self {
         self.0.set(2, value);
         self
@@ -65,12 +72,14 @@ impl<V: Into<Parameter>> input::InclineInput<V> for Sink {
 
 impl<V: Into<Parameter>> input::RadiusInput<V> for Sink {
     #[inline]
+    #[track_caller]
     fn with_radius(self, value: V) -> Self {
         self.0.set(3, value);
         self
     }
 
     #[inline]
+    #[track_caller]
     fn set_radius(&self, value: V) -> &Self {
         self.0.set(3, value);
         self
@@ -51,6 +51,19 @@ fn tempo_test() {
     })
 }
 
+#[test]
+fn tempo_ramp_test() {
+    start("tempo_ramp_test", async {
+        set_tempo(Tempo(60, 1));
+        ramp_tempo(Tempo(120, 1), Beat(4, 1));
+        for _ in 0..8 {
+            let s = osc::sine().with_frequency(now().as_f64());
+            Beat(1, 1).delay().await;
+            s.fin()
+        }
+    })
+}
+
 #[test]
 fn rand_test() {
     start("rand_test", async {
@@ -109,3 +122,15 @@ fn scheduler_test() {
         .spawn_primary();
     })
 }
+
+#[test]
+fn markers_test() {
+    start("markers_test", async {
+        set_time_signature((3, 4));
+        marker("intro");
+        Beat(3, 1).delay().await;
+        set_time_signature((6, 8));
+        euphony::section::named("verse", Beat(3, 1)).await;
+        marker("end");
+    })
+}
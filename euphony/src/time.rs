@@ -1,4 +1,4 @@
-use crate::units::time::{Beat, Tempo};
+use crate::units::time::{Beat, Tempo, TimeSignature};
 
 pub(crate) use bach::time::scheduler::{self, Scheduler};
 
@@ -12,6 +12,12 @@ mod tempo {
     bach::scope::define!(scope, Tempo);
 }
 
+mod time_signature {
+    use super::*;
+
+    bach::scope::define!(scope, TimeSignature);
+}
+
 pub fn tempo() -> Tempo {
     tempo::scope::try_borrow_with(|t| t.unwrap_or(Tempo::DEFAULT))
 }
@@ -30,6 +36,30 @@ pub fn set_tempo(tempo: Tempo) -> Tempo {
     tempo::scope::set(Some(tempo)).unwrap_or(Tempo::DEFAULT)
 }
 
+/// Linearly changes the tick duration to match `tempo` over the next `beats`
+///
+/// Returns the previous tempo. [`tempo`] reports the target tempo as soon as the ramp starts.
+pub fn ramp_tempo(tempo: Tempo, beats: Beat) -> Tempo {
+    let beats_per_tick = beats_per_tick();
+    let duration = tempo * beats_per_tick;
+    let ticks = (beats / beats_per_tick).whole();
+    crate::output::ramp_timing(duration, ticks);
+    tempo::scope::set(Some(tempo)).unwrap_or(Tempo::DEFAULT)
+}
+
+pub fn time_signature() -> TimeSignature {
+    time_signature::scope::try_borrow_with(|t| t.unwrap_or_default())
+}
+
+/// Sets the time signature, returning the previous one
+///
+/// The time signature is recorded in the timeline but doesn't change how beats are scheduled.
+pub fn set_time_signature<T: Into<TimeSignature>>(time_signature: T) -> TimeSignature {
+    let time_signature = time_signature.into();
+    crate::output::set_time_signature(time_signature.0, time_signature.1);
+    time_signature::scope::set(Some(time_signature)).unwrap_or_default()
+}
+
 pub fn delay(beats: Beat) -> Timer {
     scheduler::scope::borrow_with(|handle| {
         let ticks = beats / beats_per_tick();